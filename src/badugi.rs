@@ -0,0 +1,177 @@
+//! Badugi, the four card lowball draw game.
+//!
+//! A hand plays only the cards that have different ranks and different suits from each
+//! other. Four such cards make a "badugi", which beats any three card hand, which beats
+//! any two card hand. Aces are low, so hands of the same size are compared on their
+//! highest card, then the next highest and so on, and the lowest hand wins: A-2-3-4 of
+//! four different suits is the best possible hand.
+
+use crate::cards::{parse_cards, Card, Ranks};
+use crate::error::PokerHandError;
+use crate::poker_hand::rank_tiers;
+use std::cmp::Ordering;
+
+/// The rank of a card with aces low.
+fn low_rank(card: &Card) -> u8 {
+    match card.rank {
+        Ranks::Ace => 1,
+        rank => rank as u8,
+    }
+}
+
+/// A Badugi hand.
+///
+/// Hands compare by value, so a better hand is greater, and hands with the same ranks are
+/// equal whatever the suits.
+#[derive(Debug, Clone)]
+pub struct BadugiHand {
+    /// The cards that play, highest first with aces low.
+    pub cards: Vec<Card>,
+    /// The cards that do not play.
+    pub dead: Vec<Card>,
+}
+
+impl BadugiHand {
+    /// Parse a hand such as "AS 2H 3D KC".
+    pub fn new(hand: &str) -> Result<BadugiHand, PokerHandError> {
+        BadugiHand::from_cards(&parse_cards(hand)?)
+    }
+
+    pub fn from_cards(cards: &[Card]) -> Result<BadugiHand, PokerHandError> {
+        if cards.len() != 4 {
+            return Err(PokerHandError::new("A badugi hand must have four cards"));
+        }
+        for (i, card) in cards.iter().enumerate() {
+            if cards[i + 1..].contains(card) {
+                return Err(PokerHandError::new("Duplicate cards in hand"));
+            }
+        }
+        // Each subset of the card positions whose cards have distinct ranks and suits is
+        // a candidate.
+        let mut best: Option<BadugiHand> = None;
+        for mask in 1u32..16 {
+            let playing: Vec<Card> = (0..4)
+                .filter(|i| mask & (1 << i) != 0)
+                .map(|i| cards[i])
+                .collect();
+            let distinct = playing.iter().enumerate().all(|(i, a)| {
+                playing[i + 1..]
+                    .iter()
+                    .all(|b| a.rank != b.rank && a.suit != b.suit)
+            });
+            if !distinct {
+                continue;
+            }
+            let mut candidate = BadugiHand {
+                cards: playing,
+                dead: (0..4)
+                    .filter(|i| mask & (1 << i) == 0)
+                    .map(|i| cards[i])
+                    .collect(),
+            };
+            candidate
+                .cards
+                .sort_by_key(|card| std::cmp::Reverse(low_rank(card)));
+            if best.as_ref().map_or(true, |best| candidate > *best) {
+                best = Some(candidate);
+            }
+        }
+        Ok(best.unwrap())
+    }
+
+    /// The number of cards that play. Four makes a badugi.
+    pub fn size(&self) -> usize {
+        self.cards.len()
+    }
+
+    pub fn is_badugi(&self) -> bool {
+        self.size() == 4
+    }
+}
+
+impl Ord for BadugiHand {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let ranks = |hand: &BadugiHand| hand.cards.iter().map(low_rank).collect::<Vec<u8>>();
+        // More cards is better, then lower ranks are better.
+        self.size()
+            .cmp(&other.size())
+            .then_with(|| ranks(other).cmp(&ranks(self)))
+    }
+}
+
+impl PartialEq for BadugiHand {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for BadugiHand {}
+
+impl PartialOrd for BadugiHand {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Given a list of Badugi hands, return those which win, as `winning_hands` does for
+/// poker hands.
+pub fn winning_badugi_hands<'a>(hands: &[&'a str]) -> Option<Vec<&'a str>> {
+    ranked_badugi_hands(hands).into_iter().next()
+}
+
+/// Given a list of Badugi hands, return them grouped into tiers of equal value, best
+/// first. Invalid hands are ignored.
+pub fn ranked_badugi_hands<'a>(hands: &[&'a str]) -> Vec<Vec<&'a str>> {
+    let parsed: Vec<(&'a str, BadugiHand)> = hands
+        .iter()
+        .filter_map(|&hand| BadugiHand::new(hand).ok().map(|parsed| (hand, parsed)))
+        .collect();
+    rank_tiers(&parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hand(cards: &str) -> BadugiHand {
+        BadugiHand::new(cards).unwrap()
+    }
+
+    #[test]
+    fn test_playing_cards() {
+        assert!(hand("AS 2H 3D 4C").is_badugi());
+        // A pair or two cards of one suit leave three cards.
+        assert_eq!(hand("AS AH 3D 4C").size(), 3);
+        assert_eq!(hand("AS 2S 3D 4C").size(), 3);
+        // The best three cards are kept.
+        let three = hand("KS 2S 3D 4C");
+        assert_eq!(three.cards[0].rank, Ranks::Four);
+        assert_eq!(three.dead[0].rank, Ranks::King);
+        assert_eq!(hand("AS 2S 3S 4C").size(), 2);
+        assert_eq!(hand("AS 2S 3S 4S").size(), 1);
+        assert!(BadugiHand::new("AS 2H 3D").is_err());
+        assert!(BadugiHand::new("AS 2H 3D 3D").is_err());
+    }
+
+    #[test]
+    fn test_ordering() {
+        // Any badugi beats any three card hand.
+        assert!(hand("KS QH JD 10C") > hand("AS 2S 3D 4C"));
+        // Aces are low, and the highest card decides first.
+        assert!(hand("AS 2H 3D 4C") > hand("2S 3H 4D 5C"));
+        assert!(hand("AS 2H 3D 8C") > hand("AS 2H 7D 9C"));
+        assert_eq!(hand("AS 2H 3D 4C"), hand("AH 2S 3C 4D"));
+    }
+
+    #[test]
+    fn test_showdown() {
+        let hands = ["AS 2H 3D 4C", "AH 2S 3C 4D", "KS QH JD 10C", "AD 2D 3H 4S"];
+        assert_eq!(
+            winning_badugi_hands(&hands),
+            Some(vec!["AS 2H 3D 4C", "AH 2S 3C 4D"])
+        );
+        let tiers = ranked_badugi_hands(&hands);
+        assert_eq!(tiers[1], vec!["KS QH JD 10C"]);
+        assert_eq!(winning_badugi_hands(&[]), None);
+    }
+}
@@ -0,0 +1,124 @@
+//! Evaluating large batches of hands at once.
+//!
+//! Stored showdowns are evaluated in bulk, so the batch functions take a whole slice of
+//! hand strings or card sets and return one `HandValue` per hand, which gives both the
+//! hand's classification, through `HandValue::hand_rank`, and its strength, as a single
+//! comparable number. An invalid hand gets an error in its place without stopping the rest
+//! of the batch.
+//!
+//! With the `rayon` feature enabled the hands are evaluated in parallel on rayon's thread
+//! pool. Either way the results are returned in the same order as the hands were given, so
+//! the output is the same whether or not the feature is enabled.
+
+use crate::cards::{parse_hand, Card};
+use crate::error::PokerHandError;
+use crate::evaluator::{evaluate, HandValue};
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+/// Evaluate hand strings in the format accepted by `PokerHand::new`, such as "4S 5H 4C 8D 4H".
+pub fn evaluate_hand_strs(hands: &[&str]) -> Vec<Result<HandValue, PokerHandError>> {
+    map_in_order(hands, |hand| {
+        let cards = parse_hand::<5>(hand.as_bytes())?;
+        evaluate(&cards)
+    })
+}
+
+/// Evaluate the best five card hand from each set of five to seven cards.
+pub fn evaluate_card_sets<C>(hands: &[C]) -> Vec<Result<HandValue, PokerHandError>>
+where
+    C: AsRef<[Card]> + Sync,
+{
+    map_in_order(hands, |cards| evaluate(cards.as_ref()))
+}
+
+/// Apply `f` to every item, in parallel if the `rayon` feature is enabled, keeping the
+/// results in the order of the items.
+#[cfg(feature = "rayon")]
+fn map_in_order<T, R, F>(items: &[T], f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync + Send,
+{
+    items.par_iter().map(f).collect()
+}
+
+#[cfg(not(feature = "rayon"))]
+fn map_in_order<T, R, F>(items: &[T], f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync + Send,
+{
+    items.iter().map(f).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards::parse_cards;
+    use crate::poker_hand::PokerHand;
+    use crate::poker_hand::PokerHandRanks;
+
+    #[test]
+    fn test_evaluate_hand_strs() {
+        let hands = [
+            "4S 5H 4C 8D 4H",
+            "10D JH QS KD AC",
+            "4S 5H 4C 8D",
+            "2S 4C 7S 9H 10H",
+            "4S 4S 4C 8D 4H",
+        ];
+        let results = evaluate_hand_strs(&hands);
+        assert_eq!(results.len(), hands.len());
+        let ranks: Vec<Option<PokerHandRanks>> = results
+            .iter()
+            .map(|result| result.as_ref().ok().map(|value| value.hand_rank()))
+            .collect();
+        assert_eq!(
+            ranks,
+            vec![
+                Some(PokerHandRanks::ThreeOfAKind),
+                Some(PokerHandRanks::Straight),
+                None,
+                Some(PokerHandRanks::HighCard),
+                None,
+            ]
+        );
+        let error = results[2].as_ref().unwrap_err().to_string();
+        assert!(error.ends_with("Expected 5 cards but found 4"));
+        for (hand, result) in hands.iter().zip(&results) {
+            if let Ok(value) = result {
+                let expected = HandValue::from(&PokerHand::new(hand).unwrap());
+                assert_eq!(*value, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_evaluate_card_sets_in_order() {
+        let sets: Vec<Vec<Card>> = (0..200)
+            .map(|i| {
+                (0..7)
+                    .filter_map(|j| Card::from_index((i + 7 * j) % 52))
+                    .collect()
+            })
+            .collect();
+        let results = evaluate_card_sets(&sets);
+        for (cards, result) in sets.iter().zip(results) {
+            assert_eq!(result.unwrap(), evaluate(cards).unwrap());
+        }
+
+        let arrays = [
+            parse_cards("AH KH QH JH 10H").unwrap(),
+            parse_cards("AH KH").unwrap(),
+        ];
+        let results = evaluate_card_sets(&arrays);
+        assert_eq!(
+            results[0].as_ref().unwrap().hand_rank(),
+            PokerHandRanks::StraightFlush
+        );
+        assert!(results[1].is_err());
+    }
+}
@@ -1,9 +1,15 @@
-/// Cards, and methods to determine their relative values.
+//! Cards, and methods to determine their relative values.
+
+use crate::error::{ParseError, PokerHandError};
+use core::fmt;
+use core::str::FromStr;
 
 /// The card suits.
 ///
 /// In poker, suits are not ordered, but we need to be able to compare them.
-#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+/// Where an order is needed, such as awarding odd chips, the declaration order is used:
+/// clubs, diamonds, hearts, then spades as the highest suit.
+#[derive(Debug, Eq, Ord, PartialEq, PartialOrd, Copy, Clone, Hash)]
 pub enum Suits {
     Clubs,
     Diamonds,
@@ -11,10 +17,15 @@ pub enum Suits {
     Spades,
 }
 
+impl Suits {
+    /// All four suits, in declaration order.
+    pub const ALL: [Suits; 4] = [Suits::Clubs, Suits::Diamonds, Suits::Hearts, Suits::Spades];
+}
+
 /// The card ranks.
 ///
 /// The items can be compared using ==, < and > to determine their relative values as used in poker.
-#[derive(Debug, Eq, Ord, PartialEq, PartialOrd, Copy, Clone)]
+#[derive(Debug, Eq, Ord, PartialEq, PartialOrd, Copy, Clone, Hash)]
 pub enum Ranks {
     Two = 2,
     Three = 3,
@@ -31,6 +42,25 @@ pub enum Ranks {
     Ace = 14,
 }
 
+impl Ranks {
+    /// All thirteen ranks, from lowest to highest.
+    pub const ALL: [Ranks; 13] = [
+        Ranks::Two,
+        Ranks::Three,
+        Ranks::Four,
+        Ranks::Five,
+        Ranks::Six,
+        Ranks::Seven,
+        Ranks::Eight,
+        Ranks::Nine,
+        Ranks::Ten,
+        Ranks::Jack,
+        Ranks::Queen,
+        Ranks::King,
+        Ranks::Ace,
+    ];
+}
+
 /// An individual card.
 ///
 /// Cards can be compared using ==, > and <.
@@ -44,6 +74,190 @@ impl Card {
     pub fn new(rank: Ranks, suit: Suits) -> Self {
         Self { rank, suit }
     }
+
+    /// The position of the card in a 52 card deck, from 0 (2C) to 51 (AS).
+    ///
+    /// Cards are ordered by rank first and then by suit, so the index is a dense key
+    /// suitable for lookup tables and bit sets.
+    pub fn index(&self) -> usize {
+        (self.rank as usize - 2) * 4 + self.suit as usize
+    }
+
+    /// The card in the two character notation used by poker sites and the PHH hand history
+    /// format, e.g. "Th" or "As".
+    #[cfg(feature = "std")]
+    pub fn to_short_string(&self) -> String {
+        let rank = match self.rank {
+            Ranks::Ten => 'T',
+            Ranks::Jack => 'J',
+            Ranks::Queen => 'Q',
+            Ranks::King => 'K',
+            Ranks::Ace => 'A',
+            numeric => char::from(b'0' + numeric as u8),
+        };
+        let suit = match self.suit {
+            Suits::Clubs => 'c',
+            Suits::Diamonds => 'd',
+            Suits::Hearts => 'h',
+            Suits::Spades => 's',
+        };
+        format!("{}{}", rank, suit)
+    }
+
+    /// Parse the two character notation, e.g. "Th" or "As". Either case is accepted.
+    #[cfg(feature = "std")]
+    pub fn from_short_str(card: &str) -> Result<Card, PokerHandError> {
+        let upper = card.to_ascii_uppercase();
+        match upper.strip_prefix('T') {
+            Some(suit) if upper.len() == 2 => format!("10{}", suit).parse(),
+            _ if upper.len() == 2 && !upper.starts_with('1') => upper.parse(),
+            _ => Err(PokerHandError::new("Invalid card")),
+        }
+    }
+
+    /// The inverse of `index`. Returns None if the index is not below 52.
+    pub fn from_index(index: usize) -> Option<Card> {
+        if index >= 52 {
+            return None;
+        }
+        Some(Card::new(Ranks::ALL[index / 4], Suits::ALL[index % 4]))
+    }
+}
+
+/// Cards are written in the same notation as the hand strings, e.g. "10H" or "QS".
+impl fmt::Display for Card {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let rank = match self.rank {
+            Ranks::Two => "2",
+            Ranks::Three => "3",
+            Ranks::Four => "4",
+            Ranks::Five => "5",
+            Ranks::Six => "6",
+            Ranks::Seven => "7",
+            Ranks::Eight => "8",
+            Ranks::Nine => "9",
+            Ranks::Ten => "10",
+            Ranks::Jack => "J",
+            Ranks::Queen => "Q",
+            Ranks::King => "K",
+            Ranks::Ace => "A",
+        };
+        let suit = match self.suit {
+            Suits::Clubs => 'C',
+            Suits::Diamonds => 'D',
+            Suits::Hearts => 'H',
+            Suits::Spades => 'S',
+        };
+        write!(f, "{}{}", rank, suit)
+    }
+}
+
+impl FromStr for Card {
+    type Err = PokerHandError;
+
+    fn from_str(card: &str) -> Result<Self, Self::Err> {
+        if card.len() < 2 || !card.is_ascii() {
+            return Err(PokerHandError::new("Invalid card"));
+        }
+        let (rank, suit) = card.split_at(card.len() - 1);
+        let rank = match rank {
+            "2" => Ranks::Two,
+            "3" => Ranks::Three,
+            "4" => Ranks::Four,
+            "5" => Ranks::Five,
+            "6" => Ranks::Six,
+            "7" => Ranks::Seven,
+            "8" => Ranks::Eight,
+            "9" => Ranks::Nine,
+            "10" => Ranks::Ten,
+            "J" => Ranks::Jack,
+            "Q" => Ranks::Queen,
+            "K" => Ranks::King,
+            "A" => Ranks::Ace,
+            _ => return Err(PokerHandError::new("Invalid card rank")),
+        };
+        let suit = match suit {
+            "C" => Suits::Clubs,
+            "D" => Suits::Diamonds,
+            "H" => Suits::Hearts,
+            "S" => Suits::Spades,
+            _ => return Err(PokerHandError::new("Invalid card suit")),
+        };
+        Ok(Card::new(rank, suit))
+    }
+}
+
+/// Parse a space separated list of cards, e.g. "AH KH" or "QH JH 2C".
+///
+/// Any number of cards is accepted, but the same card may not appear twice.
+#[cfg(feature = "std")]
+pub fn parse_cards(cards: &str) -> Result<Vec<Card>, PokerHandError> {
+    let parsed = cards
+        .split_whitespace()
+        .map(Card::from_str)
+        .collect::<Result<Vec<Card>, PokerHandError>>()?;
+    for (i, card) in parsed.iter().enumerate() {
+        if parsed[i + 1..].contains(card) {
+            return Err(PokerHandError::new("Duplicate cards in hand"));
+        }
+    }
+    Ok(parsed)
+}
+
+/// Parse a hand string of exactly `N` cards, such as "4S 5H 4C 8D 4H", without allocating.
+///
+/// This is the format of `PokerHand::new`: ranks 2 to 10, J, Q, K and A followed by the
+/// suits C, D, H and S, in upper case, with a single space between cards. The bytes are
+/// parsed by hand rather than with a regular expression, and the first problem found is
+/// reported with its position. Duplicate cards are not rejected here, as the hand
+/// evaluators check for them.
+pub fn parse_hand<const N: usize>(hand: &[u8]) -> Result<[Card; N], ParseError> {
+    let mut cards = [Card::new(Ranks::Two, Suits::Clubs); N];
+    let mut position = 0;
+    for (found, card) in cards.iter_mut().enumerate() {
+        if found > 0 {
+            match hand.get(position) {
+                Some(b' ') => position += 1,
+                Some(_) => return Err(ParseError::ExpectedSpace { position }),
+                None => return Err(ParseError::TooFewCards { expected: N, found }),
+            }
+        }
+        if position == hand.len() {
+            return Err(ParseError::TooFewCards { expected: N, found });
+        }
+        let (parsed, end) = parse_card_at(hand, position)?;
+        *card = parsed;
+        position = end;
+    }
+    match hand[position..] {
+        [] => Ok(cards),
+        [b' ', ..] if parse_card_at(hand, position + 1).is_ok() => {
+            Err(ParseError::TooManyCards { expected: N })
+        }
+        _ => Err(ParseError::TrailingInput { position }),
+    }
+}
+
+/// Parse one card starting at `position`, returning it and the position after it.
+fn parse_card_at(hand: &[u8], position: usize) -> Result<(Card, usize), ParseError> {
+    let (rank, length) = match hand[position..] {
+        [b'1', b'0', ..] => (Ranks::Ten, 2),
+        [digit @ b'2'..=b'9', ..] => (Ranks::ALL[(digit - b'2') as usize], 1),
+        [b'J', ..] => (Ranks::Jack, 1),
+        [b'Q', ..] => (Ranks::Queen, 1),
+        [b'K', ..] => (Ranks::King, 1),
+        [b'A', ..] => (Ranks::Ace, 1),
+        _ => return Err(ParseError::InvalidRank { position }),
+    };
+    let position = position + length;
+    let suit = match hand.get(position) {
+        Some(b'C') => Suits::Clubs,
+        Some(b'D') => Suits::Diamonds,
+        Some(b'H') => Suits::Hearts,
+        Some(b'S') => Suits::Spades,
+        _ => return Err(ParseError::InvalidSuit { position }),
+    };
+    Ok((Card::new(rank, suit), position + 1))
 }
 
 impl PartialEq for Card {
@@ -53,14 +267,14 @@ impl PartialEq for Card {
 }
 
 impl Ord for Card {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
         self.rank.cmp(&other.rank)
     }
 }
 
 impl PartialOrd for Card {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        self.rank.partial_cmp(&other.rank)
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
     }
 }
 
@@ -99,6 +313,8 @@ mod tests {
         assert!(card1 != card4);
     }
 
+    #[cfg(feature = "std")]
+    #[allow(clippy::useless_vec)]
     #[test]
     fn test_cards_sort() {
         // Confirm that PartialEq is doing the correct behaviour.
@@ -111,4 +327,123 @@ mod tests {
         assert!(cards[0] == card3);
         assert!(cards[3] == card1);
     }
+
+    #[test]
+    fn test_card_index() {
+        assert_eq!(Card::new(Ranks::Two, Suits::Clubs).index(), 0);
+        assert_eq!(Card::new(Ranks::Ace, Suits::Spades).index(), 51);
+        for index in 0..52 {
+            assert_eq!(Card::from_index(index).unwrap().index(), index);
+        }
+        assert!(Card::from_index(52).is_none());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_card_notation() {
+        let card: Card = "10H".parse().unwrap();
+        assert!(card == Card::new(Ranks::Ten, Suits::Hearts));
+        assert_eq!(card.to_string(), "10H");
+        assert_eq!(Card::new(Ranks::Queen, Suits::Spades).to_string(), "QS");
+        assert!("1H".parse::<Card>().is_err());
+        assert!("10X".parse::<Card>().is_err());
+        assert!("H".parse::<Card>().is_err());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_short_notation() {
+        let ten = Card::new(Ranks::Ten, Suits::Hearts);
+        assert_eq!(ten.to_short_string(), "Th");
+        assert_eq!(Card::new(Ranks::Two, Suits::Clubs).to_short_string(), "2c");
+        assert!(Card::from_short_str("Th").unwrap() == ten);
+        assert!(Card::from_short_str("tH").unwrap() == ten);
+        assert!(Card::from_short_str("As").unwrap() == Card::new(Ranks::Ace, Suits::Spades));
+        assert!(Card::from_short_str("10h").is_err());
+        assert!(Card::from_short_str("1h").is_err());
+        assert!(Card::from_short_str("Tx").is_err());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_parse_cards() {
+        let cards = parse_cards("AH KH  QH").unwrap();
+        assert_eq!(cards.len(), 3);
+        assert!(cards[2] == Card::new(Ranks::Queen, Suits::Hearts));
+        assert!(parse_cards("").unwrap().is_empty());
+        assert!(parse_cards("AH AH").is_err());
+        assert!(parse_cards("AH 1H").is_err());
+    }
+
+    #[test]
+    fn test_parse_hand() {
+        let cards: [Card; 5] = parse_hand(b"9H AS JC 10D 5H").unwrap();
+        assert!(cards[0] == Card::new(Ranks::Nine, Suits::Hearts));
+        assert!(cards[3] == Card::new(Ranks::Ten, Suits::Diamonds));
+        assert!(cards[4] == Card::new(Ranks::Five, Suits::Hearts));
+        let hole = parse_hand::<2>(b"QS 2C").unwrap();
+        assert!(
+            hole == [
+                Card::new(Ranks::Queen, Suits::Spades),
+                Card::new(Ranks::Two, Suits::Clubs)
+            ]
+        );
+        assert!(parse_hand::<0>(b"").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_parse_hand_errors() {
+        let error = |hand: &str| parse_hand::<5>(hand.as_bytes()).unwrap_err();
+        assert_eq!(
+            error("9H AS JC 12D 5H"),
+            ParseError::InvalidRank { position: 9 }
+        );
+        assert_eq!(
+            error("9H AS JK 10D 5H"),
+            ParseError::InvalidSuit { position: 7 }
+        );
+        assert_eq!(error("9H AS 10"), ParseError::InvalidSuit { position: 8 });
+        assert_eq!(
+            error("9H ÀS JC 10D 5H"),
+            ParseError::InvalidRank { position: 3 }
+        );
+        assert_eq!(
+            error("9h AS JC 10D 5H"),
+            ParseError::InvalidSuit { position: 1 }
+        );
+        assert_eq!(
+            error("9H  AS JC 10D 5H"),
+            ParseError::InvalidRank { position: 3 }
+        );
+        assert_eq!(
+            error("9HAS JC 10D 5H"),
+            ParseError::ExpectedSpace { position: 2 }
+        );
+        assert_eq!(
+            error("9H AS JC 10D"),
+            ParseError::TooFewCards {
+                expected: 5,
+                found: 4
+            }
+        );
+        assert_eq!(
+            error(""),
+            ParseError::TooFewCards {
+                expected: 5,
+                found: 0
+            }
+        );
+        assert_eq!(
+            error("9H AS JC 10D 5H QS"),
+            ParseError::TooManyCards { expected: 5 }
+        );
+        assert_eq!(
+            error("9H AS JC 10D 5H "),
+            ParseError::TrailingInput { position: 15 }
+        );
+        assert_eq!(
+            error("9H AS JC 10D 5H x"),
+            ParseError::TrailingInput { position: 15 }
+        );
+    }
 }
@@ -0,0 +1,145 @@
+//! Caribbean Stud Poker, the casino table game.
+//!
+//! The player and the dealer each get five cards, ranked as for `PokerHand`. After seeing
+//! their hand, the player bets an ante and either folds or calls with a bet of twice the
+//! ante. The dealer qualifies with ace-king high or better. If the dealer does not qualify,
+//! the ante pays even money and the call is returned. Otherwise the higher hand wins: the
+//! ante pays even money and the call pays according to the player's hand.
+
+use crate::cards::Ranks;
+use crate::poker_hand::{HandDetail, PokerHand, PokerHandRanks};
+use std::cmp::Ordering;
+
+/// Whether a dealer holding the hand qualifies: ace and king, or any pair or better.
+pub fn dealer_qualifies(dealer: &PokerHand) -> bool {
+    match dealer.detail() {
+        HandDetail::HighCard { ranks } => ranks[0] == Ranks::Ace && ranks[1] == Ranks::King,
+        _ => true,
+    }
+}
+
+/// The pays on the call bet, as multiples of the bet, when the player beats a qualifying
+/// dealer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CaribbeanPaytable {
+    /// The pays for each hand type, other than a royal flush.
+    pub pays: Vec<(PokerHandRanks, u64)>,
+    pub royal_flush: u64,
+}
+
+impl CaribbeanPaytable {
+    /// The usual paytable: a pair or high card pays 1, two pair 2, three of a kind 3, a
+    /// straight 4, a flush 5, a full house 7, four of a kind 20, a straight flush 50 and a
+    /// royal flush 100.
+    pub fn standard() -> CaribbeanPaytable {
+        CaribbeanPaytable {
+            pays: vec![
+                (PokerHandRanks::HighCard, 1),
+                (PokerHandRanks::Pair, 1),
+                (PokerHandRanks::TwoPair, 2),
+                (PokerHandRanks::ThreeOfAKind, 3),
+                (PokerHandRanks::Straight, 4),
+                (PokerHandRanks::Flush, 5),
+                (PokerHandRanks::FullHouse, 7),
+                (PokerHandRanks::FourOfAKind, 20),
+                (PokerHandRanks::StraightFlush, 50),
+            ],
+            royal_flush: 100,
+        }
+    }
+
+    /// The pay for the player's hand.
+    pub fn pay(&self, hand: &PokerHand) -> u64 {
+        if hand.detail().is_royal_flush() {
+            return self.royal_flush;
+        }
+        self.pays
+            .iter()
+            .find(|(rank, _)| *rank == hand.hand_rank)
+            .map_or(0, |(_, pay)| *pay)
+    }
+}
+
+/// The player's win (positive) or loss (negative) on each bet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CaribbeanResult {
+    pub ante: i64,
+    pub call: i64,
+}
+
+impl CaribbeanResult {
+    pub fn total(&self) -> i64 {
+        self.ante + self.call
+    }
+}
+
+/// Settle an ante, and a call of twice the ante if the player called, against the dealer.
+pub fn settle(
+    player: &PokerHand,
+    dealer: &PokerHand,
+    ante: u64,
+    called: bool,
+    paytable: &CaribbeanPaytable,
+) -> CaribbeanResult {
+    let ante = ante as i64;
+    if !called {
+        return CaribbeanResult {
+            ante: -ante,
+            call: 0,
+        };
+    }
+    if !dealer_qualifies(dealer) {
+        return CaribbeanResult { ante, call: 0 };
+    }
+    let call = 2 * ante;
+    match player.partial_cmp(dealer) {
+        Some(Ordering::Greater) => CaribbeanResult {
+            ante,
+            call: call * paytable.pay(player) as i64,
+        },
+        Some(Ordering::Less) => CaribbeanResult {
+            ante: -ante,
+            call: -call,
+        },
+        _ => CaribbeanResult::default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hand(cards: &str) -> PokerHand<'_> {
+        PokerHand::new(cards).unwrap()
+    }
+
+    #[test]
+    fn test_dealer_qualification() {
+        assert!(dealer_qualifies(&hand("AS KD 7C 4H 2S")));
+        assert!(!dealer_qualifies(&hand("AS QD 7C 4H 2S")));
+        assert!(dealer_qualifies(&hand("2S 2D 7C 4H 3S")));
+    }
+
+    #[test]
+    fn test_settlement() {
+        let paytable = CaribbeanPaytable::standard();
+        let qualifying = hand("AS KD 7C 4H 2S");
+        let result = settle(&hand("9S 9D 9C 4D 3S"), &qualifying, 10, true, &paytable);
+        assert_eq!(result, CaribbeanResult { ante: 10, call: 60 });
+        let royal = hand("AH KH QH JH 10H");
+        assert_eq!(settle(&royal, &qualifying, 10, true, &paytable).call, 2000);
+        // The dealer does not qualify.
+        let result = settle(&royal, &hand("AS QD 7C 4H 2S"), 10, true, &paytable);
+        assert_eq!(result, CaribbeanResult { ante: 10, call: 0 });
+        // The dealer wins, or the player folds.
+        let result = settle(&hand("AD KS 6C 4S 2H"), &qualifying, 10, true, &paytable);
+        assert_eq!(result.total(), -30);
+        assert_eq!(
+            settle(&royal, &qualifying, 10, false, &paytable).total(),
+            -10
+        );
+        // A tie pushes.
+        let result = settle(&hand("AD KS 7D 4S 2H"), &qualifying, 10, true, &paytable);
+        assert_eq!(result.total(), 0);
+    }
+}
@@ -0,0 +1,1268 @@
+//! Counterfactual regret minimization for small two player poker games.
+//!
+//! Counterfactual regret minimization (CFR) finds approximate Nash equilibria of two player
+//! zero-sum games of imperfect information. Each iteration walks the whole game tree and,
+//! at every information set (what a player can see when they act: their own cards, the
+//! board and the betting so far), accumulates the regret for not having taken each action.
+//! The next strategy plays each action in proportion to its positive regret, and the
+//! average of the strategies converges to an equilibrium.
+//!
+//! CFR+ is the variant which solved heads-up limit hold'em. It floors the regrets at zero
+//! after every update, updates the two players alternately, and weights later iterations
+//! more heavily in the average, which converges far faster in practice.
+//!
+//! Games implement the `Game` trait by describing each node of their tree. The crate
+//! provides Kuhn poker, Leduc hold'em, and limit hold'em river subgames, whose holdings
+//! can be grouped into buckets by a `CardAbstraction` to keep the tree small. How close a
+//! strategy is to an equilibrium is measured by its `exploitability`: the average of what
+//! the two best responses to it win.
+//!
+//! Betting histories in information set keys use one letter per action: x for a check, b
+//! for a bet, c for a call, f for a fold and r for a raise, with a slash between rounds.
+//! Kuhn poker's first player holding the king and facing a bet after checking is at
+//! "K:xb", for example, and the cards are given by rank alone as the suits do not matter.
+//! Payoffs are in chips, from the first player's point of view.
+
+use crate::cards::{Card, Ranks, Suits};
+use crate::error::PokerHandError;
+use crate::evaluator::{evaluate_unchecked, HandValue};
+use crate::strength::{hand_strength, Enumeration};
+use crate::table::Action;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+/// A node of a game tree, with the states it leads to.
+pub enum Node<S> {
+    /// The end of the game, with the first player's winnings.
+    Terminal(f64),
+    /// Cards being dealt, with the probability of each outcome.
+    Chance(Vec<(f64, S)>),
+    /// A player choosing an action.
+    Decision {
+        player: usize,
+        /// Identifies everything the player knows, and must be the same for every node
+        /// the player cannot tell apart, which must offer the same actions.
+        info_set: String,
+        actions: Vec<(Action, S)>,
+    },
+}
+
+/// A two player zero-sum game, described one node at a time.
+pub trait Game {
+    type State;
+
+    /// The state at the start of the game, usually a chance node dealing the cards.
+    fn root(&self) -> Self::State;
+
+    /// The node for a state.
+    fn node(&self, state: &Self::State) -> Node<Self::State>;
+}
+
+/// The variant of CFR used by a `Solver`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CfrVariant {
+    /// The original algorithm, with simultaneous updates and a uniform average.
+    Vanilla,
+    /// Regrets floored at zero, alternating updates and a linearly weighted average.
+    Plus,
+}
+
+/// A strategy for every information set, as the probability of each action.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Strategy {
+    actions: BTreeMap<String, Vec<(Action, f64)>>,
+}
+
+impl Strategy {
+    /// The actions and their probabilities at an information set.
+    pub fn get(&self, info_set: &str) -> Option<&[(Action, f64)]> {
+        self.actions.get(info_set).map(Vec::as_slice)
+    }
+
+    /// The probability of an action at an information set, or `None` if the strategy does
+    /// not cover the information set or the action is not available there.
+    pub fn probability(&self, info_set: &str, action: Action) -> Option<f64> {
+        self.get(info_set)?
+            .iter()
+            .find(|(available, _)| *available == action)
+            .map(|&(_, probability)| probability)
+    }
+
+    /// Set the probabilities of the actions at an information set.
+    pub fn insert(&mut self, info_set: &str, actions: Vec<(Action, f64)>) {
+        self.actions.insert(info_set.to_string(), actions);
+    }
+
+    /// The information sets and their actions, ordered by key.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &[(Action, f64)])> {
+        self.actions
+            .iter()
+            .map(|(info_set, actions)| (info_set.as_str(), actions.as_slice()))
+    }
+
+    /// The number of information sets covered.
+    pub fn len(&self) -> usize {
+        self.actions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.actions.is_empty()
+    }
+}
+
+/// One line per information set, such as "K:xb  fold 0.000  call 1.000".
+impl fmt::Display for Strategy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (info_set, actions) in &self.actions {
+            write!(f, "{}", info_set)?;
+            for (action, probability) in actions {
+                write!(f, "  {} {:.3}", action_name(*action), probability)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+fn action_name(action: Action) -> String {
+    match action {
+        Action::Fold => "fold".to_string(),
+        Action::Check => "check".to_string(),
+        Action::Call => "call".to_string(),
+        Action::Bet(amount) => format!("bet {}", amount),
+        Action::Raise(amount) => format!("raise {}", amount),
+    }
+}
+
+/// The game tree, expanded once so that the iterations do not have to rebuild it.
+struct Tree {
+    nodes: Vec<TreeNode>,
+    info_sets: Vec<InfoSet>,
+}
+
+enum TreeNode {
+    Terminal(f64),
+    Chance(Vec<(f64, usize)>),
+    Decision {
+        player: usize,
+        info_set: usize,
+        children: Vec<usize>,
+    },
+}
+
+struct InfoSet {
+    key: String,
+    player: usize,
+    actions: Vec<Action>,
+    /// The number of the player's own decisions before this one.
+    depth: usize,
+}
+
+impl Tree {
+    fn new<G: Game>(game: &G) -> Tree {
+        let mut tree = Tree {
+            nodes: Vec::new(),
+            info_sets: Vec::new(),
+        };
+        let mut keys = HashMap::new();
+        tree.expand(game, game.root(), [0, 0], &mut keys);
+        tree
+    }
+
+    /// Add the subtree below a state, returning the index of its root.
+    fn expand<G: Game>(
+        &mut self,
+        game: &G,
+        state: G::State,
+        depths: [usize; 2],
+        keys: &mut HashMap<String, usize>,
+    ) -> usize {
+        let node = match game.node(&state) {
+            Node::Terminal(payoff) => TreeNode::Terminal(payoff),
+            Node::Chance(outcomes) => TreeNode::Chance(
+                outcomes
+                    .into_iter()
+                    .map(|(probability, state)| {
+                        (probability, self.expand(game, state, depths, keys))
+                    })
+                    .collect(),
+            ),
+            Node::Decision {
+                player,
+                info_set,
+                actions,
+            } => {
+                let index = *keys.entry(info_set.clone()).or_insert_with(|| {
+                    self.info_sets.push(InfoSet {
+                        key: info_set,
+                        player,
+                        actions: actions.iter().map(|&(action, _)| action).collect(),
+                        depth: depths[player],
+                    });
+                    self.info_sets.len() - 1
+                });
+                let mut depths = depths;
+                depths[player] += 1;
+                let children = actions
+                    .into_iter()
+                    .map(|(_, state)| self.expand(game, state, depths, keys))
+                    .collect();
+                TreeNode::Decision {
+                    player,
+                    info_set: index,
+                    children,
+                }
+            }
+        };
+        self.nodes.push(node);
+        self.nodes.len() - 1
+    }
+
+    fn root(&self) -> usize {
+        // Children are added before their parents, so the root is added last.
+        self.nodes.len() - 1
+    }
+
+    /// The probabilities of each information set's actions under a strategy, playing
+    /// uniformly where the strategy does not cover an information set.
+    fn probabilities(&self, strategy: &Strategy) -> Vec<Vec<f64>> {
+        self.info_sets
+            .iter()
+            .map(|info_set| {
+                let uniform = 1.0 / info_set.actions.len() as f64;
+                info_set
+                    .actions
+                    .iter()
+                    .map(|&action| {
+                        strategy
+                            .probability(&info_set.key, action)
+                            .unwrap_or(uniform)
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// The first player's expected winnings when both players play the probabilities.
+    fn value(&self, node: usize, probabilities: &[Vec<f64>]) -> f64 {
+        match &self.nodes[node] {
+            TreeNode::Terminal(payoff) => *payoff,
+            TreeNode::Chance(outcomes) => outcomes
+                .iter()
+                .map(|&(probability, child)| probability * self.value(child, probabilities))
+                .sum(),
+            TreeNode::Decision {
+                info_set, children, ..
+            } => children
+                .iter()
+                .zip(&probabilities[*info_set])
+                .map(|(&child, probability)| probability * self.value(child, probabilities))
+                .sum(),
+        }
+    }
+
+    /// What the best response of `responder` to the probabilities wins.
+    fn best_response(&self, responder: usize, probabilities: &[Vec<f64>]) -> f64 {
+        // The responder's nodes in each of their information sets, with the probability
+        // that chance and the opponent reach them.
+        let mut reaches: Vec<Vec<(usize, f64)>> = vec![Vec::new(); self.info_sets.len()];
+        self.collect_reaches(self.root(), 1.0, responder, probabilities, &mut reaches);
+
+        // With perfect recall, every information set below one of the responder's has
+        // more of their decisions before it, so deciding the deepest first means the
+        // responses below an information set are known when it is decided.
+        let mut order: Vec<usize> = (0..self.info_sets.len())
+            .filter(|&index| self.info_sets[index].player == responder)
+            .collect();
+        order.sort_by_key(|&index| std::cmp::Reverse(self.info_sets[index].depth));
+        let mut choices: Vec<Option<usize>> = vec![None; self.info_sets.len()];
+        for index in order {
+            let mut best = (f64::NEG_INFINITY, 0);
+            for action in 0..self.info_sets[index].actions.len() {
+                let mut value = 0.0;
+                for &(node, reach) in &reaches[index] {
+                    if let TreeNode::Decision { children, .. } = &self.nodes[node] {
+                        value += reach
+                            * self.response_value(
+                                children[action],
+                                responder,
+                                probabilities,
+                                &choices,
+                            );
+                    }
+                }
+                if value > best.0 {
+                    best = (value, action);
+                }
+            }
+            choices[index] = Some(best.1);
+        }
+        self.response_value(self.root(), responder, probabilities, &choices)
+    }
+
+    fn collect_reaches(
+        &self,
+        node: usize,
+        reach: f64,
+        responder: usize,
+        probabilities: &[Vec<f64>],
+        reaches: &mut [Vec<(usize, f64)>],
+    ) {
+        match &self.nodes[node] {
+            TreeNode::Terminal(_) => {}
+            TreeNode::Chance(outcomes) => {
+                for &(probability, child) in outcomes {
+                    self.collect_reaches(
+                        child,
+                        reach * probability,
+                        responder,
+                        probabilities,
+                        reaches,
+                    );
+                }
+            }
+            TreeNode::Decision {
+                player,
+                info_set,
+                children,
+            } => {
+                if *player == responder {
+                    reaches[*info_set].push((node, reach));
+                }
+                for (action, &child) in children.iter().enumerate() {
+                    let reach = if *player == responder {
+                        reach
+                    } else {
+                        reach * probabilities[*info_set][action]
+                    };
+                    self.collect_reaches(child, reach, responder, probabilities, reaches);
+                }
+            }
+        }
+    }
+
+    /// The responder's winnings below a node, playing the chosen actions.
+    fn response_value(
+        &self,
+        node: usize,
+        responder: usize,
+        probabilities: &[Vec<f64>],
+        choices: &[Option<usize>],
+    ) -> f64 {
+        match &self.nodes[node] {
+            TreeNode::Terminal(payoff) => {
+                if responder == 0 {
+                    *payoff
+                } else {
+                    -payoff
+                }
+            }
+            TreeNode::Chance(outcomes) => outcomes
+                .iter()
+                .map(|&(probability, child)| {
+                    probability * self.response_value(child, responder, probabilities, choices)
+                })
+                .sum(),
+            TreeNode::Decision {
+                player,
+                info_set,
+                children,
+            } => {
+                if *player == responder {
+                    // Deeper information sets are always decided first.
+                    let choice = choices[*info_set].unwrap_or(0);
+                    self.response_value(children[choice], responder, probabilities, choices)
+                } else {
+                    children
+                        .iter()
+                        .zip(&probabilities[*info_set])
+                        .map(|(&child, probability)| {
+                            probability
+                                * self.response_value(child, responder, probabilities, choices)
+                        })
+                        .sum()
+                }
+            }
+        }
+    }
+}
+
+/// The average of what a best response to each player's part of the strategy wins.
+///
+/// This is zero for an equilibrium, and otherwise is how much the strategy can be beaten
+/// by per game, averaged over the two seats. Information sets the strategy does not cover
+/// are played uniformly at random.
+pub fn exploitability<G: Game>(game: &G, strategy: &Strategy) -> f64 {
+    let tree = Tree::new(game);
+    let probabilities = tree.probabilities(strategy);
+    (tree.best_response(0, &probabilities) + tree.best_response(1, &probabilities)) / 2.0
+}
+
+/// The first player's expected winnings when both players play the strategy.
+pub fn game_value<G: Game>(game: &G, strategy: &Strategy) -> f64 {
+    let tree = Tree::new(game);
+    tree.value(tree.root(), &tree.probabilities(strategy))
+}
+
+/// A CFR solver for a game, which keeps its regrets between calls to `solve`.
+pub struct Solver {
+    tree: Tree,
+    variant: CfrVariant,
+    regrets: Vec<Vec<f64>>,
+    strategy_sums: Vec<Vec<f64>>,
+    iterations: usize,
+}
+
+impl Solver {
+    /// A solver for the game, which is expanded into a tree up front.
+    pub fn new<G: Game>(game: &G, variant: CfrVariant) -> Solver {
+        let tree = Tree::new(game);
+        let zeros: Vec<Vec<f64>> = tree
+            .info_sets
+            .iter()
+            .map(|info_set| vec![0.0; info_set.actions.len()])
+            .collect();
+        Solver {
+            tree,
+            variant,
+            regrets: zeros.clone(),
+            strategy_sums: zeros,
+            iterations: 0,
+        }
+    }
+
+    /// Run more iterations.
+    pub fn solve(&mut self, iterations: usize) {
+        for _ in 0..iterations {
+            self.iterations += 1;
+            match self.variant {
+                CfrVariant::Vanilla => self.iterate([true, true], 1.0),
+                CfrVariant::Plus => {
+                    let weight = self.iterations as f64;
+                    self.iterate([true, false], weight);
+                    self.iterate([false, true], weight);
+                }
+            }
+        }
+    }
+
+    /// The number of iterations run so far.
+    pub fn iterations(&self) -> usize {
+        self.iterations
+    }
+
+    /// The number of information sets in the game.
+    pub fn info_sets(&self) -> usize {
+        self.tree.info_sets.len()
+    }
+
+    /// The average strategy, which converges to an equilibrium.
+    pub fn average_strategy(&self) -> Strategy {
+        self.strategy(|index| normalized(&self.strategy_sums[index]))
+    }
+
+    /// The strategy the next iteration plays, from regret matching.
+    pub fn current_strategy(&self) -> Strategy {
+        self.strategy(|index| normalized(&positive(&self.regrets[index])))
+    }
+
+    /// The exploitability of the average strategy.
+    pub fn exploitability(&self) -> f64 {
+        let probabilities = self.tree.probabilities(&self.average_strategy());
+        (self.tree.best_response(0, &probabilities) + self.tree.best_response(1, &probabilities))
+            / 2.0
+    }
+
+    fn strategy<F: Fn(usize) -> Vec<f64>>(&self, probabilities: F) -> Strategy {
+        let mut strategy = Strategy::default();
+        for (index, info_set) in self.tree.info_sets.iter().enumerate() {
+            let actions = info_set
+                .actions
+                .iter()
+                .copied()
+                .zip(probabilities(index))
+                .collect();
+            strategy.insert(&info_set.key, actions);
+        }
+        strategy
+    }
+
+    /// Walk the tree once, updating the regrets of the players being updated, and adding
+    /// their current strategies to the averages with the weight.
+    fn iterate(&mut self, updating: [bool; 2], weight: f64) {
+        let current: Vec<Vec<f64>> = self
+            .regrets
+            .iter()
+            .map(|regrets| normalized(&positive(regrets)))
+            .collect();
+        let mut deltas: Vec<Vec<f64>> = self.regrets.iter().map(|r| vec![0.0; r.len()]).collect();
+        let root = self.tree.root();
+        self.traverse(
+            root,
+            [1.0, 1.0],
+            1.0,
+            &current,
+            updating,
+            weight,
+            &mut deltas,
+        );
+        for (index, deltas) in deltas.into_iter().enumerate() {
+            for (regret, delta) in self.regrets[index].iter_mut().zip(deltas) {
+                *regret += delta;
+                if self.variant == CfrVariant::Plus {
+                    *regret = regret.max(0.0);
+                }
+            }
+        }
+    }
+
+    /// The first player's expected winnings below a node under the current strategies.
+    #[allow(clippy::too_many_arguments)]
+    fn traverse(
+        &mut self,
+        node: usize,
+        reach: [f64; 2],
+        chance: f64,
+        current: &[Vec<f64>],
+        updating: [bool; 2],
+        weight: f64,
+        deltas: &mut [Vec<f64>],
+    ) -> f64 {
+        let (player, info_set, children) = match &self.tree.nodes[node] {
+            TreeNode::Terminal(payoff) => return *payoff,
+            TreeNode::Chance(outcomes) => {
+                let outcomes = outcomes.clone();
+                return outcomes
+                    .into_iter()
+                    .map(|(probability, child)| {
+                        probability
+                            * self.traverse(
+                                child,
+                                reach,
+                                chance * probability,
+                                current,
+                                updating,
+                                weight,
+                                deltas,
+                            )
+                    })
+                    .sum();
+            }
+            TreeNode::Decision {
+                player,
+                info_set,
+                children,
+            } => (*player, *info_set, children.clone()),
+        };
+        let strategy = &current[info_set];
+        let mut values = Vec::with_capacity(children.len());
+        let mut value = 0.0;
+        for (action, &child) in children.iter().enumerate() {
+            let mut child_reach = reach;
+            child_reach[player] *= strategy[action];
+            let child_value = self.traverse(
+                child,
+                child_reach,
+                chance,
+                current,
+                updating,
+                weight,
+                deltas,
+            );
+            value += strategy[action] * child_value;
+            values.push(child_value);
+        }
+        if updating[player] {
+            // Regrets are for the acting player, so the second player's are negated.
+            let sign = if player == 0 { 1.0 } else { -1.0 };
+            let counterfactual_reach = reach[1 - player] * chance;
+            for (action, child_value) in values.into_iter().enumerate() {
+                deltas[info_set][action] += sign * counterfactual_reach * (child_value - value);
+                self.strategy_sums[info_set][action] += weight * reach[player] * strategy[action];
+            }
+        }
+        value
+    }
+}
+
+fn positive(regrets: &[f64]) -> Vec<f64> {
+    regrets.iter().map(|regret| regret.max(0.0)).collect()
+}
+
+/// The weights scaled to sum to one, or uniform if they are all zero.
+fn normalized(weights: &[f64]) -> Vec<f64> {
+    let total: f64 = weights.iter().sum();
+    if total > 0.0 {
+        weights.iter().map(|weight| weight / total).collect()
+    } else {
+        vec![1.0 / weights.len() as f64; weights.len()]
+    }
+}
+
+/// Where a round of limit betting stands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Round {
+    /// A player is still to act.
+    Open,
+    /// The bets are matched, after a call or a second check.
+    Closed,
+    /// The player folded.
+    Folded(usize),
+}
+
+/// The betting in a limit game where the first player acts first in every round.
+#[derive(Debug, Clone)]
+struct LimitBetting {
+    /// Each player's chips in the pot, including antes.
+    total: [u64; 2],
+    /// Each player's chips put in on the current round.
+    round: [u64; 2],
+    to_act: usize,
+    /// The bets and raises made in the current round.
+    bets: u32,
+    status: Round,
+    history: String,
+}
+
+impl LimitBetting {
+    fn new(ante: u64) -> LimitBetting {
+        LimitBetting {
+            total: [ante, ante],
+            round: [0, 0],
+            to_act: 0,
+            bets: 0,
+            status: Round::Open,
+            history: String::new(),
+        }
+    }
+
+    /// The legal actions, with the betting they lead to. Bets and raises are capped at
+    /// `cap` a round.
+    fn actions(&self, bet_size: u64, cap: u32) -> Vec<(Action, LimitBetting)> {
+        let player = self.to_act;
+        let mut actions = Vec::new();
+        if self.round[1 - player] > self.round[player] {
+            actions.push(Action::Fold);
+            actions.push(Action::Call);
+            if self.bets < cap {
+                actions.push(Action::Raise(self.round[1 - player] + bet_size));
+            }
+        } else {
+            actions.push(Action::Check);
+            if self.bets < cap {
+                actions.push(Action::Bet(bet_size));
+            }
+        }
+        actions
+            .into_iter()
+            .map(|action| (action, self.play(action)))
+            .collect()
+    }
+
+    fn play(&self, action: Action) -> LimitBetting {
+        let player = self.to_act;
+        let mut next = self.clone();
+        next.to_act = 1 - player;
+        match action {
+            Action::Fold => {
+                next.history.push('f');
+                next.status = Round::Folded(player);
+            }
+            Action::Check => {
+                next.history.push('x');
+                // The first player always opens the round, so the second closes it.
+                if player == 1 {
+                    next.status = Round::Closed;
+                }
+            }
+            Action::Call => {
+                next.history.push('c');
+                next.put_in(player, self.round[1 - player]);
+                next.status = Round::Closed;
+            }
+            Action::Bet(amount) => {
+                next.history.push('b');
+                next.put_in(player, amount);
+                next.bets += 1;
+            }
+            Action::Raise(amount) => {
+                next.history.push('r');
+                next.put_in(player, amount);
+                next.bets += 1;
+            }
+        }
+        next
+    }
+
+    /// Bring the player's chips in for the round up to the amount.
+    fn put_in(&mut self, player: usize, amount: u64) {
+        self.total[player] += amount - self.round[player];
+        self.round[player] = amount;
+    }
+
+    fn next_round(&self) -> LimitBetting {
+        LimitBetting {
+            round: [0, 0],
+            to_act: 0,
+            bets: 0,
+            status: Round::Open,
+            history: format!("{}/", self.history),
+            ..self.clone()
+        }
+    }
+
+    /// The first player's winnings when the betting is over, given how the first player's
+    /// hand compares at showdown.
+    fn payoff(&self, showdown: impl FnOnce() -> Ordering) -> f64 {
+        let won = match self.status {
+            Round::Folded(player) => player == 1,
+            _ => match showdown() {
+                Ordering::Greater => true,
+                Ordering::Equal => return 0.0,
+                Ordering::Less => false,
+            },
+        };
+        if won {
+            self.total[1] as f64
+        } else {
+            -(self.total[0] as f64)
+        }
+    }
+}
+
+/// A decision node for the player to act.
+fn decision<S>(
+    betting: &LimitBetting,
+    info_set: String,
+    bet_size: u64,
+    cap: u32,
+    state: impl Fn(LimitBetting) -> S,
+) -> Node<S> {
+    Node::Decision {
+        player: betting.to_act,
+        info_set,
+        actions: betting
+            .actions(bet_size, cap)
+            .into_iter()
+            .map(|(action, betting)| (action, state(betting)))
+            .collect(),
+    }
+}
+
+/// Equally likely outcomes.
+fn uniform<S>(outcomes: Vec<S>) -> Node<S> {
+    let probability = 1.0 / outcomes.len() as f64;
+    Node::Chance(
+        outcomes
+            .into_iter()
+            .map(|state| (probability, state))
+            .collect(),
+    )
+}
+
+/// Every way of dealing one card to each player from the deck.
+fn deals(deck: &[Card]) -> Vec<[Card; 2]> {
+    let mut deals = Vec::new();
+    for &first in deck {
+        for &second in deck {
+            if first != second {
+                deals.push([first, second]);
+            }
+        }
+    }
+    deals
+}
+
+/// The letter of a card's rank, such as "K".
+fn rank_letter(card: Card) -> char {
+    card.to_short_string().chars().next().unwrap_or('?')
+}
+
+/// Kuhn poker: three cards, one each, an ante of one and a single bet of one.
+///
+/// The first player's value at equilibrium is −1/18.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct KuhnPoker;
+
+/// A state of a Kuhn poker game.
+#[derive(Debug, Clone)]
+pub struct KuhnState {
+    cards: Option<[Card; 2]>,
+    betting: LimitBetting,
+}
+
+impl KuhnPoker {
+    /// The deck: the jack, queen and king of spades.
+    pub fn deck() -> [Card; 3] {
+        [Ranks::Jack, Ranks::Queen, Ranks::King].map(|rank| Card::new(rank, Suits::Spades))
+    }
+}
+
+impl Game for KuhnPoker {
+    type State = KuhnState;
+
+    fn root(&self) -> KuhnState {
+        KuhnState {
+            cards: None,
+            betting: LimitBetting::new(1),
+        }
+    }
+
+    fn node(&self, state: &KuhnState) -> Node<KuhnState> {
+        let betting = &state.betting;
+        let cards = match state.cards {
+            Some(cards) => cards,
+            None => {
+                return uniform(
+                    deals(&KuhnPoker::deck())
+                        .into_iter()
+                        .map(|cards| KuhnState {
+                            cards: Some(cards),
+                            betting: betting.clone(),
+                        })
+                        .collect(),
+                )
+            }
+        };
+        if betting.status != Round::Open {
+            return Node::Terminal(betting.payoff(|| cards[0].rank.cmp(&cards[1].rank)));
+        }
+        let info_set = format!("{}:{}", rank_letter(cards[betting.to_act]), betting.history);
+        decision(betting, info_set, 1, 1, |betting| KuhnState {
+            cards: Some(cards),
+            betting,
+        })
+    }
+}
+
+/// Leduc hold'em: a six card deck of two jacks, queens and kings, one private card each
+/// and one board card.
+///
+/// Each player antes one. There are two rounds of betting, before and after the board
+/// card, with bets of two and then four and at most a bet and a raise in each round. A
+/// player who pairs the board wins, and otherwise the higher card wins.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LeducHoldem;
+
+/// A state of a Leduc hold'em game.
+#[derive(Debug, Clone)]
+pub struct LeducState {
+    cards: Option<[Card; 2]>,
+    board: Option<Card>,
+    betting: LimitBetting,
+}
+
+impl LeducHoldem {
+    /// The deck: the jacks, queens and kings of spades and hearts.
+    pub fn deck() -> [Card; 6] {
+        let rank = [Ranks::Jack, Ranks::Queen, Ranks::King];
+        let mut deck = [Card::new(Ranks::Jack, Suits::Spades); 6];
+        for (i, card) in deck.iter_mut().enumerate() {
+            let suit = if i % 2 == 0 {
+                Suits::Spades
+            } else {
+                Suits::Hearts
+            };
+            *card = Card::new(rank[i / 2], suit);
+        }
+        deck
+    }
+
+    /// Compare the first player's hand with the second's.
+    fn showdown(cards: [Card; 2], board: Card) -> Ordering {
+        let paired = |card: Card| card.rank == board.rank;
+        paired(cards[0])
+            .cmp(&paired(cards[1]))
+            .then(cards[0].rank.cmp(&cards[1].rank))
+    }
+}
+
+impl Game for LeducHoldem {
+    type State = LeducState;
+
+    fn root(&self) -> LeducState {
+        LeducState {
+            cards: None,
+            board: None,
+            betting: LimitBetting::new(1),
+        }
+    }
+
+    fn node(&self, state: &LeducState) -> Node<LeducState> {
+        let betting = &state.betting;
+        let cards = match state.cards {
+            Some(cards) => cards,
+            None => {
+                return uniform(
+                    deals(&LeducHoldem::deck())
+                        .into_iter()
+                        .map(|cards| LeducState {
+                            cards: Some(cards),
+                            ..state.clone()
+                        })
+                        .collect(),
+                )
+            }
+        };
+        match (betting.status, state.board) {
+            (Round::Folded(_), _) | (Round::Closed, Some(_)) => {
+                let board = state.board;
+                Node::Terminal(betting.payoff(|| LeducHoldem::showdown(cards, board.unwrap())))
+            }
+            (Round::Closed, None) => uniform(
+                LeducHoldem::deck()
+                    .iter()
+                    .filter(|card| !cards.contains(card))
+                    .map(|&board| LeducState {
+                        cards: Some(cards),
+                        board: Some(board),
+                        betting: betting.next_round(),
+                    })
+                    .collect(),
+            ),
+            (Round::Open, board) => {
+                let card = rank_letter(cards[betting.to_act]);
+                let info_set = match board {
+                    Some(board) => {
+                        format!("{} {}:{}", card, rank_letter(board), betting.history)
+                    }
+                    None => format!("{}:{}", card, betting.history),
+                };
+                let bet_size = if board.is_some() { 4 } else { 2 };
+                decision(betting, info_set, bet_size, 2, |betting| LeducState {
+                    cards: Some(cards),
+                    board,
+                    betting,
+                })
+            }
+        }
+    }
+}
+
+/// Groups the holdings of a hold'em subgame into buckets, so that strategies are shared
+/// by the holdings in a bucket and the game has fewer information sets.
+pub trait CardAbstraction {
+    /// The bucket of a holding on the board.
+    fn bucket(&self, hole: [Card; 2], board: &[Card]) -> usize;
+}
+
+/// Every holding in its own bucket.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoAbstraction;
+
+impl CardAbstraction for NoAbstraction {
+    fn bucket(&self, hole: [Card; 2], _board: &[Card]) -> usize {
+        let (high, low) = (
+            hole[0].index().max(hole[1].index()),
+            hole[0].index().min(hole[1].index()),
+        );
+        high * 52 + low
+    }
+}
+
+/// Holdings bucketed by their hand strength against a random holding, in equal steps.
+#[derive(Debug, Clone, Copy)]
+pub struct StrengthBuckets {
+    buckets: usize,
+}
+
+impl StrengthBuckets {
+    /// Split the holdings into this many buckets, which must be at least one.
+    pub fn new(buckets: usize) -> Result<StrengthBuckets, PokerHandError> {
+        if buckets == 0 {
+            return Err(PokerHandError::new("There must be at least one bucket"));
+        }
+        Ok(StrengthBuckets { buckets })
+    }
+
+    pub fn buckets(&self) -> usize {
+        self.buckets
+    }
+}
+
+impl CardAbstraction for StrengthBuckets {
+    fn bucket(&self, hole: [Card; 2], board: &[Card]) -> usize {
+        // Callers pass valid holdings and boards, so the strength is always known.
+        let strength = hand_strength(&hole, board, 1, Enumeration::Exhaustive).unwrap_or(0.0);
+        ((strength * self.buckets as f64) as usize).min(self.buckets - 1)
+    }
+}
+
+/// A limit hold'em river subgame: a round of betting on a complete board between two
+/// ranges of holdings, with a pot already built.
+///
+/// The holdings are dealt uniformly from the ranges, skipping pairs which share a card.
+/// The first player acts first, bets are of a fixed size, and at most `cap` bets and
+/// raises are made. Showdowns are decided by the hand evaluator.
+pub struct HoldemSubgame<A: CardAbstraction> {
+    board: Vec<Card>,
+    ranges: [Vec<[Card; 2]>; 2],
+    pot: u64,
+    bet_size: u64,
+    cap: u32,
+    abstraction: A,
+    /// The bucket and hand value of each holding of each range.
+    holdings: [Vec<(usize, HandValue)>; 2],
+}
+
+/// A state of a hold'em subgame, with the holdings as indices into the ranges.
+#[derive(Debug, Clone)]
+pub struct HoldemSubgameState {
+    holdings: Option<[usize; 2]>,
+    betting: LimitBetting,
+}
+
+impl<A: CardAbstraction> HoldemSubgame<A> {
+    /// A subgame on a five card board, with each player's range of holdings and the pot
+    /// before the betting, which the players contributed equally, so it must be even.
+    pub fn new(
+        board: &[Card],
+        ranges: [Vec<[Card; 2]>; 2],
+        pot: u64,
+        bet_size: u64,
+        cap: u32,
+        abstraction: A,
+    ) -> Result<HoldemSubgame<A>, PokerHandError> {
+        if board.len() != 5 {
+            return Err(PokerHandError::new(
+                "A river subgame needs five board cards",
+            ));
+        }
+        if pot % 2 != 0 {
+            return Err(PokerHandError::new(
+                "The pot must be split evenly between the players",
+            ));
+        }
+        for (i, card) in board.iter().enumerate() {
+            if board[i + 1..].contains(card) {
+                return Err(PokerHandError::new("Duplicate cards in hand"));
+            }
+        }
+        for range in &ranges {
+            if range.is_empty() {
+                return Err(PokerHandError::new("A range needs at least one holding"));
+            }
+            if range
+                .iter()
+                .any(|hole| hole[0] == hole[1] || hole.iter().any(|card| board.contains(card)))
+            {
+                return Err(PokerHandError::new("Duplicate cards in hand"));
+            }
+        }
+        let holdings = [0, 1].map(|player| {
+            ranges[player]
+                .iter()
+                .map(|&hole| {
+                    let cards = [
+                        hole[0], hole[1], board[0], board[1], board[2], board[3], board[4],
+                    ];
+                    (abstraction.bucket(hole, board), evaluate_unchecked(&cards))
+                })
+                .collect()
+        });
+        let subgame = HoldemSubgame {
+            board: board.to_vec(),
+            ranges,
+            pot,
+            bet_size,
+            cap,
+            abstraction,
+            holdings,
+        };
+        if subgame.deals().is_empty() {
+            return Err(PokerHandError::new(
+                "The ranges have no holdings without shared cards",
+            ));
+        }
+        Ok(subgame)
+    }
+
+    /// The information set key of a holding after a betting history, for looking up its
+    /// strategy.
+    pub fn info_set(&self, hole: [Card; 2], history: &str) -> String {
+        format!("{}:{}", self.abstraction.bucket(hole, &self.board), history)
+    }
+
+    fn deals(&self) -> Vec<[usize; 2]> {
+        let mut deals = Vec::new();
+        for (i, ours) in self.ranges[0].iter().enumerate() {
+            for (j, theirs) in self.ranges[1].iter().enumerate() {
+                if !theirs.iter().any(|card| ours.contains(card)) {
+                    deals.push([i, j]);
+                }
+            }
+        }
+        deals
+    }
+}
+
+impl<A: CardAbstraction> Game for HoldemSubgame<A> {
+    type State = HoldemSubgameState;
+
+    fn root(&self) -> HoldemSubgameState {
+        HoldemSubgameState {
+            holdings: None,
+            betting: LimitBetting::new(self.pot / 2),
+        }
+    }
+
+    fn node(&self, state: &HoldemSubgameState) -> Node<HoldemSubgameState> {
+        let betting = &state.betting;
+        let holdings = match state.holdings {
+            Some(holdings) => holdings,
+            None => {
+                return uniform(
+                    self.deals()
+                        .into_iter()
+                        .map(|holdings| HoldemSubgameState {
+                            holdings: Some(holdings),
+                            betting: betting.clone(),
+                        })
+                        .collect(),
+                )
+            }
+        };
+        let [ours, theirs] = [0, 1].map(|player| self.holdings[player][holdings[player]]);
+        if betting.status != Round::Open {
+            return Node::Terminal(betting.payoff(|| ours.1.cmp(&theirs.1)));
+        }
+        let bucket = if betting.to_act == 0 {
+            ours.0
+        } else {
+            theirs.0
+        };
+        let info_set = format!("{}:{}", bucket, betting.history);
+        decision(betting, info_set, self.bet_size, self.cap, |betting| {
+            HoldemSubgameState {
+                holdings: Some(holdings),
+                betting,
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards::parse_cards;
+
+    #[test]
+    fn test_kuhn_poker_equilibrium() {
+        let mut solver = Solver::new(&KuhnPoker, CfrVariant::Plus);
+        assert_eq!(solver.info_sets(), 12);
+        solver.solve(1000);
+        let strategy = solver.average_strategy();
+        assert!(solver.exploitability() < 0.002);
+        assert!((game_value(&KuhnPoker, &strategy) + 1.0 / 18.0).abs() < 0.002);
+
+        // The second player calls a bet with the king and folds the jack, and the first
+        // player bets the king three times as often as they bluff with the jack.
+        let probability = |info_set: &str, action| strategy.probability(info_set, action).unwrap();
+        assert!(probability("K:b", Action::Call) > 0.99);
+        assert!(probability("J:b", Action::Fold) > 0.99);
+        let bluff = probability("J:", Action::Bet(1));
+        let value = probability("K:", Action::Bet(1));
+        assert!((value - 3.0 * bluff).abs() < 0.05);
+        assert!(strategy.to_string().contains("K:b  fold 0.000  call 1.000"));
+    }
+
+    #[test]
+    fn test_vanilla_cfr_converges_more_slowly() {
+        let mut vanilla = Solver::new(&KuhnPoker, CfrVariant::Vanilla);
+        let mut plus = Solver::new(&KuhnPoker, CfrVariant::Plus);
+        let uniform = exploitability(&KuhnPoker, &Strategy::default());
+        vanilla.solve(300);
+        plus.solve(300);
+        assert!(vanilla.exploitability() < 0.02);
+        assert!(plus.exploitability() < vanilla.exploitability());
+        assert!(uniform > 0.4);
+        assert_eq!(plus.iterations(), 300);
+    }
+
+    #[test]
+    fn test_leduc_holdem() {
+        let mut solver = Solver::new(&LeducHoldem, CfrVariant::Plus);
+        assert_eq!(solver.info_sets(), 288);
+        solver.solve(100);
+        assert!(solver.exploitability() < 0.05);
+        // The first player's value at equilibrium is about −0.0856.
+        let value = game_value(&LeducHoldem, &solver.average_strategy());
+        assert!((value + 0.0856).abs() < 0.02);
+        // Nobody folds a pair of kings.
+        let strategy = solver.average_strategy();
+        assert!(strategy.probability("K K:bc/b", Action::Fold).unwrap() < 0.01);
+    }
+
+    #[test]
+    fn test_river_subgame_with_a_polarized_range() {
+        // The first player has a set of kings or nothing against the second player's
+        // queens. With a pot of 4 and a bet of 2, the bluffs are a third of the value bets
+        // and the queens call two thirds of the time.
+        let hole = |cards: &str| {
+            let cards = parse_cards(cards).unwrap();
+            [cards[0], cards[1]]
+        };
+        let board = parse_cards("KC 8D 6H 2S 3C").unwrap();
+        let ranges = [vec![hole("KH KD"), hole("JH 10D")], vec![hole("QH QD")]];
+        let subgame = HoldemSubgame::new(&board, ranges, 4, 2, 2, NoAbstraction).unwrap();
+        let mut solver = Solver::new(&subgame, CfrVariant::Plus);
+        solver.solve(2000);
+        let strategy = solver.average_strategy();
+        let bet = |hand: &str| {
+            strategy
+                .probability(&subgame.info_set(hole(hand), ""), Action::Bet(2))
+                .unwrap()
+        };
+        let call = strategy
+            .probability(&subgame.info_set(hole("QH QD"), "b"), Action::Call)
+            .unwrap();
+        assert!(bet("KH KD") > 0.95);
+        assert!((bet("JH 10D") - 1.0 / 3.0).abs() < 0.05);
+        assert!((call - 2.0 / 3.0).abs() < 0.05);
+        assert!(solver.exploitability() < 0.01);
+
+        // Bucketing by strength puts both of the first player's hands in different buckets.
+        let bucketed = HoldemSubgame::new(
+            &board,
+            [vec![hole("KH KD"), hole("JH 10D")], vec![hole("QH QD")]],
+            4,
+            2,
+            2,
+            StrengthBuckets::new(4).unwrap(),
+        )
+        .unwrap();
+        assert_ne!(
+            bucketed.info_set(hole("KH KD"), ""),
+            bucketed.info_set(hole("JH 10D"), "")
+        );
+        assert!(StrengthBuckets::new(0).is_err());
+        assert!(HoldemSubgame::new(
+            &board,
+            [vec![hole("KH KD")], vec![hole("QH QD")]],
+            5,
+            2,
+            2,
+            NoAbstraction
+        )
+        .is_err());
+        assert!(HoldemSubgame::new(
+            &board[..4],
+            [vec![hole("KH KD")], vec![hole("QH QD")]],
+            4,
+            2,
+            2,
+            NoAbstraction
+        )
+        .is_err());
+        assert!(HoldemSubgame::new(
+            &board,
+            [vec![hole("KC KD")], vec![hole("QH QD")]],
+            4,
+            2,
+            2,
+            NoAbstraction
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,235 @@
+//! Enumeration and indexing of every distinct five card poker hand.
+//!
+//! Hands are indexed with the colexicographic ("colex") ranking of their card indices, which
+//! maps the 2,598,960 five card hands onto the dense range 0..2,598,960. The same ranking
+//! works for subsets of any size, so it is also exposed for partial hands.
+
+use crate::cards::Card;
+use crate::error::PokerHandError;
+use crate::poker_hand::{PokerHand, PokerHandRanks};
+
+/// The number of cards in a deck.
+pub const DECK_SIZE: usize = 52;
+
+/// The number of cards in a poker hand.
+pub const HAND_SIZE: usize = 5;
+
+/// The number of distinct five card hands, C(52, 5).
+pub const TOTAL_HANDS: u32 = 2_598_960;
+
+/// The binomial coefficient C(n, k), or 0 if k > n.
+///
+/// Only intended for deck sized arguments, where the result always fits in a u64.
+pub fn binomial(n: usize, k: usize) -> u64 {
+    if k > n {
+        return 0;
+    }
+    let k = k.min(n - k);
+    let mut result: u64 = 1;
+    for i in 0..k {
+        // The running product is always a binomial coefficient, so the division is exact.
+        result = result * (n - i) as u64 / (i + 1) as u64;
+    }
+    result
+}
+
+/// The colex rank of a set of distinct cards.
+///
+/// The cards may be given in any order. For k cards the result is in the range 0..C(52, k).
+pub fn colex_rank(cards: &[Card]) -> Result<u64, PokerHandError> {
+    let mut indices: Vec<usize> = cards.iter().map(|card| card.index()).collect();
+    indices.sort_unstable();
+    if indices.windows(2).any(|pair| pair[0] == pair[1]) {
+        return Err(PokerHandError::new("Duplicate cards in hand"));
+    }
+    Ok(indices
+        .iter()
+        .enumerate()
+        .map(|(i, &index)| binomial(index, i + 1))
+        .sum())
+}
+
+/// The inverse of `colex_rank`: the k cards with the given rank, in ascending index order.
+///
+/// Returns None if the rank is not below C(52, k).
+pub fn colex_unrank(rank: u64, k: usize) -> Option<Vec<Card>> {
+    if rank >= binomial(DECK_SIZE, k) {
+        return None;
+    }
+    let mut remaining = rank;
+    let mut cards = Vec::with_capacity(k);
+    let mut upper = DECK_SIZE;
+    for i in (1..=k).rev() {
+        // Find the largest index whose binomial coefficient fits in the remaining rank.
+        let mut index = upper - 1;
+        while binomial(index, i) > remaining {
+            index -= 1;
+        }
+        remaining -= binomial(index, i);
+        cards.push(Card::from_index(index)?);
+        upper = index;
+    }
+    cards.reverse();
+    Some(cards)
+}
+
+/// The dense index of a five card hand, in the range 0..TOTAL_HANDS.
+pub fn hand_index(cards: &[Card]) -> Result<u32, PokerHandError> {
+    if cards.len() != HAND_SIZE {
+        return Err(PokerHandError::new("A poker hand must have five cards"));
+    }
+    Ok(colex_rank(cards)? as u32)
+}
+
+/// The five card hand with the given dense index, in ascending card index order.
+pub fn hand_from_index(index: u32) -> Option<[Card; HAND_SIZE]> {
+    let cards = colex_unrank(index as u64, HAND_SIZE)?;
+    Some([cards[0], cards[1], cards[2], cards[3], cards[4]])
+}
+
+/// An iterator over every distinct combination of k cards, in colex order.
+///
+/// The n'th item yielded has colex rank n, so `colex_rank` of each item counts up from zero.
+pub struct Combinations {
+    indices: Vec<usize>,
+    done: bool,
+}
+
+impl Combinations {
+    /// Iterate over all combinations of k cards from a full deck.
+    pub fn new(k: usize) -> Combinations {
+        Combinations {
+            indices: (0..k).collect(),
+            done: k > DECK_SIZE,
+        }
+    }
+}
+
+impl Iterator for Combinations {
+    type Item = Vec<Card>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let item = self
+            .indices
+            .iter()
+            .map(|&index| Card::from_index(index).unwrap())
+            .collect();
+
+        // Advance to the colex successor: bump the lowest index that has room to grow and
+        // reset every index below it to its smallest value.
+        let k = self.indices.len();
+        let mut i = 0;
+        loop {
+            if i == k {
+                self.done = true;
+                break;
+            }
+            let limit = if i + 1 < k {
+                self.indices[i + 1]
+            } else {
+                DECK_SIZE
+            };
+            if self.indices[i] + 1 < limit {
+                self.indices[i] += 1;
+                for (j, index) in self.indices.iter_mut().enumerate().take(i) {
+                    *index = j;
+                }
+                break;
+            }
+            i += 1;
+        }
+        Some(item)
+    }
+}
+
+/// Iterate over every distinct five card hand, in dense index order.
+pub fn all_hands() -> impl Iterator<Item = [Card; HAND_SIZE]> {
+    Combinations::new(HAND_SIZE).map(|cards| [cards[0], cards[1], cards[2], cards[3], cards[4]])
+}
+
+/// The number of five card hands of each hand type, from HighCard to StraightFlush.
+///
+/// This classifies every hand, so it takes a moment to run.
+pub fn category_frequencies() -> Vec<(PokerHandRanks, u32)> {
+    let mut counts = [0u32; 9];
+    for cards in all_hands() {
+        // Every enumerated hand is valid, so classification cannot fail.
+        let hand = PokerHand::from_cards(&cards).unwrap();
+        counts[hand.hand_rank as usize - 1] += 1;
+    }
+    PokerHandRanks::ALL.iter().copied().zip(counts).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards::{Ranks, Suits};
+
+    #[test]
+    fn test_binomial() {
+        assert_eq!(binomial(52, 5), TOTAL_HANDS as u64);
+        assert_eq!(binomial(52, 0), 1);
+        assert_eq!(binomial(4, 5), 0);
+        assert_eq!(binomial(47, 2), 1081);
+    }
+
+    #[test]
+    fn test_hand_index_bounds() {
+        let lowest = hand_from_index(0).unwrap();
+        assert_eq!(lowest[0], Card::new(Ranks::Two, Suits::Clubs));
+        assert_eq!(lowest[4], Card::new(Ranks::Three, Suits::Clubs));
+        let highest = hand_from_index(TOTAL_HANDS - 1).unwrap();
+        assert_eq!(highest[0], Card::new(Ranks::King, Suits::Spades));
+        assert_eq!(highest[4], Card::new(Ranks::Ace, Suits::Spades));
+        assert!(hand_from_index(TOTAL_HANDS).is_none());
+    }
+
+    #[test]
+    fn test_hand_index_errors() {
+        let card = Card::new(Ranks::Two, Suits::Clubs);
+        assert!(hand_index(&[card; 4]).is_err());
+        assert!(hand_index(&[card; 5]).is_err());
+    }
+
+    #[test]
+    fn test_colex_round_trip_for_partial_hands() {
+        for k in 0..4 {
+            for (position, cards) in Combinations::new(k).enumerate() {
+                let rank = colex_rank(&cards).unwrap();
+                assert_eq!(rank, position as u64);
+                assert_eq!(colex_unrank(rank, k).unwrap(), cards);
+            }
+        }
+    }
+
+    #[test]
+    fn test_every_hand_is_indexed_and_classified() {
+        // Exhaustive check of both the dense index and the hand classification.
+        let mut count: u32 = 0;
+        for cards in all_hands() {
+            assert_eq!(hand_index(&cards).unwrap(), count);
+            count += 1;
+        }
+        assert_eq!(count, TOTAL_HANDS);
+        assert_eq!(
+            hand_from_index(1_234_567).map(|h| hand_index(&h).unwrap()),
+            Some(1_234_567)
+        );
+
+        let expected = [
+            (PokerHandRanks::HighCard, 1_302_540),
+            (PokerHandRanks::Pair, 1_098_240),
+            (PokerHandRanks::TwoPair, 123_552),
+            (PokerHandRanks::ThreeOfAKind, 54_912),
+            (PokerHandRanks::Straight, 10_200),
+            (PokerHandRanks::Flush, 5_108),
+            (PokerHandRanks::FullHouse, 3_744),
+            (PokerHandRanks::FourOfAKind, 624),
+            (PokerHandRanks::StraightFlush, 40),
+        ];
+        assert_eq!(category_frequencies(), expected.to_vec());
+    }
+}
@@ -0,0 +1,440 @@
+//! Dealing, discarding and drawing for five card draw and 2-7 triple draw.
+//!
+//! `DrawHand` deals five cards to each player from a `Deck`, then runs the draw rounds:
+//! in each round every player still in the hand, in seat order, discards any of their
+//! cards and is dealt replacements. The discards go to the muck, and if the stub runs out
+//! the muck is shuffled to make a new stub, as in a card room. A player's own discards are
+//! mucked only after their replacements are dealt, so they never get them back. Betting
+//! between the draws is left to the caller, who folds players out of the hand.
+//!
+//! Five card draw has one draw and is won by the best high hand. 2-7 triple draw has three
+//! draws and is won by the lowest hand, where aces are always high and straights and
+//! flushes count against the hand, so 7-5-4-3-2 of mixed suits is the best hand and
+//! A-2-3-4-5 is an ace high hand rather than a straight.
+
+use crate::cards::{Card, Ranks};
+use crate::error::{PokerHandError, TableError};
+use crate::poker_hand::{rank_tiers, PokerHand, PokerHandRanks};
+use crate::rng::SeededRng;
+
+/// The number of cards in each player's hand.
+const HAND_SIZE: usize = 5;
+
+/// A deck of cards being dealt from, with a muck of discards.
+#[derive(Debug, Clone)]
+pub struct Deck {
+    /// The undealt cards. The next card dealt is the last.
+    stub: Vec<Card>,
+    muck: Vec<Card>,
+    rng: SeededRng,
+}
+
+impl Deck {
+    /// A full deck shuffled from the seed. The same seed always deals the same cards, and
+    /// shuffles the muck the same way.
+    pub fn shuffled(seed: u64) -> Deck {
+        let mut rng = SeededRng::new(seed);
+        let mut stub: Vec<Card> = (0..52).filter_map(Card::from_index).collect();
+        rng.shuffle_prefix(&mut stub, 52);
+        Deck {
+            stub,
+            muck: Vec::new(),
+            rng,
+        }
+    }
+
+    /// A deck which deals the given cards in order, then the muck shuffled from the seed.
+    pub fn stacked(cards: &[Card], seed: u64) -> Result<Deck, TableError> {
+        for (i, card) in cards.iter().enumerate() {
+            if cards[i + 1..].contains(card) {
+                return Err(TableError::InvalidSetup(format!(
+                    "The card {} is in the deck twice",
+                    card
+                )));
+            }
+        }
+        Ok(Deck {
+            stub: cards.iter().rev().copied().collect(),
+            muck: Vec::new(),
+            rng: SeededRng::new(seed),
+        })
+    }
+
+    /// The number of cards left in the stub.
+    pub fn remaining(&self) -> usize {
+        self.stub.len()
+    }
+
+    /// The number of discards in the muck.
+    pub fn mucked(&self) -> usize {
+        self.muck.len()
+    }
+
+    /// Deal cards from the stub, shuffling the muck into a new stub if it runs out.
+    pub fn deal(&mut self, count: usize) -> Result<Vec<Card>, TableError> {
+        if count > self.stub.len() + self.muck.len() {
+            return Err(TableError::InvalidDraw(format!(
+                "Only {} cards are left to deal",
+                self.stub.len() + self.muck.len()
+            )));
+        }
+        let mut cards = Vec::with_capacity(count);
+        for _ in 0..count {
+            if self.stub.is_empty() {
+                let size = self.muck.len();
+                self.rng.shuffle_prefix(&mut self.muck, size);
+                self.stub.append(&mut self.muck);
+            }
+            cards.extend(self.stub.pop());
+        }
+        Ok(cards)
+    }
+
+    fn muck(&mut self, cards: &[Card]) {
+        self.muck.extend_from_slice(cards);
+    }
+}
+
+/// The draw games, which set the number of draws and how hands are ranked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DrawVariant {
+    /// One draw, and the best high hand wins.
+    FiveCardDraw,
+    /// Three draws, and the best 2-7 low hand wins.
+    DeuceToSevenTripleDraw,
+}
+
+impl DrawVariant {
+    pub fn draw_rounds(self) -> usize {
+        match self {
+            DrawVariant::FiveCardDraw => 1,
+            DrawVariant::DeuceToSevenTripleDraw => 3,
+        }
+    }
+
+    /// Whether the lowest hand wins.
+    pub fn is_lowball(self) -> bool {
+        self == DrawVariant::DeuceToSevenTripleDraw
+    }
+}
+
+/// A five card hand valued for 2-7 lowball, where the ace is always high.
+///
+/// The hand is ranked as a `PokerHand` except that A-2-3-4-5 is not a straight, so the
+/// lowest `PokerHand` is the best 2-7 hand.
+pub fn deuce_to_seven_hand(cards: &[Card]) -> Result<PokerHand<'static>, PokerHandError> {
+    let mut hand = PokerHand::from_cards(cards)?;
+    let wheel = hand.cards[0].rank == Ranks::Five;
+    match hand.hand_rank {
+        PokerHandRanks::Straight if wheel => hand.hand_rank = PokerHandRanks::HighCard,
+        PokerHandRanks::StraightFlush if wheel => hand.hand_rank = PokerHandRanks::Flush,
+        _ => return Ok(hand),
+    }
+    // The ace was scored last as the low card of the straight.
+    hand.cards.rotate_right(1);
+    Ok(hand)
+}
+
+/// A single hand of a draw game.
+#[derive(Debug, Clone)]
+pub struct DrawHand {
+    variant: DrawVariant,
+    deck: Deck,
+    hands: Vec<Vec<Card>>,
+    folded: Vec<bool>,
+    /// The number of draw rounds completed.
+    round: usize,
+    /// Whether each player has drawn in the current round.
+    drawn: Vec<bool>,
+}
+
+impl DrawHand {
+    /// Deal five cards to each player, one at a time starting with seat 0, who is also
+    /// the first to draw.
+    pub fn new(
+        variant: DrawVariant,
+        players: usize,
+        mut deck: Deck,
+    ) -> Result<DrawHand, TableError> {
+        if players < 2 {
+            return Err(TableError::InvalidSetup(
+                "A hand needs at least two players".to_string(),
+            ));
+        }
+        if players * HAND_SIZE > deck.remaining() {
+            return Err(TableError::InvalidSetup(format!(
+                "There are not enough cards to deal to {} players",
+                players
+            )));
+        }
+        let mut hands = vec![Vec::with_capacity(HAND_SIZE); players];
+        for _ in 0..HAND_SIZE {
+            for hand in hands.iter_mut() {
+                hand.extend(deck.deal(1)?);
+            }
+        }
+        Ok(DrawHand {
+            variant,
+            deck,
+            hands,
+            folded: vec![false; players],
+            round: 0,
+            drawn: vec![false; players],
+        })
+    }
+
+    pub fn variant(&self) -> DrawVariant {
+        self.variant
+    }
+
+    pub fn deck(&self) -> &Deck {
+        &self.deck
+    }
+
+    /// The player's current cards.
+    pub fn hand(&self, seat: usize) -> &[Card] {
+        &self.hands[seat]
+    }
+
+    pub fn is_folded(&self, seat: usize) -> bool {
+        self.folded[seat]
+    }
+
+    /// The number of draw rounds completed.
+    pub fn round(&self) -> usize {
+        self.round
+    }
+
+    /// Whether all the draws are done, or only one player is left.
+    pub fn is_complete(&self) -> bool {
+        self.round == self.variant.draw_rounds() || self.active().count() < 2
+    }
+
+    /// The player due to draw next.
+    pub fn to_draw(&self) -> Option<usize> {
+        if self.is_complete() {
+            return None;
+        }
+        self.active().find(|&seat| !self.drawn[seat])
+    }
+
+    fn active(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.hands.len()).filter(move |&seat| !self.folded[seat])
+    }
+
+    /// Fold a player out of the hand, mucking their cards.
+    pub fn fold(&mut self, seat: usize) -> Result<(), TableError> {
+        if self.is_complete() {
+            return Err(TableError::HandComplete);
+        }
+        if seat >= self.hands.len() || self.folded[seat] {
+            return Err(TableError::InvalidDraw(format!(
+                "Seat {} is not in the hand",
+                seat
+            )));
+        }
+        self.folded[seat] = true;
+        let cards = std::mem::take(&mut self.hands[seat]);
+        self.deck.muck(&cards);
+        self.finish_round();
+        Ok(())
+    }
+
+    /// Discard the given cards from the player's hand and deal replacements, which are
+    /// returned. Standing pat is a draw with no discards.
+    pub fn draw(&mut self, seat: usize, discards: &[Card]) -> Result<Vec<Card>, TableError> {
+        let expected = self.to_draw().ok_or(TableError::HandComplete)?;
+        if seat != expected {
+            return Err(TableError::OutOfTurn {
+                expected,
+                actual: seat,
+            });
+        }
+        for (i, card) in discards.iter().enumerate() {
+            if !self.hands[seat].contains(card) {
+                return Err(TableError::InvalidDraw(format!(
+                    "The card {} is not in seat {}'s hand",
+                    card, seat
+                )));
+            }
+            if discards[i + 1..].contains(card) {
+                return Err(TableError::InvalidDraw(format!(
+                    "The card {} is discarded twice",
+                    card
+                )));
+            }
+        }
+        let replacements = self.deck.deal(discards.len())?;
+        self.hands[seat].retain(|card| !discards.contains(card));
+        self.hands[seat].extend_from_slice(&replacements);
+        self.deck.muck(discards);
+        self.drawn[seat] = true;
+        self.finish_round();
+        Ok(replacements)
+    }
+
+    /// Start the next round once every player still in the hand has drawn.
+    fn finish_round(&mut self) {
+        if self.active().all(|seat| self.drawn[seat]) {
+            self.round += 1;
+            self.drawn = vec![false; self.hands.len()];
+        }
+    }
+
+    /// The players still in the hand grouped into tiers of equal hands, the winners first.
+    pub fn showdown(&self) -> Result<Vec<Vec<usize>>, TableError> {
+        if !self.is_complete() {
+            return Err(TableError::InvalidDraw(
+                "The draws are not complete".to_string(),
+            ));
+        }
+        let hands = self
+            .active()
+            .map(|seat| {
+                let hand = if self.variant.is_lowball() {
+                    deuce_to_seven_hand(&self.hands[seat])
+                } else {
+                    PokerHand::from_cards(&self.hands[seat])
+                };
+                hand.map(|hand| (seat, hand))
+            })
+            .collect::<Result<Vec<(usize, PokerHand)>, PokerHandError>>()
+            .map_err(|error| TableError::InvalidDraw(error.to_string()))?;
+        let mut tiers = rank_tiers(&hands);
+        if self.variant.is_lowball() {
+            tiers.reverse();
+        }
+        Ok(tiers)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards::{parse_cards, Suits};
+
+    fn cards(cards: &str) -> Vec<Card> {
+        parse_cards(cards).unwrap()
+    }
+
+    /// A deck dealing the two hands, one card at a time, then the draw cards.
+    fn stacked(first: &str, second: &str, draws: &str) -> Deck {
+        let (first, second) = (cards(first), cards(second));
+        let mut order: Vec<Card> = first
+            .iter()
+            .zip(&second)
+            .flat_map(|(a, b)| vec![*a, *b])
+            .collect();
+        order.extend(cards(draws));
+        Deck::stacked(&order, 1).unwrap()
+    }
+
+    #[test]
+    fn test_deck() {
+        let mut deck = Deck::shuffled(7);
+        let mut dealt = deck.deal(52).unwrap();
+        assert_eq!(deck.remaining(), 0);
+        assert!(deck.deal(1).is_err());
+        dealt.sort_by_key(|card| card.index());
+        assert_eq!(
+            dealt,
+            (0..52).filter_map(Card::from_index).collect::<Vec<Card>>()
+        );
+        assert_eq!(Deck::shuffled(7).deal(5), Deck::shuffled(7).deal(5));
+        let ace = Card::new(Ranks::Ace, Suits::Spades);
+        assert!(Deck::stacked(&[ace, ace], 1).is_err());
+    }
+
+    #[test]
+    fn test_five_card_draw() {
+        let deck = stacked("AS AD 7C 4H 2S", "KS KD 9C 8H 3D", "AH 8C 5S");
+        let mut hand = DrawHand::new(DrawVariant::FiveCardDraw, 2, deck).unwrap();
+        assert_eq!(hand.hand(0), &cards("AS AD 7C 4H 2S")[..]);
+        assert_eq!(
+            hand.draw(1, &cards("KS")),
+            Err(TableError::OutOfTurn {
+                expected: 0,
+                actual: 1
+            })
+        );
+        assert!(matches!(
+            hand.draw(0, &cards("KS")),
+            Err(TableError::InvalidDraw(_))
+        ));
+        assert!(matches!(
+            hand.draw(0, &[hand.hand(0)[2], hand.hand(0)[2]]),
+            Err(TableError::InvalidDraw(_))
+        ));
+        assert_eq!(hand.draw(0, &cards("7C")).unwrap(), cards("AH"));
+        assert_eq!(
+            hand.draw(1, &cards("3D 4H")),
+            Err(TableError::InvalidDraw(
+                "The card 4H is not in seat 1's hand".to_string()
+            ))
+        );
+        hand.draw(1, &cards("9C 3D")).unwrap();
+        assert!(hand.is_complete());
+        assert_eq!(hand.draw(0, &[]), Err(TableError::HandComplete));
+        assert_eq!(hand.deck().mucked(), 3);
+        assert_eq!(hand.showdown().unwrap(), vec![vec![0], vec![1]]);
+    }
+
+    #[test]
+    fn test_deuce_to_seven_ranking() {
+        let low = |hand: &str| deuce_to_seven_hand(&cards(hand)).unwrap();
+        // The wheel is ace high, not a straight, and the ace is always high.
+        assert_eq!(low("AS 2D 3C 4H 5S").hand_rank, PokerHandRanks::HighCard);
+        assert_eq!(low("AS 2S 3S 4S 5S").hand_rank, PokerHandRanks::Flush);
+        assert!(low("7S 5D 4C 3H 2S") < low("8S 6D 4C 3H 2S"));
+        assert!(low("KS QD JC 9H 8S") < low("AS 2D 3C 4H 5S"));
+        assert!(low("AS KD QC JH 9S") < low("6S 5D 4C 3H 2S"));
+    }
+
+    #[test]
+    fn test_triple_draw_with_reshuffle() {
+        // Nine players leave seven cards in the stub, so the muck is reshuffled during the
+        // first draw.
+        let mut hand =
+            DrawHand::new(DrawVariant::DeuceToSevenTripleDraw, 9, Deck::shuffled(3)).unwrap();
+        assert_eq!(hand.deck().remaining(), 7);
+        for _ in 0..3 {
+            for seat in 0..9 {
+                let discards = hand.hand(seat)[..3].to_vec();
+                hand.draw(seat, &discards).unwrap();
+            }
+        }
+        assert!(hand.is_complete());
+        // Every card is in a hand, the stub or the muck exactly once.
+        let mut seen: Vec<usize> = (0..9)
+            .flat_map(|seat| hand.hand(seat).to_vec())
+            .map(|card| card.index())
+            .collect();
+        seen.sort_unstable();
+        seen.dedup();
+        assert_eq!(seen.len(), 45);
+        assert_eq!(
+            seen.len() + hand.deck().remaining() + hand.deck().mucked(),
+            52
+        );
+        let tiers = hand.showdown().unwrap();
+        assert_eq!(tiers.iter().map(Vec::len).sum::<usize>(), 9);
+    }
+
+    #[test]
+    fn test_lowball_showdown_and_folds() {
+        let deck = stacked("7S 5D 4C 3H 2S", "AS 2D 3C 4H 5S", "");
+        let mut hand = DrawHand::new(DrawVariant::DeuceToSevenTripleDraw, 2, deck).unwrap();
+        for _ in 0..3 {
+            hand.draw(0, &[]).unwrap();
+            hand.draw(1, &[]).unwrap();
+        }
+        assert_eq!(hand.showdown().unwrap(), vec![vec![0], vec![1]]);
+
+        let deck = stacked("7S 5D 4C 3H 2S", "AS 2D 3C 4H 5S", "");
+        let mut hand = DrawHand::new(DrawVariant::DeuceToSevenTripleDraw, 2, deck).unwrap();
+        assert!(hand.showdown().is_err());
+        hand.fold(0).unwrap();
+        assert!(hand.is_complete());
+        assert_eq!(hand.showdown().unwrap(), vec![vec![1]]);
+    }
+}
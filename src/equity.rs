@@ -0,0 +1,186 @@
+//! All-in equity of one Texas Hold'em hand against another.
+//!
+//! Once the players are all in there are no more decisions, so a hand's share of the pot
+//! depends only on the board cards still to come. The odds are found by dealing out every
+//! possible run out, or a sample of them, and comparing the hands with the evaluator.
+
+use crate::cards::Card;
+use crate::error::PokerHandError;
+use crate::evaluator::evaluate_unchecked;
+use crate::rng::SeededRng;
+use crate::strength::Enumeration;
+use std::cmp::Ordering;
+
+/// The chances of winning, tying and losing at showdown.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ShowdownOdds {
+    pub win: f64,
+    pub tie: f64,
+    pub loss: f64,
+}
+
+impl ShowdownOdds {
+    /// The expected share of the pot, counting a tie as half.
+    pub fn equity(&self) -> f64 {
+        self.win + self.tie / 2.0
+    }
+}
+
+/// The odds of the hole cards against the opponent's hole cards, with the board cards
+/// dealt so far, usually none.
+pub fn heads_up_odds(
+    hole: &[Card],
+    opponent: &[Card],
+    board: &[Card],
+    enumeration: Enumeration,
+) -> Result<ShowdownOdds, PokerHandError> {
+    if hole.len() != 2 || opponent.len() != 2 {
+        return Err(PokerHandError::new("A player must have two hole cards"));
+    }
+    if board.len() > 5 {
+        return Err(PokerHandError::new("Invalid number of board cards"));
+    }
+    let known = [hole, opponent, board].concat();
+    for (i, card) in known.iter().enumerate() {
+        if known[i + 1..].contains(card) {
+            return Err(PokerHandError::new("Duplicate cards in hand"));
+        }
+    }
+    let unseen: Vec<Card> = (0..52)
+        .filter_map(Card::from_index)
+        .filter(|card| !known.contains(card))
+        .collect();
+
+    // Each player's seven cards, with the run out filled in from position 2 + board.len().
+    let mut ours = [hole[0]; 7];
+    let mut theirs = [opponent[0]; 7];
+    ours[..2].copy_from_slice(hole);
+    theirs[..2].copy_from_slice(opponent);
+    ours[2..2 + board.len()].copy_from_slice(board);
+    theirs[2..2 + board.len()].copy_from_slice(board);
+    let dealt = 2 + board.len();
+
+    let mut tally = [0u64; 3];
+    let mut compare = |ours: &[Card; 7], theirs: &[Card; 7]| {
+        let outcome = match evaluate_unchecked(ours).cmp(&evaluate_unchecked(theirs)) {
+            Ordering::Greater => 0,
+            Ordering::Equal => 1,
+            Ordering::Less => 2,
+        };
+        tally[outcome] += 1;
+    };
+    match enumeration {
+        Enumeration::Exhaustive => {
+            deal_runouts(&unseen, 0, dealt, &mut ours, &mut theirs, &mut compare);
+        }
+        Enumeration::Sampled { samples, seed } => {
+            let mut rng = SeededRng::new(seed);
+            let mut deck = unseen;
+            let needed = 7 - dealt;
+            for _ in 0..samples {
+                rng.shuffle_prefix(&mut deck, needed);
+                ours[dealt..].copy_from_slice(&deck[..needed]);
+                theirs[dealt..].copy_from_slice(&deck[..needed]);
+                compare(&ours, &theirs);
+            }
+        }
+    }
+
+    let total: u64 = tally.iter().sum();
+    if total == 0 {
+        return Ok(ShowdownOdds::default());
+    }
+    let share = |count: u64| count as f64 / total as f64;
+    Ok(ShowdownOdds {
+        win: share(tally[0]),
+        tie: share(tally[1]),
+        loss: share(tally[2]),
+    })
+}
+
+/// Fill positions `position..7` of both hands with every combination of the unseen cards
+/// from `from` on, calling `visit` for each complete pair of hands.
+fn deal_runouts<F>(
+    unseen: &[Card],
+    from: usize,
+    position: usize,
+    ours: &mut [Card; 7],
+    theirs: &mut [Card; 7],
+    visit: &mut F,
+) where
+    F: FnMut(&[Card; 7], &[Card; 7]),
+{
+    if position == 7 {
+        visit(ours, theirs);
+        return;
+    }
+    let still_needed = 7 - position;
+    for i in from..=unseen.len() - still_needed {
+        ours[position] = unseen[i];
+        theirs[position] = unseen[i];
+        deal_runouts(unseen, i + 1, position + 1, ours, theirs, visit);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards::parse_cards;
+
+    fn cards(cards: &str) -> Vec<Card> {
+        parse_cards(cards).unwrap()
+    }
+
+    #[test]
+    fn test_odds_on_the_turn() {
+        // Nine hearts make the flush, but the 2H and 3H also fill up the kings, leaving
+        // seven winning cards of the 44.
+        let odds = heads_up_odds(
+            &cards("AH 7H"),
+            &cards("KS KD"),
+            &cards("KH 8H 2D 3C"),
+            Enumeration::Exhaustive,
+        )
+        .unwrap();
+        assert!((odds.win - 7.0 / 44.0).abs() < 1e-12);
+        assert_eq!(odds.tie, 0.0);
+        assert!((odds.win + odds.loss - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_exhaustive_flop_odds_and_ties() {
+        let odds = heads_up_odds(
+            &cards("AH KD"),
+            &cards("AS KC"),
+            &cards("QH JD 2C"),
+            Enumeration::Exhaustive,
+        )
+        .unwrap();
+        // Only running hearts or clubs break the tie.
+        assert!(odds.tie > 0.9);
+        assert!((odds.equity() - 0.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_sampled_preflop_odds() {
+        // Aces are about a 4 to 1 favourite over kings.
+        let odds = heads_up_odds(
+            &cards("AH AD"),
+            &cards("KS KC"),
+            &[],
+            Enumeration::Sampled {
+                samples: 20_000,
+                seed: 9,
+            },
+        )
+        .unwrap();
+        assert!((odds.equity() - 0.82).abs() < 0.02);
+        assert!(heads_up_odds(
+            &cards("AH AD"),
+            &cards("AH KC"),
+            &[],
+            Enumeration::Exhaustive
+        )
+        .is_err());
+    }
+}
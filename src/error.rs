@@ -1,21 +1,34 @@
+use core::fmt;
+#[cfg(feature = "std")]
 use std::error::Error;
-use std::fmt;
 
 /// An error type for invalid poker hands.
 ///
 /// The message field is a string describing the error.
 /// For example, PokerHandError::new("Invalid poker hand")
+///
+/// Without the `std` feature there is no heap to hold the message, so it must be a static
+/// string.
 #[derive(Debug)]
 pub struct PokerHandError {
+    #[cfg(feature = "std")]
     message: String,
+    #[cfg(not(feature = "std"))]
+    message: &'static str,
 }
 
 impl PokerHandError {
+    #[cfg(feature = "std")]
     pub fn new(message: &str) -> PokerHandError {
         PokerHandError {
             message: message.to_string(),
         }
     }
+
+    #[cfg(not(feature = "std"))]
+    pub fn new(message: &'static str) -> PokerHandError {
+        PokerHandError { message }
+    }
 }
 
 impl fmt::Display for PokerHandError {
@@ -24,8 +37,132 @@ impl fmt::Display for PokerHandError {
     }
 }
 
+#[cfg(feature = "std")]
 impl Error for PokerHandError {
     fn description(&self) -> &str {
         &self.message
     }
 }
+
+/// An error from parsing a hand string with `cards::parse_hand`.
+///
+/// Positions are byte offsets into the hand string, so the offending part can be pointed
+/// out to the user.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseError {
+    /// The string ended after this many cards.
+    TooFewCards { expected: usize, found: usize },
+    /// The string holds more cards than expected.
+    TooManyCards { expected: usize },
+    /// A card does not start with a rank from 2 to 10, J, Q, K or A.
+    InvalidRank { position: usize },
+    /// A rank is not followed by one of the suits C, D, H or S.
+    InvalidSuit { position: usize },
+    /// Cards must be separated by a single space.
+    ExpectedSpace { position: usize },
+    /// Something other than another card follows the last card.
+    TrailingInput { position: usize },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::TooFewCards { expected, found } => write!(
+                f,
+                "ParseError: Expected {} cards but found {}",
+                expected, found
+            ),
+            ParseError::TooManyCards { expected } => {
+                write!(f, "ParseError: Expected only {} cards", expected)
+            }
+            ParseError::InvalidRank { position } => {
+                write!(f, "ParseError: Invalid card rank at byte {}", position)
+            }
+            ParseError::InvalidSuit { position } => {
+                write!(f, "ParseError: Invalid card suit at byte {}", position)
+            }
+            ParseError::ExpectedSpace { position } => {
+                write!(f, "ParseError: Expected a space at byte {}", position)
+            }
+            ParseError::TrailingInput { position } => {
+                write!(f, "ParseError: Unexpected input at byte {}", position)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for ParseError {}
+
+impl From<ParseError> for PokerHandError {
+    #[cfg(feature = "std")]
+    fn from(error: ParseError) -> Self {
+        PokerHandError::new(&error.to_string())
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn from(_: ParseError) -> Self {
+        PokerHandError::new("Invalid poker hand")
+    }
+}
+
+/// An error type for invalid table setups and illegal betting actions.
+///
+/// The table engine reports every rule violation with one of these rather than panicking,
+/// so that a server can reject a bad request and carry on.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TableError {
+    /// The stacks, blinds or cards given for the hand are not valid.
+    InvalidSetup(String),
+    /// The hand is over, so no more actions are accepted.
+    HandComplete,
+    /// A player acted when it was another player's turn.
+    OutOfTurn { expected: usize, actual: usize },
+    /// The player tried to check while facing a bet.
+    CannotCheck { to_call: u64 },
+    /// The player tried to call when there is no bet to call.
+    NothingToCall,
+    /// The player tried to bet when there is already a bet, which requires a raise.
+    CannotBet,
+    /// The player tried to raise when there is no bet, or when raising is not open to them.
+    CannotRaise,
+    /// The bet or raise is below the minimum, and is not an all in.
+    BetTooSmall { minimum: u64 },
+    /// The bet or raise is more than the player's stack or the pot limit allows.
+    BetTooLarge { maximum: u64 },
+    /// The discards are not in the player's hand, or cannot be replaced from the deck.
+    InvalidDraw(String),
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for TableError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TableError::InvalidSetup(message) | TableError::InvalidDraw(message) => {
+                write!(f, "TableError: {}", message)
+            }
+            TableError::HandComplete => write!(f, "TableError: The hand is complete"),
+            TableError::OutOfTurn { expected, actual } => write!(
+                f,
+                "TableError: Seat {} acted but it is seat {}'s turn",
+                actual, expected
+            ),
+            TableError::CannotCheck { to_call } => {
+                write!(f, "TableError: Cannot check facing a bet of {}", to_call)
+            }
+            TableError::NothingToCall => write!(f, "TableError: There is no bet to call"),
+            TableError::CannotBet => write!(f, "TableError: Cannot bet when facing a bet"),
+            TableError::CannotRaise => write!(f, "TableError: Raising is not allowed"),
+            TableError::BetTooSmall { minimum } => {
+                write!(f, "TableError: The minimum bet or raise is to {}", minimum)
+            }
+            TableError::BetTooLarge { maximum } => {
+                write!(f, "TableError: The maximum bet or raise is to {}", maximum)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for TableError {}
@@ -0,0 +1,263 @@
+//! A fast hand evaluator for five to seven cards.
+//!
+//! `PokerHand` keeps the cards of a hand around so they can be inspected, which makes it
+//! convenient but slow when millions of hands have to be compared. The evaluator instead
+//! reduces the best five card hand to a single `HandValue` integer, without allocating.
+//! Hand values are ordered exactly as `PokerHand` orders the equivalent hands.
+
+use crate::cards::{Card, Ranks};
+use crate::error::PokerHandError;
+#[cfg(feature = "std")]
+use crate::poker_hand::PokerHand;
+use crate::poker_hand::PokerHandRanks;
+
+/// The strength of the best five card hand, as a single comparable number.
+///
+/// The hand type is stored in the top bits, followed by the five card ranks in "scoring
+/// order", four bits each, so comparing two values compares the hands.
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Copy, Clone, Hash)]
+pub struct HandValue(u32);
+
+impl HandValue {
+    fn from_parts(hand_rank: PokerHandRanks, ranks: [u8; 5]) -> HandValue {
+        let mut value = hand_rank as u32;
+        for rank in ranks {
+            value = (value << 4) | rank as u32;
+        }
+        HandValue(value)
+    }
+
+    /// The raw value, for storage or lookup tables.
+    pub fn value(&self) -> u32 {
+        self.0
+    }
+
+    /// The type of the hand.
+    pub fn hand_rank(&self) -> PokerHandRanks {
+        PokerHandRanks::ALL[(self.0 >> 20) as usize - 1]
+    }
+
+    /// The ranks of the five cards of the hand, in scoring order.
+    ///
+    /// This matches the order of `PokerHand::cards`, so an ace-low straight ends with the ace.
+    pub fn ranks(&self) -> [Ranks; 5] {
+        let mut ranks = [Ranks::Two; 5];
+        for (i, rank) in ranks.iter_mut().enumerate() {
+            let value = (self.0 >> (16 - 4 * i)) & 0xF;
+            *rank = Ranks::ALL[value as usize - 2];
+        }
+        ranks
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<&PokerHand<'_>> for HandValue {
+    fn from(hand: &PokerHand) -> Self {
+        let mut ranks = [0u8; 5];
+        for (rank, card) in ranks.iter_mut().zip(&hand.cards) {
+            *rank = card.rank as u8;
+        }
+        HandValue::from_parts(hand.hand_rank, ranks)
+    }
+}
+
+/// Evaluate the best five card hand from five to seven distinct cards.
+pub fn evaluate(cards: &[Card]) -> Result<HandValue, PokerHandError> {
+    if cards.len() < 5 || cards.len() > 7 {
+        return Err(PokerHandError::new(
+            "The best hand is chosen from 5 to 7 cards",
+        ));
+    }
+    let mut seen: u64 = 0;
+    for card in cards {
+        let bit = 1u64 << card.index();
+        if seen & bit != 0 {
+            return Err(PokerHandError::new("Duplicate cards in hand"));
+        }
+        seen |= bit;
+    }
+    Ok(evaluate_unchecked(cards))
+}
+
+/// Evaluate the best five card hand, trusting the caller to pass five to seven distinct cards.
+pub(crate) fn evaluate_unchecked(cards: &[Card]) -> HandValue {
+    // Per rank counts, and for each suit a bit mask of the ranks held in that suit.
+    // Bits and indices are the rank values, so bit 14 is the ace.
+    let mut counts = [0u8; 15];
+    let mut suit_masks = [0u16; 4];
+    for card in cards {
+        counts[card.rank as usize] += 1;
+        suit_masks[card.suit as usize] |= 1 << card.rank as u16;
+    }
+    let rank_mask = suit_masks.iter().fold(0, |mask, suit| mask | suit);
+
+    if let Some(&flush_mask) = suit_masks.iter().find(|mask| mask.count_ones() >= 5) {
+        if let Some(ranks) = straight(flush_mask) {
+            return HandValue::from_parts(PokerHandRanks::StraightFlush, ranks);
+        }
+        // Quads and full houses need too many cards of other suits to coexist with a flush.
+        let mut ranks = [0u8; 5];
+        fill_from_mask(&mut ranks, flush_mask);
+        return HandValue::from_parts(PokerHandRanks::Flush, ranks);
+    }
+
+    // Ranks holding at least n cards, highest first.
+    let ranks_with = |n: u8| (2..=14u8).rev().filter(move |&r| counts[r as usize] >= n);
+    let kickers = |ranks: &mut [u8], exclude: &[u8]| {
+        let mut mask = rank_mask;
+        for &rank in exclude {
+            mask &= !(1 << rank);
+        }
+        fill_from_mask(ranks, mask);
+    };
+
+    if let Some(quads) = ranks_with(4).next() {
+        let mut ranks = [quads, quads, quads, quads, 0];
+        kickers(&mut ranks[4..], &[quads]);
+        return HandValue::from_parts(PokerHandRanks::FourOfAKind, ranks);
+    }
+    if let Some(trips) = ranks_with(3).next() {
+        if let Some(pair) = ranks_with(2).find(|&r| r != trips) {
+            return HandValue::from_parts(
+                PokerHandRanks::FullHouse,
+                [trips, trips, trips, pair, pair],
+            );
+        }
+    }
+    if let Some(ranks) = straight(rank_mask) {
+        return HandValue::from_parts(PokerHandRanks::Straight, ranks);
+    }
+    if let Some(trips) = ranks_with(3).next() {
+        let mut ranks = [trips, trips, trips, 0, 0];
+        kickers(&mut ranks[3..], &[trips]);
+        return HandValue::from_parts(PokerHandRanks::ThreeOfAKind, ranks);
+    }
+    let mut pairs = ranks_with(2);
+    match (pairs.next(), pairs.next()) {
+        (Some(high), Some(low)) => {
+            let mut ranks = [high, high, low, low, 0];
+            kickers(&mut ranks[4..], &[high, low]);
+            HandValue::from_parts(PokerHandRanks::TwoPair, ranks)
+        }
+        (Some(pair), None) => {
+            let mut ranks = [pair, pair, 0, 0, 0];
+            kickers(&mut ranks[2..], &[pair]);
+            HandValue::from_parts(PokerHandRanks::Pair, ranks)
+        }
+        _ => {
+            let mut ranks = [0u8; 5];
+            fill_from_mask(&mut ranks, rank_mask);
+            HandValue::from_parts(PokerHandRanks::HighCard, ranks)
+        }
+    }
+}
+
+/// The ranks of the highest straight in a rank mask, in scoring order.
+fn straight(mask: u16) -> Option<[u8; 5]> {
+    for high in (6..=14u8).rev() {
+        let run = 0b11111 << (high - 4);
+        if mask & run == run {
+            return Some([high, high - 1, high - 2, high - 3, high - 4]);
+        }
+    }
+    // The ace plays low in the wheel, so it is scored last.
+    let wheel = (1 << 14) | 0b111100;
+    if mask & wheel == wheel {
+        return Some([5, 4, 3, 2, 14]);
+    }
+    None
+}
+
+/// Fill the slots with the highest ranks in the mask, in descending order.
+fn fill_from_mask(ranks: &mut [u8], mask: u16) {
+    let mut remaining = ranks.iter_mut();
+    for rank in (2..=14u8).rev() {
+        if mask & (1 << rank) != 0 {
+            match remaining.next() {
+                Some(slot) => *slot = rank,
+                None => break,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards::parse_hand;
+    #[cfg(feature = "std")]
+    use crate::combinatorics::all_hands;
+    #[cfg(feature = "std")]
+    use crate::rng::SeededRng;
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_evaluate_matches_poker_hand_for_every_hand() {
+        for cards in all_hands() {
+            let hand = PokerHand::from_cards(&cards).unwrap();
+            assert_eq!(evaluate_unchecked(&cards), HandValue::from(&hand));
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_evaluate_matches_best_of_for_seven_cards() {
+        let mut rng = SeededRng::new(7);
+        for _ in 0..2000 {
+            let mut deck: Vec<Card> = (0..52).filter_map(Card::from_index).collect();
+            rng.shuffle_prefix(&mut deck, 7);
+            let cards = &deck[..7];
+            let hand = PokerHand::best_of(cards).unwrap();
+            assert_eq!(evaluate(cards).unwrap(), HandValue::from(&hand));
+        }
+    }
+
+    #[test]
+    fn test_hand_value_accessors() {
+        let cards: [Card; 6] = parse_hand(b"AH 2C 3D 4S 5H 9C").unwrap();
+        let value = evaluate(&cards).unwrap();
+        assert!(value.hand_rank() == PokerHandRanks::Straight);
+        assert_eq!(value.ranks()[0], Ranks::Five);
+        assert_eq!(value.ranks()[4], Ranks::Ace);
+        let cards: [Card; 7] = parse_hand(b"9C 9D 9H 5S 5C 5D KH").unwrap();
+        let full_house = evaluate(&cards).unwrap();
+        assert!(full_house.hand_rank() == PokerHandRanks::FullHouse);
+        assert_eq!(
+            full_house.ranks(),
+            [
+                Ranks::Nine,
+                Ranks::Nine,
+                Ranks::Nine,
+                Ranks::Five,
+                Ranks::Five
+            ]
+        );
+        assert!(full_house > value);
+    }
+
+    #[test]
+    fn test_evaluate_errors() {
+        let cards: [Card; 4] = parse_hand(b"AH 2C 3D 4S").unwrap();
+        assert!(evaluate(&cards).is_err());
+        let duplicates = [cards[0], cards[1], cards[2], cards[3], cards[0]];
+        assert!(evaluate(&duplicates).is_err());
+    }
+
+    #[test]
+    fn test_evaluate_five_and_seven_card_arrays() {
+        let five: [Card; 5] = parse_hand(b"10H JH QH KH AH").unwrap();
+        let royal = evaluate(&five).unwrap();
+        assert!(royal.hand_rank() == PokerHandRanks::StraightFlush);
+        assert_eq!(royal.ranks()[0], Ranks::Ace);
+
+        // The best five of seven cards: a flush beats the straight and the pair.
+        let seven: [Card; 7] = parse_hand(b"2H 7H 9H 10C JH QS KH").unwrap();
+        let flush = evaluate(&seven).unwrap();
+        assert!(flush.hand_rank() == PokerHandRanks::Flush);
+        assert!(flush < royal);
+        let pair: [Card; 7] = parse_hand(b"2H 2D 5C 7S 9H JD KC").unwrap();
+        assert!(evaluate(&pair).unwrap().hand_rank() == PokerHandRanks::Pair);
+        let too_many: [Card; 8] = parse_hand(b"2H 2D 5C 7S 9H JD KC AC").unwrap();
+        assert!(evaluate(&too_many).is_err());
+    }
+}
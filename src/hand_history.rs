@@ -0,0 +1,1586 @@
+//! Writing and reading complete hands as text hand histories.
+//!
+//! Two formats are supported:
+//!
+//! * A PokerStars-like text format, with the cards written in the crate's own notation
+//!   (e.g. "10H") and amounts written in chips.
+//! * The open Poker Hand History (PHH) format, a TOML based format which uses the two
+//!   character card notation (e.g. "Th"). Players are listed clockwise from the seat left
+//!   of the button, so the button is always the last player.
+//!
+//! A `HandHistory` records the actions as the `Event`s produced by the table engine, with
+//! seats numbered by their position in `players`. Reloaded hands can be checked with
+//! `validate_showdown`, which re-evaluates the showdown with `PokerHand` and compares the
+//! result with the recorded winners.
+
+use crate::cards::Card;
+use crate::error::PokerHandError;
+use crate::poker_hand::PokerHand;
+use crate::pot::{settle, totals, Award, Contribution, OddChipRule};
+use crate::table::{Action, BettingStructure, Event, Street, TableHand};
+use std::fmt::Write;
+
+/// A player at the table, as recorded in a hand history.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlayerRecord {
+    /// The seat number at the table, as written in the history.
+    pub seat: usize,
+    pub name: String,
+    /// The stack before the antes and blinds were posted.
+    pub stack: u64,
+    /// The hole cards, if they were dealt to the recording player or shown.
+    pub hole_cards: Option<[Card; 2]>,
+}
+
+/// A complete hand: the players, blinds, actions, board and pot awards.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HandHistory {
+    pub hand_id: String,
+    pub table: String,
+    /// When the hand was played, written as "YYYY/MM/DD HH:MM:SS".
+    pub date: Option<String>,
+    pub structure: BettingStructure,
+    pub small_blind: u64,
+    pub big_blind: u64,
+    pub ante: u64,
+    pub straddle: Option<u64>,
+    /// The index in `players` of the player on the button.
+    pub button: usize,
+    pub players: Vec<PlayerRecord>,
+    /// Everything that happened, in order. Seats are indices into `players`.
+    pub events: Vec<Event>,
+    pub board: Vec<Card>,
+    /// The chips won from each pot. Seats are indices into `players`.
+    pub awards: Vec<Award>,
+    /// The chips taken from the pot by the house.
+    pub rake: u64,
+}
+
+impl HandHistory {
+    /// Record a completed hand from the table engine.
+    ///
+    /// `names` gives each seat's player name, and the seats are numbered from 1.
+    pub fn from_table(
+        hand: &TableHand,
+        hand_id: &str,
+        table: &str,
+        names: &[&str],
+    ) -> Result<HandHistory, PokerHandError> {
+        let result = hand
+            .result()
+            .ok_or_else(|| PokerHandError::new("The hand is not complete"))?;
+        if names.len() != hand.seats().len() {
+            return Err(PokerHandError::new("Every seat needs a player name"));
+        }
+        let players = hand
+            .seats()
+            .iter()
+            .zip(hand.starting_stacks())
+            .zip(names)
+            .enumerate()
+            .map(|(index, ((seat, &stack), name))| PlayerRecord {
+                seat: index + 1,
+                name: name.to_string(),
+                stack,
+                hole_cards: Some(seat.hole_cards),
+            })
+            .collect();
+        // Only the board cards that were dealt before the hand ended are recorded.
+        let dealt = hand
+            .events()
+            .iter()
+            .filter_map(|event| match event {
+                Event::Deal { street } => Some(street.board_cards()),
+                _ => None,
+            })
+            .max()
+            .unwrap_or(0);
+        let config = hand.config();
+        Ok(HandHistory {
+            hand_id: hand_id.to_string(),
+            table: table.to_string(),
+            date: None,
+            structure: config.structure,
+            small_blind: config.small_blind,
+            big_blind: config.big_blind,
+            ante: config.ante,
+            straddle: config.straddle,
+            button: hand.button(),
+            players,
+            events: hand.events().to_vec(),
+            board: hand.board()[..dealt].to_vec(),
+            awards: result.awards.clone(),
+            rake: result.rake.total(),
+        })
+    }
+
+    /// The chips each player put in over the hand, less any uncalled bets returned.
+    ///
+    /// Fails if the events refer to a seat which is not at the table, or return more chips
+    /// than a player put in.
+    pub fn contributions(&self) -> Result<Vec<Contribution>, PokerHandError> {
+        let mut contributions: Vec<Contribution> = (0..self.players.len())
+            .map(|seat| Contribution::new(seat, 0, false))
+            .collect();
+        let unknown_seat = || PokerHandError::new("An event refers to a seat not at the table");
+        let overflow = || PokerHandError::new("The contributions are too large");
+        for event in &self.events {
+            match *event {
+                Event::PostAnte { seat, amount }
+                | Event::PostSmallBlind { seat, amount }
+                | Event::PostBigBlind { seat, amount }
+                | Event::PostStraddle { seat, amount } => {
+                    let contribution = contributions.get_mut(seat).ok_or_else(unknown_seat)?;
+                    contribution.amount = contribution
+                        .amount
+                        .checked_add(amount)
+                        .ok_or_else(overflow)?;
+                }
+                Event::Act {
+                    seat,
+                    action,
+                    amount,
+                    ..
+                } => {
+                    let contribution = contributions.get_mut(seat).ok_or_else(unknown_seat)?;
+                    contribution.amount = contribution
+                        .amount
+                        .checked_add(amount)
+                        .ok_or_else(overflow)?;
+                    if action == Action::Fold {
+                        contribution.folded = true;
+                    }
+                }
+                Event::ReturnUncalled { seat, amount } => {
+                    let contribution = contributions.get_mut(seat).ok_or_else(unknown_seat)?;
+                    contribution.amount =
+                        contribution.amount.checked_sub(amount).ok_or_else(|| {
+                            PokerHandError::new("More chips were returned than were bet")
+                        })?;
+                }
+                Event::Deal { .. } => {}
+            }
+        }
+        Ok(contributions)
+    }
+
+    /// The players who had not folded when the hand ended.
+    pub fn showdown_players(&self) -> Vec<usize> {
+        (0..self.players.len())
+            .filter(|&seat| {
+                !self.events.iter().any(|event| {
+                    matches!(*event, Event::Act { seat: folded, action: Action::Fold, .. }
+                        if folded == seat)
+                })
+            })
+            .collect()
+    }
+
+    /// Re-evaluate the showdown and check that the recorded winners won what they should.
+    ///
+    /// Every player at the showdown must have known hole cards, and the board must be
+    /// complete. If everyone else folded, the last player must have won the whole pot.
+    /// When the hand was raked, the house's share cannot be attributed to a particular
+    /// pot, so only the winners and the total paid out are checked.
+    pub fn validate_showdown(&self) -> Result<(), PokerHandError> {
+        let expected = totals(&self.expected_awards()?);
+        let recorded = totals(&self.awards);
+        let matches = if self.rake == 0 {
+            expected == recorded
+        } else {
+            let paid: u64 = recorded.iter().map(|(_, amount)| amount).sum();
+            let pot: u64 = expected.iter().map(|(_, amount)| amount).sum();
+            let winners = |totals: &[(usize, u64)]| -> Vec<usize> {
+                totals.iter().map(|(seat, _)| *seat).collect()
+            };
+            winners(&expected) == winners(&recorded) && paid + self.rake == pot
+        };
+        if !matches {
+            return Err(PokerHandError::new(
+                "Recorded winners do not match the showdown",
+            ));
+        }
+        Ok(())
+    }
+
+    /// The best five card hand of each player at the showdown, keyed by their index in
+    /// `players`. The hands are empty if everyone else folded.
+    pub fn showdown_hands(&self) -> Result<Vec<(usize, PokerHand<'static>)>, PokerHandError> {
+        let live = self.showdown_players();
+        if live.len() < 2 {
+            return Ok(Vec::new());
+        }
+        if self.board.len() != 5 {
+            return Err(PokerHandError::new("The board is not complete"));
+        }
+        let mut hands = Vec::with_capacity(live.len());
+        for seat in live {
+            let hole_cards = self.players[seat]
+                .hole_cards
+                .ok_or_else(|| PokerHandError::new("A showdown hand was not shown"))?;
+            let cards = [&hole_cards[..], &self.board[..]].concat();
+            hands.push((seat, PokerHand::best_of(&cards)?));
+        }
+        Ok(hands)
+    }
+
+    /// The awards the hand should have produced, given the contributions and shown cards.
+    fn expected_awards(&self) -> Result<Vec<Award>, PokerHandError> {
+        let contributions = self.contributions()?;
+        let live = self.showdown_players();
+        if live.len() == 1 {
+            let amount = contributions.iter().map(|c| c.amount).sum();
+            return Ok(vec![Award {
+                pot: 0,
+                seat: live[0],
+                amount,
+            }]);
+        }
+        let rule = OddChipRule::LeftOfButton {
+            button: self.button,
+            table_size: self.players.len(),
+        };
+        settle(&contributions, &self.showdown_hands()?, &rule)
+    }
+
+    /// Check that the button, the events and the awards only refer to seats at the table.
+    fn check_seats(&self) -> Result<(), PokerHandError> {
+        let players = self.players.len();
+        if self.button >= players {
+            return Err(PokerHandError::new("The button is not at the table"));
+        }
+        let event_seats = self.events.iter().filter_map(|event| match *event {
+            Event::PostAnte { seat, .. }
+            | Event::PostSmallBlind { seat, .. }
+            | Event::PostBigBlind { seat, .. }
+            | Event::PostStraddle { seat, .. }
+            | Event::Act { seat, .. }
+            | Event::ReturnUncalled { seat, .. } => Some(seat),
+            Event::Deal { .. } => None,
+        });
+        if event_seats
+            .chain(self.awards.iter().map(|award| award.seat))
+            .any(|seat| seat >= players)
+        {
+            return Err(PokerHandError::new(
+                "An event or award refers to a seat not at the table",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Write the hand in the PokerStars-like text format.
+    ///
+    /// Fails if the button, an event or an award refers to a seat which is not at the
+    /// table.
+    pub fn to_text(&self) -> Result<String, PokerHandError> {
+        self.check_seats()?;
+        let mut text = String::new();
+        let structure = match self.structure {
+            BettingStructure::NoLimit => "No Limit",
+            BettingStructure::PotLimit => "Pot Limit",
+        };
+        let _ = write!(
+            text,
+            "PokerStars Hand #{}: Hold'em {} ({}/{})",
+            self.hand_id, structure, self.small_blind, self.big_blind
+        );
+        if let Some(date) = &self.date {
+            let _ = write!(text, " - {}", date);
+        }
+        let _ = writeln!(
+            text,
+            "\nTable '{}' {}-max Seat #{} is the button",
+            self.table,
+            self.players.len(),
+            self.players[self.button].seat
+        );
+        for player in &self.players {
+            let _ = writeln!(
+                text,
+                "Seat {}: {} ({} in chips)",
+                player.seat, player.name, player.stack
+            );
+        }
+
+        let name = |seat: usize| &self.players[seat].name;
+        let mut wrote_hole_cards = false;
+        let mut current_bet = 0;
+        for event in &self.events {
+            let is_post = matches!(
+                event,
+                Event::PostAnte { .. }
+                    | Event::PostSmallBlind { .. }
+                    | Event::PostBigBlind { .. }
+                    | Event::PostStraddle { .. }
+            );
+            if !is_post && !wrote_hole_cards {
+                text.push_str("*** HOLE CARDS ***\n");
+                for player in &self.players {
+                    if let Some(cards) = player.hole_cards {
+                        let _ = writeln!(text, "Dealt to {} [{}]", player.name, card_list(&cards));
+                    }
+                }
+                wrote_hole_cards = true;
+            }
+            match *event {
+                Event::PostAnte { seat, amount } => {
+                    let _ = writeln!(text, "{}: posts the ante {}", name(seat), amount);
+                }
+                Event::PostSmallBlind { seat, amount } => {
+                    let _ = writeln!(text, "{}: posts small blind {}", name(seat), amount);
+                    current_bet = current_bet.max(amount);
+                }
+                Event::PostBigBlind { seat, amount } => {
+                    let _ = writeln!(text, "{}: posts big blind {}", name(seat), amount);
+                    current_bet = current_bet.max(amount);
+                }
+                Event::PostStraddle { seat, amount } => {
+                    let _ = writeln!(text, "{}: posts straddle {}", name(seat), amount);
+                    current_bet = current_bet.max(amount);
+                }
+                Event::Act {
+                    seat,
+                    action,
+                    amount,
+                    all_in,
+                    ..
+                } => {
+                    let description = match action {
+                        Action::Fold => "folds".to_string(),
+                        Action::Check => "checks".to_string(),
+                        Action::Call => format!("calls {}", amount),
+                        Action::Bet(to) => {
+                            current_bet = to;
+                            format!("bets {}", amount)
+                        }
+                        Action::Raise(to) => {
+                            let raise = to - current_bet;
+                            current_bet = to;
+                            format!("raises {} to {}", raise, to)
+                        }
+                    };
+                    let all_in = if all_in { " and is all-in" } else { "" };
+                    let _ = writeln!(text, "{}: {}{}", name(seat), description, all_in);
+                }
+                Event::ReturnUncalled { seat, amount } => {
+                    let _ = writeln!(text, "Uncalled bet ({}) returned to {}", amount, name(seat));
+                }
+                Event::Deal { street } => {
+                    current_bet = 0;
+                    let shown = street.board_cards().min(self.board.len());
+                    if street == Street::Flop {
+                        let _ =
+                            writeln!(text, "*** FLOP *** [{}]", card_list(&self.board[..shown]));
+                    } else {
+                        let label = if street == Street::Turn {
+                            "TURN"
+                        } else {
+                            "RIVER"
+                        };
+                        let (before, new) = self.board[..shown].split_at(shown.saturating_sub(1));
+                        let _ = writeln!(
+                            text,
+                            "*** {} *** [{}] [{}]",
+                            label,
+                            card_list(before),
+                            card_list(new)
+                        );
+                    }
+                }
+            }
+        }
+
+        let live = self.showdown_players();
+        if live.len() > 1 {
+            text.push_str("*** SHOW DOWN ***\n");
+            for seat in live {
+                if let Some(cards) = self.players[seat].hole_cards {
+                    let all = [&cards[..], &self.board[..]].concat();
+                    let description = match PokerHand::best_of(&all) {
+                        Ok(hand) => format!(" ({})", hand.hand_rank),
+                        Err(_) => String::new(),
+                    };
+                    let _ = writeln!(
+                        text,
+                        "{}: shows [{}]{}",
+                        name(seat),
+                        card_list(&cards),
+                        description
+                    );
+                }
+            }
+        }
+        let side_pots = self.awards.iter().any(|award| award.pot > 0);
+        for award in &self.awards {
+            let pot = match (award.pot, side_pots) {
+                (0, false) => "pot".to_string(),
+                (0, true) => "main pot".to_string(),
+                (pot, _) => format!("side pot-{}", pot),
+            };
+            let _ = writeln!(
+                text,
+                "{} collected {} from {}",
+                name(award.seat),
+                award.amount,
+                pot
+            );
+        }
+        text.push_str("*** SUMMARY ***\n");
+        let total: u64 = self.awards.iter().map(|award| award.amount).sum::<u64>() + self.rake;
+        let _ = writeln!(text, "Total pot {} | Rake {}", total, self.rake);
+        if !self.board.is_empty() {
+            let _ = writeln!(text, "Board [{}]", card_list(&self.board));
+        }
+        Ok(text)
+    }
+
+    /// Read a hand in the PokerStars-like text format.
+    ///
+    /// Besides the output of `to_text`, this accepts the exports of the common poker sites:
+    /// cards may be written as "Th" or "10H", cash game amounts may carry a currency symbol,
+    /// and lines which do not affect the hand, such as chat or players joining the table,
+    /// are skipped. Cash game amounts are converted to cents.
+    pub fn parse_text(text: &str) -> Result<HandHistory, PokerHandError> {
+        HandHistory::parse_text_from(text, 1)
+    }
+
+    /// Read a hand whose first line is line `first_line` of a larger file, so that errors
+    /// report the line in the file.
+    pub(crate) fn parse_text_from(
+        text: &str,
+        first_line: usize,
+    ) -> Result<HandHistory, PokerHandError> {
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .map(|(number, line)| {
+                (
+                    number + first_line,
+                    line.trim_start_matches('\u{feff}').trim(),
+                )
+            })
+            .filter(|(_, line)| !line.is_empty());
+        let error = |number: usize, message: &str| {
+            PokerHandError::new(&format!("Line {}: {}", number, message))
+        };
+
+        // The header line: "PokerStars Hand #ID: Hold'em No Limit (SB/BB) - DATE".
+        let (number, header) = lines
+            .next()
+            .ok_or_else(|| PokerHandError::new("The hand history is empty"))?;
+        let rest = header
+            .split_once("Hand #")
+            .map(|(_, rest)| rest)
+            .ok_or_else(|| error(number, "Missing hand number"))?;
+        let (hand_id, rest) = rest
+            .split_once(':')
+            .ok_or_else(|| error(number, "Missing hand number"))?;
+        if !rest.contains("Hold'em") && !rest.contains("Holdem") {
+            return Err(error(number, "Only hold'em hands are supported"));
+        }
+        let structure = if rest.contains("Pot Limit") {
+            BettingStructure::PotLimit
+        } else if rest.contains("No Limit") {
+            BettingStructure::NoLimit
+        } else {
+            return Err(error(number, "Unsupported betting structure"));
+        };
+        // The stakes are the first bracketed pair of amounts, such as "($0.01/$0.02 USD)"
+        // or, in a tournament, "Level I (10/20)".
+        let blinds = rest
+            .split('(')
+            .filter_map(|group| group.split_once(')').map(|(group, _)| group))
+            .find(|group| group.contains('/'))
+            .ok_or_else(|| error(number, "Missing blinds"))?;
+        let cents = blinds.contains(|c| CURRENCY_SYMBOLS.contains(&c) || c == '.');
+        let (small_blind, big_blind) = blinds
+            .split_once('/')
+            .and_then(|(small, big)| {
+                let big = big.split_whitespace().next()?;
+                Some((parse_amount(small, cents)?, parse_amount(big, cents)?))
+            })
+            .ok_or_else(|| error(number, "Invalid blinds"))?;
+        let date = parse_date(rest);
+
+        // The table line: "Table 'NAME' N-max Seat #B is the button".
+        let (number, table_line) = lines
+            .next()
+            .ok_or_else(|| PokerHandError::new("Missing table line"))?;
+        let table = between(table_line, '\'', '\'')
+            .ok_or_else(|| error(number, "Missing table name"))?
+            .to_string();
+        let button_seat = table_line
+            .split_once("Seat #")
+            .and_then(|(_, rest)| rest.split_whitespace().next()?.parse::<usize>().ok())
+            .ok_or_else(|| error(number, "Missing button seat"))?;
+
+        let mut history = HandHistory {
+            hand_id: hand_id.trim().to_string(),
+            table,
+            date,
+            structure,
+            small_blind,
+            big_blind,
+            ante: 0,
+            straddle: None,
+            button: 0,
+            players: Vec::new(),
+            events: Vec::new(),
+            board: Vec::new(),
+            awards: Vec::new(),
+            rake: 0,
+        };
+        let mut street = Street::Preflop;
+        let mut street_bets: Vec<u64> = Vec::new();
+        let mut in_summary = false;
+        for (number, line) in lines {
+            if in_summary {
+                if let Some(rake) = line.split_once("| Rake ").map(|(_, rake)| rake) {
+                    let rake = rake.split_whitespace().next().unwrap_or("");
+                    history.rake =
+                        parse_amount(rake, cents).ok_or_else(|| error(number, "Invalid rake"))?;
+                }
+                continue;
+            }
+            if line.starts_with("Seat ") && history.events.is_empty() {
+                // Players sitting out are not dealt in, so they take no part in the hand.
+                if line.contains("sitting out") || line.contains("out of hand") {
+                    continue;
+                }
+                let player =
+                    parse_seat_line(line, cents).ok_or_else(|| error(number, "Invalid seat"))?;
+                history.players.push(player);
+                street_bets.push(0);
+                continue;
+            }
+            if let Some(marker) = line.strip_prefix("*** ") {
+                if marker.starts_with("FIRST") || marker.starts_with("SECOND") {
+                    return Err(error(number, "Hands run more than once are not supported"));
+                }
+                let next = if marker.starts_with("FLOP") {
+                    Some(Street::Flop)
+                } else if marker.starts_with("TURN") {
+                    Some(Street::Turn)
+                } else if marker.starts_with("RIVER") {
+                    Some(Street::River)
+                } else {
+                    None
+                };
+                if let Some(next) = next {
+                    // The last bracketed group holds the newly dealt cards.
+                    let new = line
+                        .rsplit_once('[')
+                        .and_then(|(_, cards)| cards.strip_suffix(']'))
+                        .ok_or_else(|| error(number, "Missing board cards"))?;
+                    for card in new.split_whitespace() {
+                        let card = parse_card(card).ok_or_else(|| error(number, "Invalid card"))?;
+                        history.board.push(card);
+                    }
+                    street = next;
+                    street_bets.iter_mut().for_each(|bet| *bet = 0);
+                    history.events.push(Event::Deal { street });
+                } else if marker.starts_with("SUMMARY") {
+                    in_summary = true;
+                }
+                continue;
+            }
+            if let Some(rest) = line.strip_prefix("Dealt to ") {
+                // Some sites list every player, but only show the recording player's cards.
+                if let Some((seat, cards)) = history.find_player_prefix(rest, " [") {
+                    history.players[seat].hole_cards = Some(
+                        parse_hole_cards(cards).ok_or_else(|| error(number, "Invalid cards"))?,
+                    );
+                }
+                continue;
+            }
+            if let Some(rest) = line.strip_prefix("Uncalled bet (") {
+                let (amount, name) = rest
+                    .split_once(") returned to ")
+                    .ok_or_else(|| error(number, "Invalid uncalled bet"))?;
+                let amount =
+                    parse_amount(amount, cents).ok_or_else(|| error(number, "Invalid amount"))?;
+                let seat = history
+                    .seat_of(name)
+                    .ok_or_else(|| error(number, "Unknown player"))?;
+                if amount > street_bets[seat] {
+                    return Err(error(number, "Uncalled bet is more than was bet"));
+                }
+                street_bets[seat] -= amount;
+                history.events.push(Event::ReturnUncalled { seat, amount });
+                continue;
+            }
+            if let Some((seat, rest)) = history.find_player_prefix(line, " collected ") {
+                let (amount, pot) = rest
+                    .split_once(" from ")
+                    .ok_or_else(|| error(number, "Invalid collection"))?;
+                let amount =
+                    parse_amount(amount, cents).ok_or_else(|| error(number, "Invalid amount"))?;
+                let pot = pot
+                    .trim()
+                    .strip_prefix("side pot")
+                    .and_then(|pot| pot.trim_start_matches(['-', ' ']).parse().ok())
+                    .unwrap_or(0);
+                history.awards.push(Award { pot, seat, amount });
+                continue;
+            }
+            // Anything else that is not a player's action, such as players joining or
+            // leaving the table, does not affect the hand.
+            if let Some((seat, action)) = history.find_player_prefix(line, ": ") {
+                history
+                    .apply_action_line(seat, action, street, &mut street_bets, cents)
+                    .map_err(|message| error(number, message))?;
+            }
+        }
+        if history.players.is_empty() {
+            return Err(PokerHandError::new("The hand history has no players"));
+        }
+        history.button = history
+            .players
+            .iter()
+            .position(|player| player.seat == button_seat)
+            .ok_or_else(|| PokerHandError::new("The button is not on an occupied seat"))?;
+        Ok(history)
+    }
+
+    /// Apply a "name: action" line, other than the ones handled directly by the parser.
+    fn apply_action_line(
+        &mut self,
+        seat: usize,
+        action: &str,
+        street: Street,
+        street_bets: &mut [u64],
+        cents: bool,
+    ) -> Result<(), &'static str> {
+        let all_in = action.ends_with(" and is all-in");
+        let action = action.trim_end_matches(" and is all-in");
+        let verb = action.split_whitespace().next().unwrap_or("");
+        let amounts: Vec<u64> = action
+            .split_whitespace()
+            .filter_map(|word| parse_amount(word, cents))
+            .collect();
+        let amount = amounts.first().copied();
+        let event = match verb {
+            "posts" => {
+                let amount = amounts.last().copied().ok_or("Missing amount")?;
+                if action.contains("ante") {
+                    self.ante = self.ante.max(amount);
+                    Event::PostAnte { seat, amount }
+                } else if action.contains("small & big blinds") {
+                    // A missed small blind is dead money, while the big blind is live.
+                    let live = amount.min(self.big_blind);
+                    self.events.push(Event::PostAnte {
+                        seat,
+                        amount: amount - live,
+                    });
+                    street_bets[seat] += live;
+                    Event::PostBigBlind { seat, amount: live }
+                } else if action.contains("small blind") {
+                    street_bets[seat] += amount;
+                    Event::PostSmallBlind { seat, amount }
+                } else if action.contains("big blind") {
+                    street_bets[seat] += amount;
+                    Event::PostBigBlind { seat, amount }
+                } else if action.contains("straddle") {
+                    street_bets[seat] += amount;
+                    self.straddle = Some(amount);
+                    Event::PostStraddle { seat, amount }
+                } else {
+                    return Err("Unknown post");
+                }
+            }
+            "shows" => {
+                let cards = action.split_once('[').map(|(_, cards)| cards).unwrap_or("");
+                self.players[seat].hole_cards =
+                    Some(parse_hole_cards(cards).ok_or("Invalid cards")?);
+                return Ok(());
+            }
+            "folds" | "checks" | "calls" | "bets" | "raises" => {
+                let (action, amount) = match verb {
+                    "folds" => (Action::Fold, 0),
+                    "checks" => (Action::Check, 0),
+                    "calls" => (Action::Call, amount.ok_or("Missing amount")?),
+                    "bets" => {
+                        let amount = amount.ok_or("Missing amount")?;
+                        (Action::Bet(street_bets[seat] + amount), amount)
+                    }
+                    _ => {
+                        let to = amounts.last().copied().ok_or("Missing amount")?;
+                        (Action::Raise(to), to.saturating_sub(street_bets[seat]))
+                    }
+                };
+                street_bets[seat] += amount;
+                Event::Act {
+                    street,
+                    seat,
+                    action,
+                    amount,
+                    all_in,
+                }
+            }
+            _ if IGNORED_ACTIONS
+                .iter()
+                .any(|ignored| action.starts_with(ignored)) =>
+            {
+                return Ok(());
+            }
+            _ => return Err("Unknown action"),
+        };
+        self.events.push(event);
+        Ok(())
+    }
+
+    /// Write the hand in the PHH format.
+    ///
+    /// Fails if the hand is not no-limit, if a seat is not at the table, if the board is
+    /// missing cards which were dealt, or if the chips put in do not add up.
+    pub fn to_phh(&self) -> Result<String, PokerHandError> {
+        self.check_seats()?;
+        if self.structure != BettingStructure::NoLimit {
+            return Err(PokerHandError::new(
+                "Only no-limit hold'em hands can be written as PHH",
+            ));
+        }
+        let n = self.players.len();
+        // PHH player i (from 0) is the i'th seat clockwise after the button.
+        let order: Vec<usize> = (1..=n).map(|i| (self.button + i) % n).collect();
+        let phh_index = |seat: usize| {
+            order
+                .iter()
+                .position(|&s| s == seat)
+                .map(|index| index + 1)
+                .ok_or_else(|| PokerHandError::new("An event refers to a seat not at the table"))
+        };
+
+        let mut blinds = vec![0u64; n];
+        let mut antes = vec![0u64; n];
+        let mut actions: Vec<String> = Vec::new();
+        for (i, &seat) in order.iter().enumerate() {
+            let cards = match self.players[seat].hole_cards {
+                Some(cards) => cards.iter().map(Card::to_short_string).collect(),
+                None => "????".to_string(),
+            };
+            actions.push(format!("d dh p{} {}", i + 1, cards));
+        }
+        for event in &self.events {
+            match *event {
+                Event::PostAnte { seat, amount } => antes[phh_index(seat)? - 1] = amount,
+                Event::PostSmallBlind { seat, amount }
+                | Event::PostBigBlind { seat, amount }
+                | Event::PostStraddle { seat, amount } => blinds[phh_index(seat)? - 1] = amount,
+                Event::Act { seat, action, .. } => {
+                    let action = match action {
+                        Action::Fold => "f".to_string(),
+                        Action::Check | Action::Call => "cc".to_string(),
+                        Action::Bet(to) | Action::Raise(to) => format!("cbr {}", to),
+                    };
+                    actions.push(format!("p{} {}", phh_index(seat)?, action));
+                }
+                Event::Deal { street } => {
+                    let end = street.board_cards();
+                    if end > self.board.len() {
+                        return Err(PokerHandError::new(
+                            "The board is missing cards which were dealt",
+                        ));
+                    }
+                    let start = if street == Street::Flop { 0 } else { end - 1 };
+                    let cards: String = self.board[start..end]
+                        .iter()
+                        .map(Card::to_short_string)
+                        .collect();
+                    actions.push(format!("d db {}", cards));
+                }
+                Event::ReturnUncalled { .. } => {}
+            }
+        }
+        let live = self.showdown_players();
+        if live.len() > 1 {
+            for &seat in &order {
+                if let (true, Some(cards)) = (live.contains(&seat), self.players[seat].hole_cards) {
+                    let cards: String = cards.iter().map(Card::to_short_string).collect();
+                    actions.push(format!("p{} sm {}", phh_index(seat)?, cards));
+                }
+            }
+        }
+
+        let won = totals(&self.awards);
+        let contributions = self.contributions()?;
+        let finishing: Vec<u64> = order
+            .iter()
+            .map(|&seat| {
+                let won = won.iter().find(|(s, _)| *s == seat).map_or(0, |(_, a)| *a);
+                self.players[seat]
+                    .stack
+                    .checked_sub(contributions[seat].amount)
+                    .and_then(|stack| stack.checked_add(won))
+                    .ok_or_else(|| PokerHandError::new("A player put in more than their stack"))
+            })
+            .collect::<Result<_, _>>()?;
+
+        let mut text = String::new();
+        text.push_str("variant = \"NT\"\n");
+        let _ = writeln!(text, "antes = {}", int_array(&antes));
+        let _ = writeln!(text, "blinds_or_straddles = {}", int_array(&blinds));
+        let _ = writeln!(text, "min_bet = {}", self.big_blind);
+        let starting: Vec<u64> = order.iter().map(|&seat| self.players[seat].stack).collect();
+        let _ = writeln!(text, "starting_stacks = {}", int_array(&starting));
+        text.push_str("actions = [\n");
+        for action in &actions {
+            let _ = writeln!(text, "  \"{}\",", action);
+        }
+        text.push_str("]\n");
+        let names: Vec<String> = order
+            .iter()
+            .map(|&seat| format!("\"{}\"", escape(&self.players[seat].name)))
+            .collect();
+        let _ = writeln!(text, "players = [{}]", names.join(", "));
+        let seats: Vec<u64> = order
+            .iter()
+            .map(|&seat| self.players[seat].seat as u64)
+            .collect();
+        let _ = writeln!(text, "seats = {}", int_array(&seats));
+        let _ = writeln!(text, "finishing_stacks = {}", int_array(&finishing));
+        let _ = writeln!(text, "_hand_id = \"{}\"", escape(&self.hand_id));
+        let _ = writeln!(text, "_table = \"{}\"", escape(&self.table));
+        if let Some(date) = &self.date {
+            let _ = writeln!(text, "_date = \"{}\"", escape(date));
+        }
+        Ok(text)
+    }
+
+    /// Read a no-limit hold'em hand in the PHH format.
+    ///
+    /// The winners are taken from `finishing_stacks` when present, and otherwise worked out
+    /// from the showdown.
+    pub fn parse_phh(text: &str) -> Result<HandHistory, PokerHandError> {
+        let fields = parse_toml_fields(text)?;
+        let field = |key: &str| fields.iter().find(|(k, _)| k == key).map(|(_, v)| v);
+        match field("variant") {
+            Some(TomlValue::Str(variant)) if variant == "NT" => {}
+            _ => return Err(PokerHandError::new("Only the NT variant is supported")),
+        }
+        let ints = |key: &str| -> Result<Vec<u64>, PokerHandError> {
+            match field(key) {
+                Some(TomlValue::Array(values)) => values
+                    .iter()
+                    .map(|value| match value {
+                        TomlValue::Int(int) => Ok(*int),
+                        _ => Err(PokerHandError::new(&format!("{} must hold integers", key))),
+                    })
+                    .collect(),
+                _ => Err(PokerHandError::new(&format!("Missing {}", key))),
+            }
+        };
+        let strings = |key: &str| -> Option<Vec<String>> {
+            match field(key) {
+                Some(TomlValue::Array(values)) => values
+                    .iter()
+                    .map(|value| match value {
+                        TomlValue::Str(string) => Some(string.clone()),
+                        _ => None,
+                    })
+                    .collect(),
+                _ => None,
+            }
+        };
+        let string = |key: &str| match field(key) {
+            Some(TomlValue::Str(string)) => Some(string.clone()),
+            Some(TomlValue::Int(int)) => Some(int.to_string()),
+            _ => None,
+        };
+
+        let stacks = ints("starting_stacks")?;
+        let n = stacks.len();
+        let antes = ints("antes")?;
+        let blinds = ints("blinds_or_straddles")?;
+        if n < 2 || antes.len() != n || blinds.len() != n {
+            return Err(PokerHandError::new(
+                "Player arrays must all have the same length",
+            ));
+        }
+        let names =
+            strings("players").unwrap_or_else(|| (1..=n).map(|i| format!("p{}", i)).collect());
+        let seats = ints("seats").unwrap_or_else(|_| (1..=n as u64).collect());
+        if names.len() != n || seats.len() != n {
+            return Err(PokerHandError::new(
+                "Player arrays must all have the same length",
+            ));
+        }
+        // The blinds are told apart by size: small blind, big blind, then any straddle.
+        let mut posted: Vec<usize> = (0..n).filter(|&i| blinds[i] > 0).collect();
+        posted.sort_by_key(|&i| blinds[i]);
+        let (small_blind, big_blind, straddle) = match posted[..] {
+            [small, big] => (blinds[small], blinds[big], None),
+            [small, big, straddle] => (blinds[small], blinds[big], Some(blinds[straddle])),
+            _ => return Err(PokerHandError::new("Unsupported blinds")),
+        };
+
+        let mut history = HandHistory {
+            hand_id: string("_hand_id")
+                .or_else(|| string("hand"))
+                .unwrap_or_default(),
+            table: string("_table")
+                .or_else(|| string("table"))
+                .unwrap_or_default(),
+            date: string("_date"),
+            structure: BettingStructure::NoLimit,
+            small_blind,
+            big_blind,
+            ante: antes.iter().copied().max().unwrap_or(0),
+            straddle,
+            button: n - 1,
+            players: (0..n)
+                .map(|i| PlayerRecord {
+                    seat: seats[i] as usize,
+                    name: names[i].clone(),
+                    stack: stacks[i],
+                    hole_cards: None,
+                })
+                .collect(),
+            events: Vec::new(),
+            board: Vec::new(),
+            awards: Vec::new(),
+            rake: 0,
+        };
+
+        let mut replay = Replay::new(&stacks);
+        for (seat, &ante) in antes.iter().enumerate() {
+            if ante > 0 {
+                let amount = replay.post(seat, ante, false);
+                history.events.push(Event::PostAnte { seat, amount });
+            }
+        }
+        for (role, &seat) in posted.iter().enumerate() {
+            let amount = replay.post(seat, blinds[seat], true);
+            history.events.push(match role {
+                0 => Event::PostSmallBlind { seat, amount },
+                1 => Event::PostBigBlind { seat, amount },
+                _ => Event::PostStraddle { seat, amount },
+            });
+        }
+
+        let actions = strings("actions").ok_or_else(|| PokerHandError::new("Missing actions"))?;
+        let mut street = Street::Preflop;
+        for action in &actions {
+            let words: Vec<&str> = action.split_whitespace().collect();
+            let invalid = || PokerHandError::new(&format!("Invalid action '{}'", action));
+            match words[..] {
+                ["d", "dh", player, cards] => {
+                    let seat = phh_player(player, n).ok_or_else(invalid)?;
+                    if !cards.starts_with('?') {
+                        history.players[seat].hole_cards =
+                            Some(parse_short_pair(cards).ok_or_else(invalid)?);
+                    }
+                }
+                ["d", "db", cards] => {
+                    for i in (0..cards.len()).step_by(2) {
+                        let card = cards.get(i..i + 2).ok_or_else(invalid)?;
+                        history.board.push(Card::from_short_str(card)?);
+                    }
+                    history.events.extend(replay.end_street());
+                    street = match history.board.len() {
+                        3 => Street::Flop,
+                        4 => Street::Turn,
+                        5 => Street::River,
+                        _ => return Err(invalid()),
+                    };
+                    history.events.push(Event::Deal { street });
+                }
+                [player, "sm", cards] => {
+                    let seat = phh_player(player, n).ok_or_else(invalid)?;
+                    history.players[seat].hole_cards =
+                        Some(parse_short_pair(cards).ok_or_else(invalid)?);
+                }
+                [player, "f"] => {
+                    let seat = phh_player(player, n).ok_or_else(invalid)?;
+                    history.events.push(replay.act(street, seat, None, true));
+                }
+                [player, "cc"] => {
+                    let seat = phh_player(player, n).ok_or_else(invalid)?;
+                    history.events.push(replay.act(street, seat, None, false));
+                }
+                [player, "cbr", to] => {
+                    let seat = phh_player(player, n).ok_or_else(invalid)?;
+                    let to = to.parse().map_err(|_| invalid())?;
+                    history
+                        .events
+                        .push(replay.act(street, seat, Some(to), false));
+                }
+                _ => return Err(invalid()),
+            }
+        }
+        history.events.extend(replay.end_street());
+
+        history.awards = match ints("finishing_stacks") {
+            Ok(finishing) if finishing.len() == n => {
+                let contributions = history.contributions()?;
+                (0..n)
+                    .filter_map(|seat| {
+                        let amount = finishing[seat]
+                            .checked_add(contributions[seat].amount)?
+                            .checked_sub(stacks[seat])?;
+                        if amount > 0 {
+                            Some(Award {
+                                pot: 0,
+                                seat,
+                                amount,
+                            })
+                        } else {
+                            None
+                        }
+                    })
+                    .collect()
+            }
+            _ => history.expected_awards()?,
+        };
+        Ok(history)
+    }
+
+    fn seat_of(&self, name: &str) -> Option<usize> {
+        self.players
+            .iter()
+            .position(|player| player.name == name.trim())
+    }
+
+    /// Find the player whose name, followed by the separator, starts the line, and return
+    /// their seat and the rest of the line. The longest matching name wins, so that names
+    /// which are prefixes of other names are handled.
+    fn find_player_prefix<'t>(&self, line: &'t str, separator: &str) -> Option<(usize, &'t str)> {
+        self.players
+            .iter()
+            .enumerate()
+            .filter(|(_, player)| {
+                line.starts_with(&player.name) && line[player.name.len()..].starts_with(separator)
+            })
+            .max_by_key(|(_, player)| player.name.len())
+            .map(|(seat, player)| (seat, &line[player.name.len() + separator.len()..]))
+    }
+}
+
+/// Tracks the betting while replaying PHH actions, which do not record the chip amounts.
+struct Replay {
+    stacks: Vec<u64>,
+    street_bets: Vec<u64>,
+    current_bet: u64,
+}
+
+impl Replay {
+    fn new(stacks: &[u64]) -> Replay {
+        Replay {
+            stacks: stacks.to_vec(),
+            street_bets: vec![0; stacks.len()],
+            current_bet: 0,
+        }
+    }
+
+    fn post(&mut self, seat: usize, amount: u64, counts_as_bet: bool) -> u64 {
+        let amount = amount.min(self.stacks[seat]);
+        self.stacks[seat] -= amount;
+        if counts_as_bet {
+            self.street_bets[seat] += amount;
+            self.current_bet = self.current_bet.max(self.street_bets[seat]);
+        }
+        amount
+    }
+
+    /// A fold, a check or call (`to` is None), or a bet or raise to `to`.
+    fn act(&mut self, street: Street, seat: usize, to: Option<u64>, fold: bool) -> Event {
+        let (action, amount) = if fold {
+            (Action::Fold, 0)
+        } else if let Some(to) = to {
+            let action = if self.current_bet == 0 {
+                Action::Bet(to)
+            } else {
+                Action::Raise(to)
+            };
+            let amount = self.post(seat, to.saturating_sub(self.street_bets[seat]), true);
+            (action, amount)
+        } else if self.current_bet > self.street_bets[seat] {
+            let amount = self.post(seat, self.current_bet - self.street_bets[seat], true);
+            (Action::Call, amount)
+        } else {
+            (Action::Check, 0)
+        };
+        Event::Act {
+            street,
+            seat,
+            action,
+            amount,
+            all_in: !fold && self.stacks[seat] == 0,
+        }
+    }
+
+    /// Close the betting round, returning any uncalled part of the highest bet.
+    fn end_street(&mut self) -> Option<Event> {
+        let mut bets: Vec<(u64, usize)> = self
+            .street_bets
+            .iter()
+            .enumerate()
+            .map(|(seat, &bet)| (bet, seat))
+            .collect();
+        bets.sort_unstable_by(|a, b| b.cmp(a));
+        let (highest, seat) = bets[0];
+        let second = bets.get(1).map_or(0, |&(bet, _)| bet);
+        self.street_bets.iter_mut().for_each(|bet| *bet = 0);
+        self.current_bet = 0;
+        if highest > second {
+            let amount = highest - second;
+            self.stacks[seat] += amount;
+            Some(Event::ReturnUncalled { seat, amount })
+        } else {
+            None
+        }
+    }
+}
+
+/// A value in the subset of TOML used by PHH files.
+#[derive(Debug, Clone, PartialEq)]
+enum TomlValue {
+    Int(u64),
+    Str(String),
+    Bool(bool),
+    Float(f64),
+    Array(Vec<TomlValue>),
+}
+
+/// Parse the top level "key = value" pairs, where arrays may span several lines.
+fn parse_toml_fields(text: &str) -> Result<Vec<(String, TomlValue)>, PokerHandError> {
+    let mut fields = Vec::new();
+    let mut pending: Option<(String, String)> = None;
+    for line in text.lines() {
+        let line = strip_comment(line);
+        if let Some((key, mut value)) = pending.take() {
+            value.push_str(line);
+            value.push('\n');
+            if brackets_balanced(&value) {
+                fields.push((key, parse_toml_value(value.trim())?));
+            } else {
+                pending = Some((key, value));
+            }
+            continue;
+        }
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('[') {
+            continue;
+        }
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| PokerHandError::new(&format!("Invalid line '{}'", line)))?;
+        let (key, value) = (key.trim().to_string(), value.trim().to_string());
+        if brackets_balanced(&value) {
+            fields.push((key, parse_toml_value(&value)?));
+        } else {
+            pending = Some((key, value + "\n"));
+        }
+    }
+    if pending.is_some() {
+        return Err(PokerHandError::new("Unterminated array"));
+    }
+    Ok(fields)
+}
+
+fn parse_toml_value(value: &str) -> Result<TomlValue, PokerHandError> {
+    let invalid = || PokerHandError::new(&format!("Invalid value '{}'", value));
+    if let Some(inner) = value.strip_prefix('[') {
+        let inner = inner.strip_suffix(']').ok_or_else(invalid)?;
+        let mut items = Vec::new();
+        for item in split_array_items(inner) {
+            items.push(parse_toml_value(&item)?);
+        }
+        return Ok(TomlValue::Array(items));
+    }
+    if let Some(inner) = value.strip_prefix('"') {
+        let inner = inner.strip_suffix('"').ok_or_else(invalid)?;
+        return Ok(TomlValue::Str(
+            inner.replace("\\\"", "\"").replace("\\\\", "\\"),
+        ));
+    }
+    match value {
+        "true" => return Ok(TomlValue::Bool(true)),
+        "false" => return Ok(TomlValue::Bool(false)),
+        _ => {}
+    }
+    let number = value.replace('_', "");
+    if let Ok(int) = number.parse() {
+        return Ok(TomlValue::Int(int));
+    }
+    number.parse().map(TomlValue::Float).map_err(|_| invalid())
+}
+
+/// Split the items of an array on the commas outside strings and nested arrays.
+fn split_array_items(inner: &str) -> Vec<String> {
+    let mut items = Vec::new();
+    let mut current = String::new();
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+    for c in inner.chars() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_string => escaped = true,
+            '"' => in_string = !in_string,
+            '[' if !in_string => depth += 1,
+            ']' if !in_string => depth -= 1,
+            ',' if !in_string && depth == 0 => {
+                items.push(current.trim().to_string());
+                current.clear();
+                continue;
+            }
+            _ => {}
+        }
+        current.push(c);
+    }
+    if !current.trim().is_empty() {
+        items.push(current.trim().to_string());
+    }
+    items
+}
+
+fn strip_comment(line: &str) -> &str {
+    let mut in_string = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => in_string = !in_string,
+            '#' if !in_string => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
+
+fn brackets_balanced(value: &str) -> bool {
+    let mut depth = 0;
+    let mut in_string = false;
+    for c in value.chars() {
+        match c {
+            '"' => in_string = !in_string,
+            '[' if !in_string => depth += 1,
+            ']' if !in_string => depth -= 1,
+            _ => {}
+        }
+    }
+    depth == 0
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn int_array(values: &[u64]) -> String {
+    let values: Vec<String> = values.iter().map(u64::to_string).collect();
+    format!("[{}]", values.join(", "))
+}
+
+/// The player index of a PHH player reference such as "p3".
+fn phh_player(player: &str, players: usize) -> Option<usize> {
+    let index: usize = player.strip_prefix('p')?.parse().ok()?;
+    if index >= 1 && index <= players {
+        Some(index - 1)
+    } else {
+        None
+    }
+}
+
+fn parse_short_pair(cards: &str) -> Option<[Card; 2]> {
+    if cards.len() != 4 {
+        return None;
+    }
+    Some([
+        Card::from_short_str(cards.get(..2)?).ok()?,
+        Card::from_short_str(cards.get(2..)?).ok()?,
+    ])
+}
+
+/// Parse "c1 c2]..." or "c1 c2" into two cards, in either notation.
+fn parse_hole_cards(cards: &str) -> Option<[Card; 2]> {
+    let cards = cards.split(']').next()?;
+    let mut parsed = cards.split_whitespace().map(parse_card);
+    let first = parsed.next()??;
+    let second = parsed.next()??;
+    if parsed.next().is_some() {
+        return None;
+    }
+    Some([first, second])
+}
+
+/// Parse a card written as "Th", as most sites do, or in the crate's notation, "10H".
+fn parse_card(card: &str) -> Option<Card> {
+    Card::from_short_str(card).or_else(|_| card.parse()).ok()
+}
+
+/// Parse "Seat N: NAME (S in chips)".
+fn parse_seat_line(line: &str, cents: bool) -> Option<PlayerRecord> {
+    let (seat, rest) = line.strip_prefix("Seat ")?.split_once(": ")?;
+    let (name, chips) = rest.rsplit_once(" (")?;
+    let stack = parse_amount(chips.split_whitespace().next()?, cents)?;
+    Some(PlayerRecord {
+        seat: seat.trim().parse().ok()?,
+        name: name.to_string(),
+        stack,
+        hole_cards: None,
+    })
+}
+
+/// Parse an amount such as "1,500", or, when `cents` is set, "$0.25" as 25.
+fn parse_amount(amount: &str, cents: bool) -> Option<u64> {
+    let amount = amount
+        .trim()
+        .trim_start_matches(|c| CURRENCY_SYMBOLS.contains(&c))
+        .trim_end_matches([')', ','])
+        .replace(',', "");
+    if !cents {
+        return amount.parse().ok();
+    }
+    let (whole, fraction) = amount.split_once('.').unwrap_or((&amount, ""));
+    if fraction.len() > 2 || !fraction.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let fraction: u64 = format!("{:0<2}", fraction).parse().ok()?;
+    Some(whole.parse::<u64>().ok()? * 100 + fraction)
+}
+
+/// Find the date and time, written "YYYY/MM/DD H:MM:SS", and pad it to
+/// "YYYY/MM/DD HH:MM:SS" so that dates compare in order.
+fn parse_date(header: &str) -> Option<String> {
+    let mut words = header.split_whitespace();
+    while let Some(word) = words.next() {
+        let word = word.trim_start_matches('[');
+        let date: Vec<&str> = word.split('/').collect();
+        if date.len() != 3 || date[0].len() != 4 {
+            continue;
+        }
+        let time: Vec<&str> = words.next()?.split(':').collect();
+        let numbers: Option<Vec<u32>> = date
+            .iter()
+            .chain(&time)
+            .map(|number| number.parse().ok())
+            .collect();
+        return match numbers?[..] {
+            [year, month, day, hour, minute, second] => Some(format!(
+                "{:04}/{:02}/{:02} {:02}:{:02}:{:02}",
+                year, month, day, hour, minute, second
+            )),
+            _ => None,
+        };
+    }
+    None
+}
+
+fn between(text: &str, open: char, close: char) -> Option<&str> {
+    let (_, rest) = text.split_once(open)?;
+    let (inner, _) = rest.split_once(close)?;
+    Some(inner)
+}
+
+/// The currency symbols which may precede cash game amounts.
+const CURRENCY_SYMBOLS: [char; 3] = ['$', '\u{20ac}', '\u{a3}'];
+
+/// Player lines which carry no information about the hand.
+const IGNORED_ACTIONS: [&str; 9] = [
+    "mucks",
+    "doesn't show",
+    "sits out",
+    "is sitting out",
+    "has timed out",
+    "is disconnected",
+    "is connected",
+    "has returned",
+    "leaves the table",
+];
+
+fn card_list(cards: &[Card]) -> String {
+    let cards: Vec<String> = cards.iter().map(Card::to_string).collect();
+    cards.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards::parse_cards;
+    use crate::rake::RakeRule;
+    use crate::table::TableConfig;
+
+    /// A three handed hand where the short stack is all in and loses to a side pot.
+    fn played_hand(rake: Option<RakeRule>) -> TableHand {
+        let hole_cards: Vec<[Card; 2]> = ["AS AD", "KS KD", "7C 2D"]
+            .iter()
+            .map(|cards| {
+                let cards = parse_cards(cards).unwrap();
+                [cards[0], cards[1]]
+            })
+            .collect();
+        let board = parse_cards("AH 9C 5S 3D JC").unwrap();
+        let board = [board[0], board[1], board[2], board[3], board[4]];
+        let config = TableConfig {
+            ante: 1,
+            rake,
+            ..TableConfig::no_limit(1, 2)
+        };
+        let mut hand = TableHand::new(&config, &[100, 60, 100], 0, &hole_cards, board).unwrap();
+        hand.act(0, Action::Raise(6)).unwrap();
+        hand.act(1, Action::Call).unwrap();
+        hand.act(2, Action::Call).unwrap();
+        hand.act(1, Action::Check).unwrap();
+        hand.act(2, Action::Bet(10)).unwrap();
+        hand.act(0, Action::Raise(30)).unwrap();
+        hand.act(1, Action::Raise(53)).unwrap();
+        hand.act(2, Action::Fold).unwrap();
+        hand.act(0, Action::Call).unwrap();
+        assert!(hand.is_complete());
+        hand
+    }
+
+    fn history() -> HandHistory {
+        let mut history = HandHistory::from_table(
+            &played_hand(None),
+            "1001",
+            "Alpha",
+            &["alice", "bob", "carol"],
+        )
+        .unwrap();
+        history.date = Some("2026/10/18 12:00:00".to_string());
+        history
+    }
+
+    #[test]
+    fn test_text_round_trip() {
+        let history = history();
+        let text = history.to_text().unwrap();
+        assert!(text.contains("Seat #1 is the button"));
+        assert!(text.contains("alice: raises 4 to 6"));
+        assert!(text.contains("carol: bets 10"));
+        assert!(text.contains("bob: raises 23 to 53 and is all-in"));
+        assert!(text.contains("*** TURN *** [AH 9C 5S] [3D]"));
+        assert!(text.contains("alice: shows [AS AD] (three of a kind)"));
+        let parsed = HandHistory::parse_text(&text).unwrap();
+        assert_eq!(parsed, history);
+        parsed.validate_showdown().unwrap();
+    }
+
+    #[test]
+    fn test_raked_text_round_trip() {
+        let rake = RakeRule::new(500).unwrap().with_cap(3, 5);
+        let hand = played_hand(Some(rake));
+        let history =
+            HandHistory::from_table(&hand, "1002", "Alpha", &["alice", "bob", "carol"]).unwrap();
+        assert_eq!(history.rake, 5);
+        let text = history.to_text().unwrap();
+        assert!(text.contains("Total pot 137 | Rake 5"));
+        let parsed = HandHistory::parse_text(&text).unwrap();
+        assert_eq!(parsed, history);
+        parsed.validate_showdown().unwrap();
+    }
+
+    #[test]
+    fn test_phh_round_trip() {
+        let history = history();
+        let phh = history.to_phh().unwrap();
+        assert!(phh.contains("\"d db Ah9c5s\""));
+        assert!(phh.contains("blinds_or_straddles = [1, 2, 0]"));
+        let parsed = HandHistory::parse_phh(&phh).unwrap();
+        parsed.validate_showdown().unwrap();
+        assert_eq!(parsed.board, history.board);
+        assert_eq!(totals(&parsed.awards), vec![(2, 137)]);
+        // The players are listed from the small blind, so alice on the button is last.
+        assert_eq!(parsed.players[2].name, "alice");
+        assert_eq!(parsed.contributions().unwrap()[2].amount, 60);
+        assert_eq!(
+            parsed.to_text().unwrap().lines().count(),
+            history.to_text().unwrap().lines().count()
+        );
+    }
+
+    #[test]
+    fn test_wrong_winner_fails_validation() {
+        let mut history = history();
+        history.awards[0].seat = 1;
+        assert!(history.validate_showdown().is_err());
+        // A missing showdown hand cannot be validated either.
+        let mut history = self::history();
+        history.players[1].hole_cards = None;
+        assert!(history.validate_showdown().is_err());
+    }
+
+    #[test]
+    fn test_parse_errors_report_the_line() {
+        let text = history()
+            .to_text()
+            .unwrap()
+            .replace("carol: bets 10", "carol: dances");
+        let error = HandHistory::parse_text(&text).unwrap_err();
+        assert!(error.to_string().contains("Line 20"));
+        assert!(HandHistory::parse_text("").is_err());
+        assert!(HandHistory::parse_phh("variant = \"FT\"").is_err());
+    }
+
+    #[test]
+    fn test_phh_fold_preflop_without_cards() {
+        let phh = r#"
+variant = "NT"
+antes = [0, 0, 0]
+blinds_or_straddles = [1, 2, 0]  # small blind, big blind, button
+min_bet = 2
+starting_stacks = [200, 200, 200]
+actions = ["d dh p1 ????", "d dh p2 ????", "d dh p3 ????", "p3 cbr 6", "p1 f", "p2 f"]
+"#;
+        let history = HandHistory::parse_phh(phh).unwrap();
+        assert_eq!(history.button, 2);
+        assert_eq!(history.players[0].name, "p1");
+        assert_eq!(totals(&history.awards), vec![(2, 5)]);
+        assert!(history
+            .events
+            .contains(&Event::ReturnUncalled { seat: 2, amount: 4 }));
+        history.validate_showdown().unwrap();
+    }
+
+    #[test]
+    fn test_uncalled_bet_larger_than_bet() {
+        let text = history().to_text().unwrap().replace(
+            "carol: folds\n",
+            "carol: folds\nUncalled bet (500) returned to carol\n",
+        );
+        assert!(text.contains("Uncalled bet (500)"));
+        assert!(HandHistory::parse_text(&text).is_err());
+    }
+
+    #[test]
+    fn test_inconsistent_events() {
+        let mut returned = history();
+        returned.events.push(Event::ReturnUncalled {
+            seat: 2,
+            amount: 500,
+        });
+        assert!(returned.contributions().is_err());
+        assert!(returned.validate_showdown().is_err());
+        assert!(returned.to_phh().is_err());
+        returned.to_text().unwrap();
+
+        let mut unknown = history();
+        unknown.events.push(Event::PostAnte { seat: 9, amount: 1 });
+        assert!(unknown.contributions().is_err());
+        assert!(unknown.to_text().is_err());
+        assert!(unknown.to_phh().is_err());
+
+        let mut button = history();
+        button.button = 9;
+        assert!(button.to_text().is_err());
+        assert!(button.to_phh().is_err());
+
+        let mut award = history();
+        award.awards[0].seat = 9;
+        assert!(award.to_text().is_err());
+        assert!(award.to_phh().is_err());
+
+        let mut short_stack = history();
+        short_stack.players[0].stack = 5;
+        assert!(short_stack.to_phh().is_err());
+    }
+
+    #[test]
+    fn test_missing_board() {
+        let mut history = history();
+        history.board.clear();
+        assert!(history.to_text().unwrap().contains("*** TURN *** [] []"));
+        assert!(history.to_phh().is_err());
+    }
+}
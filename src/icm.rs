@@ -0,0 +1,343 @@
+//! The Independent Chip Model (ICM), for converting tournament chip stacks to prize equity.
+//!
+//! In a tournament, chips are not worth money in proportion to their number, because the
+//! prizes are fixed and paid by finishing place. The Malmuth-Harville model estimates each
+//! player's chance of finishing in each place from the stacks alone: a player finishes
+//! first with probability equal to their share of the chips, and, given who has finished
+//! ahead of them, finishes in each later place in proportion to their share of the chips
+//! that remain. A player's prize equity is the prizes weighted by those probabilities.
+//!
+//! The exact calculation considers every set of players who might finish in the paid
+//! places ahead of each other, which grows quickly with the size of the field, so for
+//! large fields the finishing orders can instead be sampled.
+//!
+//! The push/fold calculator applies the model to a single decision: a player moving all
+//! in, and one opponent deciding whether to call. It compares the prize equity of folding
+//! with that of shoving, using the all-in equity of the hands against the caller's range.
+
+use crate::cards::Card;
+use crate::equity::heads_up_odds;
+use crate::error::PokerHandError;
+use crate::rng::SeededRng;
+use crate::strength::Enumeration;
+use std::collections::BTreeMap;
+
+/// The most players the exact calculation supports.
+const MAX_PLAYERS: usize = 64;
+
+/// Each player's prize equity by the Malmuth-Harville model, calculated exactly.
+///
+/// `payouts` holds the prize for each place, first place first. Players with no chips
+/// are out of the tournament and win nothing here. The work grows with the number of ways
+/// the paid places can be filled, so this suits final tables; use `icm_equity_sampled` for
+/// large fields.
+pub fn icm_equity(stacks: &[u64], payouts: &[f64]) -> Result<Vec<f64>, PokerHandError> {
+    if stacks.len() > MAX_PLAYERS {
+        return Err(PokerHandError::new(
+            "The exact calculation supports at most 64 players",
+        ));
+    }
+    let total: u64 = stacks.iter().sum();
+    let live = stacks.iter().filter(|&&stack| stack > 0).count();
+    let mut equity = vec![0.0; stacks.len()];
+
+    // The probability of each set of players (as a bit mask) taking the places so far.
+    let mut finished: BTreeMap<u64, f64> = BTreeMap::new();
+    finished.insert(0, 1.0);
+    for &prize in payouts.iter().take(live) {
+        let mut next = BTreeMap::new();
+        for (&mask, &probability) in &finished {
+            let placed: u64 = (0..stacks.len())
+                .filter(|i| mask & (1 << i) != 0)
+                .map(|i| stacks[i])
+                .sum();
+            let remaining = (total - placed) as f64;
+            for (i, &stack) in stacks.iter().enumerate() {
+                if stack == 0 || mask & (1 << i) != 0 {
+                    continue;
+                }
+                let p = probability * stack as f64 / remaining;
+                equity[i] += p * prize;
+                *next.entry(mask | (1 << i)).or_insert(0.0) += p;
+            }
+        }
+        finished = next;
+    }
+    Ok(equity)
+}
+
+/// Each player's prize equity by the Malmuth-Harville model, estimated from `samples`
+/// random finishing orders drawn using the seed.
+///
+/// Each sample gives every player an exponentially distributed time to win with a rate
+/// equal to their stack, and orders the players by it, which draws finishing orders with
+/// exactly the model's probabilities. The cost is proportional to the number of players
+/// and samples, so this scales to fields of any size.
+pub fn icm_equity_sampled(
+    stacks: &[u64],
+    payouts: &[f64],
+    samples: usize,
+    seed: u64,
+) -> Result<Vec<f64>, PokerHandError> {
+    if samples == 0 {
+        return Err(PokerHandError::new("At least one sample is needed"));
+    }
+    let mut rng = SeededRng::new(seed);
+    let live: Vec<usize> = (0..stacks.len()).filter(|&i| stacks[i] > 0).collect();
+    let places = payouts.len().min(live.len());
+    let mut equity = vec![0.0; stacks.len()];
+    let mut times: Vec<(f64, usize)> = Vec::with_capacity(live.len());
+    for _ in 0..samples {
+        times.clear();
+        for &i in &live {
+            let time = -(1.0 - rng.next_f64()).ln() / stacks[i] as f64;
+            times.push((time, i));
+        }
+        if places < times.len() {
+            times.select_nth_unstable_by(places, |a, b| a.0.total_cmp(&b.0));
+        }
+        let paid = &mut times[..places];
+        paid.sort_unstable_by(|a, b| a.0.total_cmp(&b.0));
+        for (&(_, i), prize) in paid.iter().zip(payouts) {
+            equity[i] += prize;
+        }
+    }
+    for value in equity.iter_mut() {
+        *value /= samples as f64;
+    }
+    Ok(equity)
+}
+
+/// A player moving all in, with one opponent left to call or fold.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PushFoldSpot {
+    /// The chips each player has behind, after posting blinds and antes.
+    pub stacks: Vec<u64>,
+    /// The chips each player has already put in the pot this hand.
+    pub posted: Vec<u64>,
+    /// The prize for each place, first place first.
+    pub payouts: Vec<f64>,
+    /// The player moving all in.
+    pub shover: usize,
+    /// The player left to act, who wins the pot if the shover folds.
+    pub caller: usize,
+}
+
+/// The prize equity of the shover's two choices.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PushFoldResult {
+    /// The shover's prize equity after folding.
+    pub fold_equity: f64,
+    /// The shover's expected prize equity after moving all in.
+    pub shove_equity: f64,
+    /// The chance that the caller holds a hand in their calling range.
+    pub call_probability: f64,
+    /// The shover's average all-in equity against the calling range.
+    pub equity_when_called: f64,
+}
+
+impl PushFoldResult {
+    /// Whether moving all in is worth more than folding.
+    pub fn is_profitable(&self) -> bool {
+        self.shove_equity > self.fold_equity
+    }
+
+    /// The prize equity gained by moving all in rather than folding.
+    pub fn shove_gain(&self) -> f64 {
+        self.shove_equity - self.fold_equity
+    }
+}
+
+/// Compare folding with moving all in holding `hole`, when the caller calls with the
+/// holdings in `calling_range` and folds everything else.
+///
+/// Calling holdings which share a card with `hole` are impossible and are left out. The
+/// all-in equity is found for each remaining holding using `enumeration`, so sampling is
+/// much faster for wide ranges. A tied pot is split, with any odd chip to the shover.
+pub fn push_fold(
+    spot: &PushFoldSpot,
+    hole: &[Card],
+    calling_range: &[[Card; 2]],
+    enumeration: Enumeration,
+) -> Result<PushFoldResult, PokerHandError> {
+    check_spot(spot)?;
+    let (shover, caller) = (spot.shover, spot.caller);
+    let pot: u64 = spot.posted.iter().sum();
+    let icm = |stacks: &[u64]| icm_equity(stacks, &spot.payouts).map(|equity| equity[shover]);
+
+    // Folding gives the pot to the caller; a fold to the shove gives it to the shover.
+    let mut stacks = spot.stacks.clone();
+    stacks[caller] += pot;
+    let fold_equity = icm(&stacks)?;
+    let mut stacks = spot.stacks.clone();
+    stacks[shover] += pot;
+    let steal_equity = icm(&stacks)?;
+
+    // When called, each player risks the smaller of the two total stacks.
+    let shover_total = spot.stacks[shover] + spot.posted[shover];
+    let caller_total = spot.stacks[caller] + spot.posted[caller];
+    let risked = shover_total.min(caller_total);
+    let mut called = spot.stacks.clone();
+    called[shover] = shover_total - risked;
+    called[caller] = caller_total - risked;
+    let all_in_pot = pot - spot.posted[shover] - spot.posted[caller] + 2 * risked;
+    let result = |shover_share: u64| {
+        let mut stacks = called.clone();
+        stacks[shover] += shover_share;
+        stacks[caller] += all_in_pot - shover_share;
+        icm(&stacks)
+    };
+    let (win, tie, loss) = (
+        result(all_in_pot)?,
+        result(all_in_pot - all_in_pot / 2)?,
+        result(0)?,
+    );
+
+    let mut combos = 0;
+    let mut called_equity = 0.0;
+    let mut all_in_equity = 0.0;
+    for holding in calling_range {
+        if holding.iter().any(|card| hole.contains(card)) {
+            continue;
+        }
+        let odds = heads_up_odds(hole, holding, &[], enumeration)?;
+        combos += 1;
+        called_equity += odds.win * win + odds.tie * tie + odds.loss * loss;
+        all_in_equity += odds.equity();
+    }
+    // The caller holds one of the 1225 holdings that do not share a card with ours.
+    let call_probability = combos as f64 / 1225.0;
+    let (shove_equity, equity_when_called) = if combos == 0 {
+        (steal_equity, 0.0)
+    } else {
+        (
+            (1.0 - call_probability) * steal_equity
+                + call_probability * called_equity / combos as f64,
+            all_in_equity / combos as f64,
+        )
+    };
+    Ok(PushFoldResult {
+        fold_equity,
+        shove_equity,
+        call_probability,
+        equity_when_called,
+    })
+}
+
+fn check_spot(spot: &PushFoldSpot) -> Result<(), PokerHandError> {
+    let players = spot.stacks.len();
+    if spot.posted.len() != players {
+        return Err(PokerHandError::new(
+            "The stacks and posted chips must be given for every player",
+        ));
+    }
+    if spot.shover >= players || spot.caller >= players || spot.shover == spot.caller {
+        return Err(PokerHandError::new(
+            "The shover and caller must be two different players",
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards::{parse_cards, Ranks};
+
+    fn close(a: f64, b: f64, tolerance: f64) -> bool {
+        (a - b).abs() < tolerance
+    }
+
+    #[test]
+    fn test_icm_equity() {
+        // Winner takes all pays in proportion to the chips.
+        let equity = icm_equity(&[500, 300, 200], &[100.0]).unwrap();
+        assert!(close(equity[0], 50.0, 1e-9) && close(equity[2], 20.0, 1e-9));
+        // The classic three player example: the chip leader's equity is less than their
+        // share of the chips.
+        let equity = icm_equity(&[5000, 3000, 2000], &[50.0, 30.0, 20.0]).unwrap();
+        assert!(close(equity[0], 38.393, 1e-3));
+        assert!(close(equity[1], 32.75, 1e-9));
+        assert!(close(equity[2], 28.857, 1e-3));
+        assert!(close(equity.iter().sum(), 100.0, 1e-9));
+        // Equal stacks have equal equity, and a busted player has none.
+        let equity = icm_equity(&[1000, 1000, 0], &[70.0, 30.0, 10.0]).unwrap();
+        assert_eq!(equity, vec![50.0, 50.0, 0.0]);
+    }
+
+    #[test]
+    fn test_sampled_equity_is_close_to_exact() {
+        let stacks = [4200, 3100, 2500, 1800, 900, 600];
+        let payouts = [40.0, 25.0, 15.0, 10.0];
+        let exact = icm_equity(&stacks, &payouts).unwrap();
+        let sampled = icm_equity_sampled(&stacks, &payouts, 100_000, 11).unwrap();
+        for (exact, sampled) in exact.iter().zip(&sampled) {
+            assert!(close(*exact, *sampled, 0.3));
+        }
+        // A large field, where the exact calculation would be far too slow.
+        let stacks: Vec<u64> = (1..=500).map(|i| 1000 + i * 10).collect();
+        let payouts: Vec<f64> = (0..50).map(|place| 1000.0 / (place + 1) as f64).collect();
+        let equity = icm_equity_sampled(&stacks, &payouts, 200, 3).unwrap();
+        let total: f64 = payouts.iter().sum();
+        assert!(close(equity.iter().sum(), total, 1e-6));
+        assert!(equity[499] > equity[0]);
+    }
+
+    #[test]
+    fn test_push_fold() {
+        // Three handed on the bubble: the short stack in the small blind shoves into the big
+        // blind, who calls with any pair or any ace-king.
+        let spot = PushFoldSpot {
+            stacks: vec![5000, 1000, 3800],
+            posted: vec![0, 100, 200],
+            payouts: vec![60.0, 40.0],
+            shover: 1,
+            caller: 2,
+        };
+        let range: Vec<[Card; 2]> = (0..52)
+            .flat_map(|a| (a + 1..52).map(move |b| (a, b)))
+            .map(|(a, b)| [Card::from_index(a).unwrap(), Card::from_index(b).unwrap()])
+            .filter(|[a, b]| {
+                a.rank == b.rank
+                    || (a.rank.max(b.rank) == Ranks::Ace && a.rank.min(b.rank) == Ranks::King)
+            })
+            .collect();
+        let sampled = Enumeration::Sampled {
+            samples: 500,
+            seed: 1,
+        };
+        let aces = parse_cards("AH AD").unwrap();
+        let result = push_fold(&spot, &aces, &range, sampled).unwrap();
+        assert!(result.is_profitable());
+        // 78 pairs less the 5 pairs of aces using our cards, and the 8 ace-kings without our
+        // aces.
+        assert!(close(
+            result.call_probability,
+            (78.0 - 5.0 + 8.0) / 1225.0,
+            1e-12
+        ));
+        assert!(result.equity_when_called > 0.75);
+        let trash = parse_cards("7C 2D").unwrap();
+        let result = push_fold(&spot, &trash, &range, sampled).unwrap();
+        assert!(result.equity_when_called < 0.4);
+        // The caller wakes up with a hand less than one time in thirteen, so stealing the
+        // blinds outweighs the times the shove is called, even holding trash.
+        assert!(result.is_profitable());
+        // Busting is worth nothing, and doubling up takes 1100 chips from the caller, so the
+        // shove is worth the steal plus at most the called share of doubling up.
+        let fold = icm_equity(&[5000, 1000, 4100], &spot.payouts).unwrap()[1];
+        let steal = icm_equity(&[5000, 1300, 3800], &spot.payouts).unwrap()[1];
+        let double = icm_equity(&[5000, 2200, 2700], &spot.payouts).unwrap()[1];
+        let p = result.call_probability;
+        assert!(close(result.fold_equity, fold, 1e-12));
+        assert!(result.shove_equity >= (1.0 - p) * steal - 1e-12);
+        assert!(result.shove_equity <= (1.0 - p) * steal + p * double + 1e-12);
+        assert!((1.0 - p) * steal > fold);
+
+        let bad = PushFoldSpot {
+            caller: 1,
+            ..spot.clone()
+        };
+        assert!(push_fold(&bad, &aces, &range, sampled).is_err());
+    }
+}
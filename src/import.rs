@@ -0,0 +1,235 @@
+//! Bulk import of hand history files exported by poker sites.
+//!
+//! A PokerStars or GGPoker export holds many hands, one after another. Each hand is read
+//! with `HandHistory::parse_text`, and a hand which cannot be read is reported with its
+//! error rather than stopping the import, so that one unusual hand does not lose the rest
+//! of the file.
+
+use crate::error::PokerHandError;
+use crate::hand_history::HandHistory;
+
+/// The result of importing one hand from a file.
+#[derive(Debug)]
+pub struct ImportedHand {
+    /// The line of the file on which the hand starts, counting from 1.
+    pub line: usize,
+    /// The hand, or why it could not be read. Line numbers in errors refer to the file.
+    pub result: Result<HandHistory, PokerHandError>,
+}
+
+/// A summary of an import.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImportCounts {
+    pub imported: usize,
+    pub failed: usize,
+}
+
+/// Read every hand in an export.
+///
+/// Hands are returned in the order they appear in the file.
+pub fn import_hands(text: &str) -> Vec<ImportedHand> {
+    split_hands(text)
+        .into_iter()
+        .map(|(line, hand)| ImportedHand {
+            line,
+            result: HandHistory::parse_text_from(hand, line),
+        })
+        .collect()
+}
+
+/// The hands which were read successfully.
+pub fn successful(imported: &[ImportedHand]) -> Vec<&HandHistory> {
+    imported
+        .iter()
+        .filter_map(|hand| hand.result.as_ref().ok())
+        .collect()
+}
+
+/// Count the hands which were and were not read.
+pub fn counts(imported: &[ImportedHand]) -> ImportCounts {
+    let imported_count = imported.iter().filter(|hand| hand.result.is_ok()).count();
+    ImportCounts {
+        imported: imported_count,
+        failed: imported.len() - imported_count,
+    }
+}
+
+/// Split an export into the text of each hand, with the line on which each one starts.
+///
+/// A hand starts at each header line, such as "PokerStars Hand #1:" or "Poker Hand #HD1:".
+/// Anything before the first header is ignored.
+pub fn split_hands(text: &str) -> Vec<(usize, &str)> {
+    let mut starts: Vec<(usize, usize)> = Vec::new();
+    let mut offset = 0;
+    for (number, line) in text.split_inclusive('\n').enumerate() {
+        if is_header(line) {
+            starts.push((number + 1, offset));
+        }
+        offset += line.len();
+    }
+    starts
+        .iter()
+        .enumerate()
+        .map(|(i, &(line, start))| {
+            let end = starts.get(i + 1).map_or(text.len(), |&(_, end)| end);
+            (line, &text[start..end])
+        })
+        .collect()
+}
+
+/// Whether the line starts a new hand. Header lines name the hand before any colon, which
+/// tells them apart from lines such as "Seat 1: Hand #2 (10 in chips)".
+fn is_header(line: &str) -> bool {
+    let line = line.trim_start_matches('\u{feff}').trim_start();
+    match line.split_once("Hand #") {
+        Some((prefix, _)) => {
+            !prefix.contains(':')
+                && prefix
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == ' ' || c == '\'')
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards::Card;
+    use crate::table::{Action, Event, Street};
+    use crate::winning_hands;
+
+    const POKERSTARS: &str = "\u{feff}PokerStars Hand #243000000001:  Hold'em No Limit ($0.01/$0.02 USD) - 2023/01/05 9:30:00 ET
+Table 'Aludra III' 6-max Seat #2 is the button
+Seat 1: alice ($2 in chips)
+Seat 2: bob ($2.50 in chips)
+Seat 3: carol ($1.85 in chips)
+Seat 5: dave ($2 in chips) is sitting out
+carol: posts small blind $0.01
+alice: posts big blind $0.02
+*** HOLE CARDS ***
+Dealt to alice [Th 9h]
+bob: raises $0.04 to $0.06
+carol: folds
+alice: calls $0.04
+dave has timed out
+*** FLOP *** [Ts 4d 2c]
+alice: checks
+bob: bets $0.08
+alice: calls $0.08
+*** TURN *** [Ts 4d 2c] [7s]
+alice: checks
+bob: checks
+*** RIVER *** [Ts 4d 2c 7s] [Kd]
+alice: bets $0.20
+bob: calls $0.20
+*** SHOW DOWN ***
+alice: shows [Th 9h] (a pair of Tens)
+bob: shows [Ac Qd] (high card King)
+alice collected $0.66 from pot
+*** SUMMARY ***
+Total pot $0.69 | Rake $0.03
+Board [Ts 4d 2c 7s Kd]
+Seat 1: alice (big blind) showed [Th 9h] and won ($0.66) with a pair of Tens
+
+
+PokerStars Hand #243000000002:  Omaha Pot Limit ($0.01/$0.02 USD) - 2023/01/05 9:31:00 ET
+Table 'Aludra III' 6-max Seat #3 is the button
+Seat 1: alice ($2.64 in chips)
+
+Poker Hand #HD1000: Hold'em No Limit ($0.05/$0.10) - 2023/02/11 21:04:17
+Table 'RushAndCash1' 6-max Seat #1 is the button
+Seat 1: Hero ($10.00 in chips)
+Seat 2: 4f8a2c ($12.35 in chips)
+4f8a2c: posts small blind $0.05
+Hero: posts big blind $0.10
+*** HOLE CARDS ***
+Dealt to Hero [As Ks]
+Dealt to 4f8a2c
+4f8a2c: raises $0.20 to $0.30
+Hero: raises $0.60 to $0.90
+4f8a2c: folds
+Uncalled bet ($0.60) returned to Hero
+Hero collected $0.60 from pot
+*** SUMMARY ***
+Total pot $0.60 | Rake $0
+";
+
+    #[test]
+    fn test_import_mixed_file() {
+        let imported = import_hands(POKERSTARS);
+        assert_eq!(imported.len(), 3);
+        assert_eq!(
+            counts(&imported),
+            ImportCounts {
+                imported: 2,
+                failed: 1
+            }
+        );
+        assert_eq!(imported[1].line, 35);
+        let error = imported[1].result.as_ref().unwrap_err();
+        assert!(error.to_string().contains("Line 35"));
+        assert_eq!(successful(&imported).len(), 2);
+    }
+
+    #[test]
+    fn test_import_cash_hand() {
+        let imported = import_hands(POKERSTARS);
+        let hand = imported[0].result.as_ref().unwrap();
+        assert_eq!(hand.hand_id, "243000000001");
+        assert_eq!(hand.date.as_deref(), Some("2023/01/05 09:30:00"));
+        assert_eq!((hand.small_blind, hand.big_blind), (1, 2));
+        // Dave is sitting out, so only three players were dealt in.
+        assert_eq!(hand.players.len(), 3);
+        assert_eq!(hand.players[1].stack, 250);
+        assert_eq!(hand.button, 1);
+        assert_eq!(hand.rake, 3);
+        assert_eq!(hand.board[0], "10S".parse::<Card>().unwrap());
+        assert_eq!(
+            hand.events[2],
+            Event::Act {
+                street: Street::Preflop,
+                seat: 1,
+                action: Action::Raise(6),
+                amount: 6,
+                all_in: false,
+            }
+        );
+        hand.validate_showdown().unwrap();
+
+        // The shown hands can be compared with the crate's own hand ranking.
+        let shown: Vec<String> = hand
+            .showdown_hands()
+            .unwrap()
+            .iter()
+            .map(|(_, hand)| {
+                let cards: Vec<String> = hand.cards.iter().map(Card::to_string).collect();
+                cards.join(" ")
+            })
+            .collect();
+        let shown: Vec<&str> = shown.iter().map(String::as_str).collect();
+        let winners = winning_hands(&shown).unwrap();
+        assert_eq!(winners, vec![shown[0]]);
+    }
+
+    #[test]
+    fn test_import_hand_without_showdown() {
+        let imported = import_hands(POKERSTARS);
+        let hand = imported[2].result.as_ref().unwrap();
+        assert_eq!(hand.hand_id, "HD1000");
+        assert_eq!(hand.players[1].name, "4f8a2c");
+        assert_eq!(hand.players[1].hole_cards, None);
+        assert_eq!(hand.awards[0].amount, 60);
+        assert!(hand.showdown_hands().unwrap().is_empty());
+        hand.validate_showdown().unwrap();
+    }
+
+    #[test]
+    fn test_split_hands() {
+        assert!(split_hands("no hands here\n").is_empty());
+        let hands = split_hands(POKERSTARS);
+        assert!(hands[0].1.starts_with('\u{feff}'));
+        assert!(hands[2].1.starts_with("Poker Hand #HD1000"));
+        assert!(!is_header("Seat 1: Hand #2 (10 in chips)"));
+    }
+}
@@ -1,47 +1,98 @@
-mod cards;
-mod error;
-mod poker_hand;
+//! Poker hands: parsing, evaluation and comparison, and the games built on them.
+//!
+//! The `std` feature, on by default, enables everything. Without it the crate is
+//! `#![no_std]` and needs no allocator, for firmware on card table hardware: the card
+//! types, the `parse_hand` byte parser, `HandDetail` and the five to seven card
+//! `evaluator` are available, while `PokerHand`, which keeps its cards in a `Vec`, and the
+//! game modules are not. `cargo test --no-default-features` runs the tests of the no_std
+//! core.
 
-// use error::PokerHandError;
-use poker_hand::PokerHand;
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "std")]
+pub mod badugi;
+#[cfg(feature = "std")]
+pub mod batch;
+pub mod cards;
+#[cfg(feature = "std")]
+pub mod caribbean_stud;
+#[cfg(feature = "std")]
+pub mod cfr;
+#[cfg(feature = "std")]
+pub mod combinatorics;
+#[cfg(feature = "std")]
+pub mod draw;
+#[cfg(feature = "std")]
+pub mod equity;
+pub mod error;
+pub mod evaluator;
+#[cfg(feature = "std")]
+pub mod hand_history;
+#[cfg(feature = "std")]
+pub mod icm;
+#[cfg(feature = "std")]
+pub mod import;
+#[cfg(feature = "std")]
+pub mod ofc;
+#[cfg(feature = "std")]
+pub mod outs;
+#[cfg(feature = "std")]
+pub mod pai_gow;
+pub mod poker_hand;
+#[cfg(feature = "std")]
+pub mod pot;
+#[cfg(feature = "std")]
+pub mod preflop;
+#[cfg(feature = "std")]
+pub mod push_fold;
+#[cfg(feature = "std")]
+pub mod rake;
+#[cfg(feature = "std")]
+mod rng;
+#[cfg(feature = "std")]
+pub mod simulator;
+#[cfg(feature = "std")]
+pub mod starting_hands;
+#[cfg(feature = "std")]
+pub mod stats;
+#[cfg(feature = "std")]
+pub mod strength;
+#[cfg(feature = "std")]
+pub mod table;
+#[cfg(feature = "std")]
+pub mod three_card;
+#[cfg(feature = "std")]
+pub mod tournament;
+#[cfg(feature = "std")]
+pub mod video_poker;
+
+pub use cards::{Card, Ranks, Suits};
+#[cfg(feature = "std")]
+pub use error::TableError;
+pub use error::{ParseError, PokerHandError};
+#[cfg(feature = "std")]
+pub use poker_hand::PokerHand;
+pub use poker_hand::{HandDetail, PokerHandRanks};
 
 /// Given a list of poker hands, return a list of those hands which win.
 ///
 /// Note the type signature: this function should return _the same_ reference to
 /// the winning hand(s) as were passed in, not reconstructed strings which happen to be equal.
+#[cfg(feature = "std")]
 pub fn winning_hands<'a>(hands: &[&'a str]) -> Option<Vec<&'a str>> {
-    // Record the hand handles in a mutable vector that will be sorted.
-    let mut hand_handles: Vec<PokerHand> = Vec::with_capacity(hands.len());
-    for hand in hands {
-        let hand_handle = match PokerHand::new(hand) {
-            Ok(hand_handle) => hand_handle,
-            // Ignore invalid hands and process the rest of the list.
-            // TODO:  Only catch error::PokerHandError and propogate other errors.
-            //        I tried to do that but could not get it to work.
-            Err(_) => continue,
-        };
-        hand_handles.push(hand_handle);
-    }
-
-    hand_handles.sort_by(|a, b| a.partial_cmp(b).unwrap());
-    hand_handles.reverse(); // Highest hand first.
+    ranked_hands(hands).into_iter().next()
+}
 
-    match hand_handles.len() {
-        0 => None,
-        1 => Some(vec![hand_handles[0].hand_handle]),
-        _ => {
-            let mut top_hands = vec![hand_handles[0].hand_handle];
-            for i in 1..hand_handles.len() {
-                if hand_handles[i] == hand_handles[0] {
-                    top_hands.push(hand_handles[i].hand_handle);
-                } else {
-                    break;
-                }
-            }
-            // Return an immutable vector of references to the winning hands.
-            // TODO: There must be a way to do this without cloning.
-            let return_val = top_hands.clone();
-            Some(return_val)
-        }
-    }
+/// Given a list of poker hands, return all of them grouped into tiers of equal value.
+///
+/// The first tier holds the winning hand(s), the second tier the runner(s) up, and so on.
+/// This is the ranking needed to award side pots, which may be won by a lower tier.
+/// Invalid hands are ignored and the rest of the list is processed.
+#[cfg(feature = "std")]
+pub fn ranked_hands<'a>(hands: &[&'a str]) -> Vec<Vec<&'a str>> {
+    let hand_handles: Vec<(&'a str, PokerHand)> = hands
+        .iter()
+        .filter_map(|&hand| PokerHand::new(hand).ok().map(|parsed| (hand, parsed)))
+        .collect();
+    poker_hand::rank_tiers(&hand_handles)
 }
@@ -0,0 +1,388 @@
+//! Open-face Chinese poker scoring.
+//!
+//! Each player sets thirteen cards face up in three rows: a three card top row and five
+//! card middle and bottom rows. The rows must not decrease in strength from top to bottom,
+//! or the hand is fouled. Five card rows are ranked as for `PokerHand`, and the top row
+//! only counts high cards, pairs and three of a kind.
+//!
+//! Two players compare row by row, winning a point for each row they win and a bonus for
+//! winning all three, a "scoop". Strong rows also earn royalties, and a player whose top
+//! row is a pair of queens or better qualifies for fantasyland, where the next hand is
+//! dealt all at once.
+
+use crate::cards::{parse_cards, Card, Ranks};
+use crate::error::PokerHandError;
+use crate::poker_hand::{PokerHand, PokerHandRanks};
+use std::cmp::Ordering;
+
+/// A three card top row. Straights and flushes do not count, so the hand rank is high
+/// card, pair or three of a kind.
+#[derive(Debug, Clone)]
+pub struct TopRow {
+    pub hand_rank: PokerHandRanks,
+    /// The cards in scoring order, as for `PokerHand`.
+    pub cards: [Card; 3],
+}
+
+impl TopRow {
+    pub fn from_cards(cards: &[Card]) -> Result<TopRow, PokerHandError> {
+        if cards.len() != 3 {
+            return Err(PokerHandError::new("The top row must have three cards"));
+        }
+        if cards[0] == cards[1] || cards[0] == cards[2] || cards[1] == cards[2] {
+            return Err(PokerHandError::new("Duplicate cards in hand"));
+        }
+        let mut cards = [cards[0], cards[1], cards[2]];
+        cards.sort_by_key(|card| std::cmp::Reverse(card.rank));
+        let hand_rank = if cards[0].rank == cards[2].rank {
+            PokerHandRanks::ThreeOfAKind
+        } else if cards[0].rank == cards[1].rank {
+            PokerHandRanks::Pair
+        } else if cards[1].rank == cards[2].rank {
+            cards.rotate_left(1);
+            PokerHandRanks::Pair
+        } else {
+            PokerHandRanks::HighCard
+        };
+        Ok(TopRow { hand_rank, cards })
+    }
+
+    fn ranks(&self) -> [Ranks; 3] {
+        self.cards.map(|card| card.rank)
+    }
+
+    /// Compare the top row with a five card row. Equal ranks as far as the top row goes
+    /// count as equal, so a top row never beats a middle row of the same value.
+    fn cmp_five(&self, hand: &PokerHand) -> Ordering {
+        self.hand_rank.cmp(&hand.hand_rank).then_with(|| {
+            let ranks: Vec<Ranks> = hand.cards[..3].iter().map(|card| card.rank).collect();
+            self.ranks()[..].cmp(&ranks[..])
+        })
+    }
+}
+
+impl Ord for TopRow {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.hand_rank
+            .cmp(&other.hand_rank)
+            .then_with(|| self.ranks().cmp(&other.ranks()))
+    }
+}
+
+impl PartialEq for TopRow {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for TopRow {}
+
+impl PartialOrd for TopRow {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// A player's completed thirteen card hand.
+#[derive(Debug, Clone)]
+pub struct OfcHand {
+    pub top: TopRow,
+    pub middle: PokerHand<'static>,
+    pub bottom: PokerHand<'static>,
+}
+
+impl OfcHand {
+    /// Parse the three rows, such as "QH QS 4D", "9C 9D 5S 5H 2C" and "AS KS 8S 6S 3S".
+    pub fn new(top: &str, middle: &str, bottom: &str) -> Result<OfcHand, PokerHandError> {
+        let (top, middle, bottom) = (
+            parse_cards(top)?,
+            parse_cards(middle)?,
+            parse_cards(bottom)?,
+        );
+        let all = [&top[..], &middle[..], &bottom[..]].concat();
+        for (i, card) in all.iter().enumerate() {
+            if all[i + 1..].contains(card) {
+                return Err(PokerHandError::new("Duplicate cards in hand"));
+            }
+        }
+        Ok(OfcHand {
+            top: TopRow::from_cards(&top)?,
+            middle: PokerHand::from_cards(&middle)?,
+            bottom: PokerHand::from_cards(&bottom)?,
+        })
+    }
+
+    /// Whether the rows decrease in strength from the bottom up, which fouls the hand.
+    pub fn is_fouled(&self) -> bool {
+        self.top.cmp_five(&self.middle) == Ordering::Greater || self.middle > self.bottom
+    }
+
+    /// The royalties for each row, top, middle and bottom. A fouled hand earns none.
+    pub fn royalties(&self, table: &Royalties) -> [u64; 3] {
+        if self.is_fouled() {
+            return [0; 3];
+        }
+        [
+            table.top(&self.top),
+            Royalties::pay(&table.middle, table.middle_royal_flush, &self.middle),
+            Royalties::pay(&table.bottom, table.bottom_royal_flush, &self.bottom),
+        ]
+    }
+
+    /// Whether the hand qualifies for fantasyland: a pair of queens or better on top,
+    /// without fouling.
+    pub fn qualifies_for_fantasyland(&self) -> bool {
+        let top = &self.top;
+        !self.is_fouled()
+            && (top.hand_rank == PokerHandRanks::ThreeOfAKind
+                || (top.hand_rank == PokerHandRanks::Pair && top.cards[0].rank >= Ranks::Queen))
+    }
+
+    /// The number of cards dealt in progressive fantasyland: 14 for queens on top, 15 for
+    /// kings, 16 for aces and 17 for three of a kind.
+    pub fn fantasyland_cards(&self) -> Option<usize> {
+        if !self.qualifies_for_fantasyland() {
+            return None;
+        }
+        Some(match (self.top.hand_rank, self.top.cards[0].rank) {
+            (PokerHandRanks::ThreeOfAKind, _) => 17,
+            (_, Ranks::Ace) => 16,
+            (_, Ranks::King) => 15,
+            _ => 14,
+        })
+    }
+
+    /// Whether a player already in fantasyland stays there: three of a kind on top, a full
+    /// house or better in the middle, or four of a kind or better on the bottom.
+    pub fn stays_in_fantasyland(&self) -> bool {
+        !self.is_fouled()
+            && (self.top.hand_rank == PokerHandRanks::ThreeOfAKind
+                || self.middle.hand_rank >= PokerHandRanks::FullHouse
+                || self.bottom.hand_rank >= PokerHandRanks::FourOfAKind)
+    }
+}
+
+/// The royalties earned by each row, in points.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Royalties {
+    /// The pays for a pair on top, by the rank of the pair.
+    pub top_pairs: Vec<(Ranks, u64)>,
+    /// The pays for three of a kind on top, by rank.
+    pub top_trips: Vec<(Ranks, u64)>,
+    /// The pays for middle row hand types, other than a royal flush.
+    pub middle: Vec<(PokerHandRanks, u64)>,
+    pub middle_royal_flush: u64,
+    /// The pays for bottom row hand types, other than a royal flush.
+    pub bottom: Vec<(PokerHandRanks, u64)>,
+    pub bottom_royal_flush: u64,
+}
+
+impl Royalties {
+    /// The usual royalties. On top, a pair of sixes pays 1, rising by 1 to 9 for aces, and
+    /// three of a kind pays 10 for deuces up to 22 for aces. The middle pays 2 for three
+    /// of a kind, 4 for a straight, 8 for a flush, 12 for a full house, 20 for four of a
+    /// kind, 30 for a straight flush and 50 for a royal flush. The bottom pays 2 for a
+    /// straight, 4 for a flush, 6 for a full house, 10 for four of a kind, 15 for a
+    /// straight flush and 25 for a royal flush.
+    pub fn standard() -> Royalties {
+        Royalties {
+            top_pairs: Ranks::ALL
+                .iter()
+                .filter(|&&rank| rank >= Ranks::Six)
+                .map(|&rank| (rank, rank as u64 - 5))
+                .collect(),
+            top_trips: Ranks::ALL
+                .iter()
+                .map(|&rank| (rank, rank as u64 + 8))
+                .collect(),
+            middle: vec![
+                (PokerHandRanks::ThreeOfAKind, 2),
+                (PokerHandRanks::Straight, 4),
+                (PokerHandRanks::Flush, 8),
+                (PokerHandRanks::FullHouse, 12),
+                (PokerHandRanks::FourOfAKind, 20),
+                (PokerHandRanks::StraightFlush, 30),
+            ],
+            middle_royal_flush: 50,
+            bottom: vec![
+                (PokerHandRanks::Straight, 2),
+                (PokerHandRanks::Flush, 4),
+                (PokerHandRanks::FullHouse, 6),
+                (PokerHandRanks::FourOfAKind, 10),
+                (PokerHandRanks::StraightFlush, 15),
+            ],
+            bottom_royal_flush: 25,
+        }
+    }
+
+    fn top(&self, row: &TopRow) -> u64 {
+        let pays = match row.hand_rank {
+            PokerHandRanks::Pair => &self.top_pairs,
+            PokerHandRanks::ThreeOfAKind => &self.top_trips,
+            _ => return 0,
+        };
+        pays.iter()
+            .find(|(rank, _)| *rank == row.cards[0].rank)
+            .map_or(0, |(_, pay)| *pay)
+    }
+
+    fn pay(pays: &[(PokerHandRanks, u64)], royal_flush: u64, hand: &PokerHand) -> u64 {
+        if hand.detail().is_royal_flush() {
+            return royal_flush;
+        }
+        pays.iter()
+            .find(|(rank, _)| *rank == hand.hand_rank)
+            .map_or(0, |(_, pay)| *pay)
+    }
+}
+
+/// One player's score against another. Each part is the player's win (positive) or loss
+/// (negative), and the opponent's score is the negation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct OfcScore {
+    /// One point for each row won, less one for each row lost.
+    pub rows: i64,
+    /// The scoop bonus for winning all three rows.
+    pub scoop: i64,
+    /// The player's royalties less the opponent's.
+    pub royalties: i64,
+}
+
+impl OfcScore {
+    pub fn total(&self) -> i64 {
+        self.rows + self.scoop + self.royalties
+    }
+}
+
+/// Score a player's hand against an opponent's, with `scoop_bonus` points for winning
+/// all three rows.
+///
+/// A fouled hand loses every row and is scooped by a hand that is not fouled, and earns
+/// no royalties. Two fouled hands score nothing.
+pub fn score(
+    player: &OfcHand,
+    opponent: &OfcHand,
+    table: &Royalties,
+    scoop_bonus: i64,
+) -> OfcScore {
+    let sign = |ordering: Ordering| match ordering {
+        Ordering::Greater => 1,
+        Ordering::Equal => 0,
+        Ordering::Less => -1,
+    };
+    let royalties = |hand: &OfcHand| hand.royalties(table).iter().sum::<u64>() as i64;
+    let royalties = royalties(player) - royalties(opponent);
+    let results = match (player.is_fouled(), opponent.is_fouled()) {
+        (true, true) => return OfcScore::default(),
+        (true, false) => [-1; 3],
+        (false, true) => [1; 3],
+        (false, false) => [
+            sign(player.top.cmp(&opponent.top)),
+            sign(player.middle.partial_cmp(&opponent.middle).unwrap()),
+            sign(player.bottom.partial_cmp(&opponent.bottom).unwrap()),
+        ],
+    };
+    let rows: i64 = results.iter().sum();
+    let scoop = match rows {
+        3 => scoop_bonus,
+        -3 => -scoop_bonus,
+        _ => 0,
+    };
+    OfcScore {
+        rows,
+        scoop,
+        royalties,
+    }
+}
+
+/// Each player's total score against all of the others at the table.
+pub fn score_table(hands: &[OfcHand], table: &Royalties, scoop_bonus: i64) -> Vec<i64> {
+    (0..hands.len())
+        .map(|i| {
+            (0..hands.len())
+                .filter(|&j| j != i)
+                .map(|j| score(&hands[i], &hands[j], table, scoop_bonus).total())
+                .sum()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hand(top: &str, middle: &str, bottom: &str) -> OfcHand {
+        OfcHand::new(top, middle, bottom).unwrap()
+    }
+
+    #[test]
+    fn test_top_row() {
+        let top = |cards: &str| TopRow::from_cards(&parse_cards(cards).unwrap()).unwrap();
+        assert_eq!(top("4D QH QS").hand_rank, PokerHandRanks::Pair);
+        assert_eq!(top("4D QH QS").cards[2].rank, Ranks::Four);
+        // Straights and flushes do not count on top.
+        assert_eq!(top("QH KH AH").hand_rank, PokerHandRanks::HighCard);
+        assert!(top("2C 2D 3H") > top("AH KD QS"));
+        assert!(TopRow::from_cards(&parse_cards("2C 2D").unwrap()).is_err());
+        assert!(OfcHand::new("QH QS 4D", "QH 9D 5S 5H 2C", "AS KS 8S 6S 3S").is_err());
+    }
+
+    #[test]
+    fn test_fouling() {
+        assert!(!hand("QH QS 4D", "9C 9D 5S 5H 2C", "AS KS 8S 6S 3S").is_fouled());
+        // The middle row beats the bottom.
+        assert!(hand("QH QS 4D", "AS KS 8S 6S 3S", "9C 9D 5S 5H 2C").is_fouled());
+        // The top pair beats the middle pair.
+        assert!(hand("KH KS 4D", "QC QD 8D 5H 2C", "AC AD 7S 6S 3S").is_fouled());
+        // Equal pairs with equal kickers as far as the top row goes are not fouled.
+        assert!(!hand("KH KS 4D", "KC KD 4S 3H 2C", "AC AD 7S 6S 3S").is_fouled());
+        assert!(hand("KH KS 5D", "KC KD 4S 3H 2C", "AC AD 7S 6S 3S").is_fouled());
+    }
+
+    #[test]
+    fn test_royalties() {
+        let table = Royalties::standard();
+        let royal = hand("6H 6S 4D", "9C 9D 9S 5H 2C", "AS KS QS JS 10S");
+        assert_eq!(royal.royalties(&table), [1, 2, 25]);
+        let trips = hand("AH AS AD", "KC KD KS 5H 5C", "QS QH QD QC 2S");
+        assert_eq!(trips.royalties(&table), [22, 12, 10]);
+        let fouled = hand("AH AS AD", "QS QH QD QC 2S", "KC KD KS 5H 5C");
+        assert_eq!(fouled.royalties(&table), [0, 0, 0]);
+    }
+
+    #[test]
+    fn test_scoring() {
+        let table = Royalties::standard();
+        let strong = hand("QH QS 4D", "9C 9D 5S 5H 2C", "AS KS 8S 6S 3S");
+        let weak = hand("JH 10S 4C", "8C 8D 6S 5C 3C", "AD AH 7D 6D 2D");
+        let result = score(&strong, &weak, &table, 3);
+        assert_eq!(
+            result,
+            OfcScore {
+                rows: 3,
+                scoop: 3,
+                royalties: 7 + 4,
+            }
+        );
+        assert_eq!(score(&weak, &strong, &table, 3).total(), -result.total());
+        // A fouled hand is scooped.
+        let fouled = hand("KH KD 2S", "QC QD 8H 5H 2C", "AC JD 7H 6H 3H");
+        assert_eq!(score(&weak, &fouled, &table, 3).total(), 6);
+        assert_eq!(score(&fouled, &fouled.clone(), &table, 3).total(), 0);
+        let scores = score_table(&[strong, weak, fouled], &table, 3);
+        assert_eq!(scores.iter().sum::<i64>(), 0);
+    }
+
+    #[test]
+    fn test_fantasyland() {
+        let queens = hand("QH QS 4D", "9C 9D 5S 5H 2C", "AS KS 8S 6S 3S");
+        assert!(queens.qualifies_for_fantasyland());
+        assert_eq!(queens.fantasyland_cards(), Some(14));
+        assert!(!queens.stays_in_fantasyland());
+        let jacks = hand("JH JS 4D", "9C 9D 5S 5H 2C", "AS KS 8S 6S 3S");
+        assert!(!jacks.qualifies_for_fantasyland());
+        let trips = hand("2H 2S 2D", "9C 9D 9H 5H 3C", "AS KS 8S 6S 3S");
+        assert_eq!(trips.fantasyland_cards(), Some(17));
+        assert!(trips.stays_in_fantasyland());
+    }
+}
@@ -0,0 +1,215 @@
+//! Outs and draw analysis for a hand with community cards still to come.
+//!
+//! An "out" is an unseen card which, if dealt next, improves the player's best hand to a
+//! better hand type, or (when an opponent's hole cards are known) makes it beat the
+//! opponent's hand.
+
+use crate::cards::Card;
+use crate::error::PokerHandError;
+use crate::poker_hand::{PokerHand, PokerHandRanks};
+
+/// The number of community cards on a complete board.
+const FULL_BOARD: usize = 5;
+
+/// The result of a draw analysis.
+#[derive(Debug)]
+pub struct OutsReport {
+    /// The hand type the player holds now.
+    pub current: PokerHandRanks,
+    /// The number of cards the player has not seen.
+    pub unseen: usize,
+    /// The outs, grouped by the hand type they make, from lowest to highest.
+    /// Only hand types with at least one out are listed.
+    pub outs: Vec<(PokerHandRanks, Vec<Card>)>,
+    /// The probability of hitting an out with the next card.
+    pub next_card_probability: f64,
+    /// The probability of having improved (or being ahead) once the river is dealt.
+    pub by_river_probability: f64,
+}
+
+impl OutsReport {
+    /// The total number of outs across all hand types.
+    pub fn total_outs(&self) -> usize {
+        self.outs.iter().map(|(_, cards)| cards.len()).sum()
+    }
+
+    /// The number of outs which make the given hand type.
+    pub fn outs_to(&self, hand_rank: PokerHandRanks) -> usize {
+        self.outs
+            .iter()
+            .find(|(rank, _)| *rank == hand_rank)
+            .map_or(0, |(_, cards)| cards.len())
+    }
+}
+
+/// Find the cards which improve the player's hand to a better hand type.
+///
+/// The board must be a flop (three cards) or a turn (four cards).
+pub fn analyze_draws(hole: &[Card], board: &[Card]) -> Result<OutsReport, PokerHandError> {
+    check_cards(hole, board, None)?;
+    let current = best_hand(hole, board)?.hand_rank;
+    analyze(hole, board, &[], |hand, _| Ok(hand.hand_rank > current))
+}
+
+/// Find the cards which give the player a hand that beats the opponent's hand.
+///
+/// The opponent's hole cards are treated as seen, so they are never counted as outs.
+pub fn analyze_draws_against(
+    hole: &[Card],
+    board: &[Card],
+    opponent: &[Card],
+) -> Result<OutsReport, PokerHandError> {
+    check_cards(hole, board, Some(opponent))?;
+    analyze(hole, board, opponent, |hand, board| {
+        Ok(*hand > best_hand(opponent, board)?)
+    })
+}
+
+/// Shared analysis, where `wins` decides whether the player's hand on a board is a success.
+fn analyze<F>(
+    hole: &[Card],
+    board: &[Card],
+    seen: &[Card],
+    wins: F,
+) -> Result<OutsReport, PokerHandError>
+where
+    F: Fn(&PokerHand, &[Card]) -> Result<bool, PokerHandError>,
+{
+    let current = best_hand(hole, board)?.hand_rank;
+    let unseen: Vec<Card> = (0..52)
+        .filter_map(Card::from_index)
+        .filter(|card| !hole.contains(card) && !board.contains(card) && !seen.contains(card))
+        .collect();
+
+    // Evaluate every possible next card.
+    let mut outs: Vec<(PokerHandRanks, Vec<Card>)> = Vec::new();
+    for &card in &unseen {
+        let next_board = [board, &[card]].concat();
+        let hand = best_hand(hole, &next_board)?;
+        if wins(&hand, &next_board)? {
+            match outs.iter_mut().find(|(rank, _)| *rank == hand.hand_rank) {
+                Some((_, cards)) => cards.push(card),
+                None => outs.push((hand.hand_rank, vec![card])),
+            }
+        }
+    }
+    outs.sort_by_key(|(rank, _)| *rank);
+    let total_outs: usize = outs.iter().map(|(_, cards)| cards.len()).sum();
+    let next_card_probability = total_outs as f64 / unseen.len() as f64;
+
+    // From the flop, every turn and river pair has to be run out, since two cards can
+    // combine to improve the hand without either being an out on its own.
+    let by_river_probability = if board.len() + 1 == FULL_BOARD {
+        next_card_probability
+    } else {
+        let mut hits = 0;
+        let mut runouts = 0;
+        for (i, &turn) in unseen.iter().enumerate() {
+            for &river in &unseen[i + 1..] {
+                let final_board = [board, &[turn, river]].concat();
+                let hand = best_hand(hole, &final_board)?;
+                if wins(&hand, &final_board)? {
+                    hits += 1;
+                }
+                runouts += 1;
+            }
+        }
+        hits as f64 / runouts as f64
+    };
+
+    Ok(OutsReport {
+        current,
+        unseen: unseen.len(),
+        outs,
+        next_card_probability,
+        by_river_probability,
+    })
+}
+
+/// The player's best five card hand from their hole cards and the board.
+fn best_hand(hole: &[Card], board: &[Card]) -> Result<PokerHand<'static>, PokerHandError> {
+    PokerHand::best_of(&[hole, board].concat())
+}
+
+/// Validate the number of cards and that no card appears twice.
+fn check_cards(
+    hole: &[Card],
+    board: &[Card],
+    opponent: Option<&[Card]>,
+) -> Result<(), PokerHandError> {
+    if hole.len() != 2 {
+        return Err(PokerHandError::new("A player must have two hole cards"));
+    }
+    if board.len() != 3 && board.len() != 4 {
+        return Err(PokerHandError::new(
+            "Draws can only be analyzed on the flop or the turn",
+        ));
+    }
+    if opponent.is_some_and(|cards| cards.len() != 2) {
+        return Err(PokerHandError::new("An opponent must have two hole cards"));
+    }
+    let all = [hole, board, opponent.unwrap_or(&[])].concat();
+    for (i, card) in all.iter().enumerate() {
+        if all[i + 1..].contains(card) {
+            return Err(PokerHandError::new("Duplicate cards in hand"));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards::parse_cards;
+
+    #[test]
+    fn test_flush_and_straight_draw() {
+        // Nine hearts make a flush, and the six other eights and kings make a straight.
+        let hole = parse_cards("9H 10H").unwrap();
+        let board = parse_cards("JH QC 2H").unwrap();
+        let report = analyze_draws(&hole, &board).unwrap();
+        assert!(report.current == PokerHandRanks::HighCard);
+        assert_eq!(report.unseen, 47);
+        assert_eq!(report.outs_to(PokerHandRanks::Flush), 9);
+        assert_eq!(report.outs_to(PokerHandRanks::Straight), 6);
+        // Pairing any card, including the deuce on the board, improves high card to a pair.
+        assert_eq!(report.outs_to(PokerHandRanks::Pair), 14);
+        assert_eq!(report.total_outs(), 29);
+        assert!((report.next_card_probability - 29.0 / 47.0).abs() < 1e-9);
+        assert!(report.by_river_probability > report.next_card_probability);
+    }
+
+    #[test]
+    fn test_turn_outs() {
+        // Four to a flush on the turn: nine outs with one card to come.
+        let hole = parse_cards("AS 3S").unwrap();
+        let board = parse_cards("KS 8S 2D 7C").unwrap();
+        let report = analyze_draws(&hole, &board).unwrap();
+        assert_eq!(report.outs_to(PokerHandRanks::Flush), 9);
+        assert_eq!(report.unseen, 46);
+        assert!((report.by_river_probability - report.next_card_probability).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_outs_against_opponent() {
+        // Ace high with a flush draw against a pair of kings.
+        let hole = parse_cards("AS 3S").unwrap();
+        let opponent = parse_cards("KH KD").unwrap();
+        let board = parse_cards("QS 8S 2D 7C").unwrap();
+        let report = analyze_draws_against(&hole, &board, &opponent).unwrap();
+        assert_eq!(report.outs_to(PokerHandRanks::Flush), 9);
+        // The three remaining aces make a pair of aces, but pairing the three is not enough.
+        assert_eq!(report.outs_to(PokerHandRanks::Pair), 3);
+        assert_eq!(report.unseen, 44);
+        assert_eq!(report.total_outs(), 12);
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        let hole = parse_cards("AS 3S").unwrap();
+        assert!(analyze_draws(&hole, &parse_cards("KS 8S").unwrap()).is_err());
+        assert!(analyze_draws(&hole, &parse_cards("KS 8S 2D 7C 9C").unwrap()).is_err());
+        assert!(analyze_draws(&hole, &parse_cards("AS 8S 2D").unwrap()).is_err());
+        assert!(analyze_draws(&hole[..1], &parse_cards("KS 8S 2D").unwrap()).is_err());
+    }
+}
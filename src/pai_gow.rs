@@ -0,0 +1,560 @@
+//! Pai Gow Poker: setting seven cards into a high and a low hand, and settling against
+//! the banker.
+//!
+//! The game uses a 53 card deck, the extra card being a joker which plays as a "bug": it
+//! can be used as an ace, or to complete a straight, a flush or a straight flush. Five
+//! aces, made with the joker, is the highest hand.
+//!
+//! Each player sets their seven cards into a five card high hand and a two card low hand,
+//! and the high hand must outrank the low hand. Two card hands are either a pair or two
+//! high cards. Both of a player's hands are compared with the banker's: the player wins
+//! the bet if both hands are higher, loses it if both are lower, and otherwise pushes.
+//! When two hands are of equal value, a "copy", the banker wins that hand. A commission is
+//! taken from winning bets.
+
+use crate::cards::{Card, Ranks};
+use crate::error::PokerHandError;
+use crate::poker_hand::{PokerHand, PokerHandRanks};
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+
+/// A card from the 53 card Pai Gow deck.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaiGowCard {
+    Card(Card),
+    Joker,
+}
+
+impl PaiGowCard {
+    /// The rank the card counts as when forming pairs, where the joker is an ace.
+    fn rank(&self) -> Ranks {
+        match self {
+            PaiGowCard::Card(card) => card.rank,
+            PaiGowCard::Joker => Ranks::Ace,
+        }
+    }
+}
+
+/// The joker is written "JK". Other cards use the crate's notation, e.g. "10H".
+impl FromStr for PaiGowCard {
+    type Err = PokerHandError;
+
+    fn from_str(card: &str) -> Result<Self, Self::Err> {
+        match card.to_ascii_uppercase().as_str() {
+            "JK" | "JOKER" => Ok(PaiGowCard::Joker),
+            _ => card.parse().map(PaiGowCard::Card),
+        }
+    }
+}
+
+impl fmt::Display for PaiGowCard {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PaiGowCard::Card(card) => write!(f, "{}", card),
+            PaiGowCard::Joker => write!(f, "JK"),
+        }
+    }
+}
+
+/// The 53 card deck: the 52 cards in index order, then the joker.
+pub fn deck() -> Vec<PaiGowCard> {
+    (0..52)
+        .filter_map(Card::from_index)
+        .map(PaiGowCard::Card)
+        .chain(std::iter::once(PaiGowCard::Joker))
+        .collect()
+}
+
+/// Parse whitespace separated cards, such as "AS JK 10H".
+pub fn parse_pai_gow_cards(cards: &str) -> Result<Vec<PaiGowCard>, PokerHandError> {
+    let parsed = cards
+        .split_whitespace()
+        .map(PaiGowCard::from_str)
+        .collect::<Result<Vec<PaiGowCard>, PokerHandError>>()?;
+    check_duplicates(&parsed)?;
+    Ok(parsed)
+}
+
+fn check_duplicates(cards: &[PaiGowCard]) -> Result<(), PokerHandError> {
+    for (i, card) in cards.iter().enumerate() {
+        if cards[i + 1..].contains(card) {
+            return Err(PokerHandError::new("Duplicate cards in hand"));
+        }
+    }
+    Ok(())
+}
+
+/// The value of a five card high hand.
+#[derive(Debug, Clone)]
+pub enum HighHand {
+    Hand(PokerHand<'static>),
+    /// Four aces and the joker, which beats every other hand.
+    FiveAces,
+}
+
+impl HighHand {
+    /// The best value of five cards, using the joker as an ace or to complete a straight,
+    /// flush or straight flush.
+    pub fn new(cards: &[PaiGowCard]) -> Result<HighHand, PokerHandError> {
+        if cards.len() != 5 {
+            return Err(PokerHandError::new("A high hand must have five cards"));
+        }
+        check_duplicates(cards)?;
+        let natural: Vec<Card> = cards
+            .iter()
+            .filter_map(|card| match card {
+                PaiGowCard::Card(card) => Some(*card),
+                PaiGowCard::Joker => None,
+            })
+            .collect();
+        if natural.len() == 5 {
+            return PokerHand::from_cards(&natural).map(HighHand::Hand);
+        }
+        if natural.iter().all(|card| card.rank == Ranks::Ace) {
+            return Ok(HighHand::FiveAces);
+        }
+        let mut best: Option<PokerHand<'static>> = None;
+        for substitute in (0..52).filter_map(Card::from_index) {
+            if natural.contains(&substitute) {
+                continue;
+            }
+            let hand = PokerHand::from_cards(&[&natural[..], &[substitute]].concat())?;
+            let allowed = substitute.rank == Ranks::Ace
+                || matches!(
+                    hand.hand_rank,
+                    PokerHandRanks::Straight
+                        | PokerHandRanks::Flush
+                        | PokerHandRanks::StraightFlush
+                );
+            if allowed && best.as_ref().map_or(true, |best| hand > *best) {
+                best = Some(hand);
+            }
+        }
+        Ok(HighHand::Hand(best.unwrap()))
+    }
+}
+
+impl PartialEq for HighHand {
+    fn eq(&self, other: &Self) -> bool {
+        self.partial_cmp(other) == Some(Ordering::Equal)
+    }
+}
+
+impl PartialOrd for HighHand {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match (self, other) {
+            (HighHand::FiveAces, HighHand::FiveAces) => Some(Ordering::Equal),
+            (HighHand::FiveAces, _) => Some(Ordering::Greater),
+            (_, HighHand::FiveAces) => Some(Ordering::Less),
+            (HighHand::Hand(a), HighHand::Hand(b)) => a.partial_cmp(b),
+        }
+    }
+}
+
+/// The value of a two card low hand: a pair beats any two high cards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct LowHand {
+    pub pair: bool,
+    /// The ranks, highest first. The joker plays as an ace.
+    pub ranks: [Ranks; 2],
+}
+
+impl LowHand {
+    pub fn new(cards: &[PaiGowCard]) -> Result<LowHand, PokerHandError> {
+        if cards.len() != 2 {
+            return Err(PokerHandError::new("A low hand must have two cards"));
+        }
+        check_duplicates(cards)?;
+        let (a, b) = (cards[0].rank(), cards[1].rank());
+        Ok(LowHand {
+            pair: a == b,
+            ranks: [a.max(b), a.min(b)],
+        })
+    }
+}
+
+/// Seven cards set into a high and a low hand.
+#[derive(Debug, Clone)]
+pub struct Split {
+    pub high: [PaiGowCard; 5],
+    pub low: [PaiGowCard; 2],
+    high_hand: HighHand,
+    low_hand: LowHand,
+}
+
+impl Split {
+    /// Set the cards, checking that the high hand outranks the low hand.
+    pub fn new(high: [PaiGowCard; 5], low: [PaiGowCard; 2]) -> Result<Split, PokerHandError> {
+        check_duplicates(&[&high[..], &low[..]].concat())?;
+        let split = Split {
+            high_hand: HighHand::new(&high)?,
+            low_hand: LowHand::new(&low)?,
+            high,
+            low,
+        };
+        if !split.high_outranks_low() {
+            return Err(PokerHandError::new(
+                "The high hand must outrank the low hand",
+            ));
+        }
+        Ok(split)
+    }
+
+    /// Parse a split written as the five high cards, then the two low cards.
+    pub fn parse(high: &str, low: &str) -> Result<Split, PokerHandError> {
+        let high = parse_pai_gow_cards(high)?;
+        let low = parse_pai_gow_cards(low)?;
+        match (
+            <[PaiGowCard; 5]>::try_from(high),
+            <[PaiGowCard; 2]>::try_from(low),
+        ) {
+            (Ok(high), Ok(low)) => Split::new(high, low),
+            _ => Err(PokerHandError::new(
+                "A split has five high and two low cards",
+            )),
+        }
+    }
+
+    pub fn high_hand(&self) -> &HighHand {
+        &self.high_hand
+    }
+
+    pub fn low_hand(&self) -> LowHand {
+        self.low_hand
+    }
+
+    /// Whether the high hand is better than the low hand, comparing the two card hand with
+    /// the first two cards of the high hand in scoring order.
+    fn high_outranks_low(&self) -> bool {
+        let hand = match &self.high_hand {
+            HighHand::FiveAces => return true,
+            HighHand::Hand(hand) => hand,
+        };
+        let top = [hand.cards[0].rank, hand.cards[1].rank];
+        match hand.hand_rank {
+            PokerHandRanks::HighCard => !self.low_hand.pair && top >= self.low_hand.ranks,
+            PokerHandRanks::Pair => !self.low_hand.pair || top[0] >= self.low_hand.ranks[0],
+            _ => true,
+        }
+    }
+}
+
+/// Every valid way to set seven cards.
+pub fn valid_splits(cards: &[PaiGowCard]) -> Result<Vec<Split>, PokerHandError> {
+    if cards.len() != 7 {
+        return Err(PokerHandError::new("Pai Gow hands have seven cards"));
+    }
+    check_duplicates(cards)?;
+    let mut splits = Vec::new();
+    for i in 0..7 {
+        for j in i + 1..7 {
+            let low = [cards[i], cards[j]];
+            let mut high = [cards[0]; 5];
+            let rest = (0..7).filter(|&k| k != i && k != j).map(|k| cards[k]);
+            for (slot, card) in high.iter_mut().zip(rest) {
+                *slot = card;
+            }
+            if let Ok(split) = Split::new(high, low) {
+                splits.push(split);
+            }
+        }
+    }
+    Ok(splits)
+}
+
+/// A way of setting hands, as the banker must.
+pub trait HouseWay {
+    fn set(&self, cards: &[PaiGowCard]) -> Result<Split, PokerHandError>;
+}
+
+/// A typical casino house way, with the choices that vary between casinos as options.
+///
+/// Pairs, three of a kind and four of a kind are counted with the joker as an ace.
+///
+/// * No pair: the highest card stays in the high hand, and the next two go low.
+/// * One pair: the pair goes high, and the two highest other cards go low.
+/// * Two pair: split, with the lower pair low, unless the higher pair is no better than
+///   `keep_two_pair_up_to` and there is an ace to play in the low hand.
+/// * Three pair: the highest pair goes low.
+/// * Three of a kind: kept high, except three aces, which are split if `split_three_aces`.
+/// * Full house: split, with the highest possible pair low.
+/// * Two sets of three of a kind: the higher set is split to play a pair low.
+/// * Four of a kind: split into two pairs from `split_quads_from` up, otherwise kept high.
+/// * A straight or flush is played high, instead of no pair or one pair, when
+///   `play_straights_and_flushes` is set, with the best low hand that keeps it.
+///
+/// When more than one setting follows the rule, the one with the best low hand is used.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StandardHouseWay {
+    pub keep_two_pair_up_to: Option<Ranks>,
+    pub split_three_aces: bool,
+    pub split_quads_from: Ranks,
+    pub play_straights_and_flushes: bool,
+}
+
+impl Default for StandardHouseWay {
+    fn default() -> Self {
+        StandardHouseWay {
+            keep_two_pair_up_to: Some(Ranks::Six),
+            split_three_aces: true,
+            split_quads_from: Ranks::Jack,
+            play_straights_and_flushes: true,
+        }
+    }
+}
+
+impl HouseWay for StandardHouseWay {
+    fn set(&self, cards: &[PaiGowCard]) -> Result<Split, PokerHandError> {
+        let splits = valid_splits(cards)?;
+        let mut counts = [0usize; 15];
+        for card in cards {
+            counts[card.rank() as usize] += 1;
+        }
+        // The ranks with each count, highest first.
+        let with_count = |wanted: usize| -> Vec<Ranks> {
+            Ranks::ALL
+                .iter()
+                .rev()
+                .copied()
+                .filter(|&rank| counts[rank as usize] == wanted)
+                .collect()
+        };
+        let (quads, trips, pairs) = (with_count(4), with_count(3), with_count(2));
+        let high_has = |split: &Split, rank: Ranks, count: usize| {
+            split.high.iter().filter(|card| card.rank() == rank).count() >= count
+        };
+        let low_pair =
+            |split: &Split, rank: Ranks| split.low_hand.pair && split.low_hand.ranks[0] == rank;
+
+        let choice = if counts[Ranks::Ace as usize] == 5 {
+            // Five aces are kept together.
+            best(&splits, |split| {
+                matches!(split.high_hand, HighHand::FiveAces)
+            })
+        } else if let Some(&rank) = quads.first() {
+            if rank >= self.split_quads_from {
+                best(&splits, |split| low_pair(split, rank))
+            } else {
+                best(&splits, |split| high_has(split, rank, 4))
+            }
+        } else if let Some(&rank) = trips.first() {
+            if trips.len() > 1 {
+                // The higher set is split to play a pair low.
+                best(&splits, |split| {
+                    low_pair(split, trips[0]) && high_has(split, trips[1], 3)
+                })
+            } else if !pairs.is_empty() {
+                best(&splits, |split| {
+                    high_has(split, rank, 3) && split.low_hand.pair
+                })
+            } else if rank == Ranks::Ace && self.split_three_aces {
+                best(&splits, |split| {
+                    high_has(split, rank, 2) && split.low_hand.ranks[0] == Ranks::Ace
+                })
+            } else {
+                self.straight_or(&splits, |split| high_has(split, rank, 3))
+            }
+        } else if pairs.len() >= 3 {
+            best(&splits, |split| low_pair(split, pairs[0]))
+        } else if pairs.len() == 2 {
+            let has_ace = counts[Ranks::Ace as usize] == 1;
+            let keep = self
+                .keep_two_pair_up_to
+                .is_some_and(|limit| pairs[0] <= limit && has_ace);
+            if keep {
+                best(&splits, |split| {
+                    high_has(split, pairs[0], 2)
+                        && high_has(split, pairs[1], 2)
+                        && split.low_hand.ranks[0] == Ranks::Ace
+                })
+            } else {
+                best(&splits, |split| {
+                    high_has(split, pairs[0], 2) && low_pair(split, pairs[1])
+                })
+            }
+        } else if let Some(&rank) = pairs.first() {
+            self.straight_or(&splits, |split| high_has(split, rank, 2))
+        } else {
+            self.straight_or(&splits, |_| true)
+        };
+        // Every rule can be followed, but fall back on the best low hand regardless.
+        choice
+            .or_else(|| best(&splits, |_| true))
+            .cloned()
+            .ok_or_else(|| PokerHandError::new("The cards cannot be set"))
+    }
+}
+
+impl StandardHouseWay {
+    /// Play a straight or better high if allowed and possible, or else follow the rule.
+    fn straight_or<'s, F>(&self, splits: &'s [Split], rule: F) -> Option<&'s Split>
+    where
+        F: Fn(&Split) -> bool,
+    {
+        let straight = |split: &Split| match &split.high_hand {
+            HighHand::Hand(hand) => hand.hand_rank >= PokerHandRanks::Straight,
+            HighHand::FiveAces => true,
+        };
+        if self.play_straights_and_flushes {
+            if let Some(split) = best(splits, straight) {
+                return Some(split);
+            }
+        }
+        best(splits, rule)
+    }
+}
+
+/// The split with the best low hand, and then the best high hand, among those matching.
+fn best<F>(splits: &[Split], matches: F) -> Option<&Split>
+where
+    F: Fn(&Split) -> bool,
+{
+    splits.iter().filter(|split| matches(split)).max_by(|a, b| {
+        a.low_hand.cmp(&b.low_hand).then_with(|| {
+            a.high_hand
+                .partial_cmp(&b.high_hand)
+                .unwrap_or(Ordering::Equal)
+        })
+    })
+}
+
+/// The result of a player's hand against the banker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Win,
+    Push,
+    Loss,
+}
+
+/// A settled bet. `net` is the player's win after commission, or loss.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Settlement {
+    pub outcome: Outcome,
+    pub commission: u64,
+    pub net: i64,
+}
+
+/// Settle a player's bet against the banker, taking `commission_percent` of a win.
+///
+/// The commission is rounded down to whole chips.
+pub fn settle(player: &Split, banker: &Split, bet: u64, commission_percent: u64) -> Settlement {
+    // The banker wins copies, so the player must be strictly higher.
+    let high = player.high_hand.partial_cmp(&banker.high_hand) == Some(Ordering::Greater);
+    let low = player.low_hand > banker.low_hand;
+    match (high, low) {
+        (true, true) => {
+            let commission = bet * commission_percent / 100;
+            Settlement {
+                outcome: Outcome::Win,
+                commission,
+                net: (bet - commission) as i64,
+            }
+        }
+        (false, false) => Settlement {
+            outcome: Outcome::Loss,
+            commission: 0,
+            net: -(bet as i64),
+        },
+        _ => Settlement {
+            outcome: Outcome::Push,
+            commission: 0,
+            net: 0,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cards(cards: &str) -> Vec<PaiGowCard> {
+        parse_pai_gow_cards(cards).unwrap()
+    }
+
+    fn house_way(hand: &str) -> (String, String) {
+        let split = StandardHouseWay::default().set(&cards(hand)).unwrap();
+        let mut low: Vec<String> = split.low.iter().map(PaiGowCard::to_string).collect();
+        low.sort();
+        let mut high: Vec<String> = split.high.iter().map(PaiGowCard::to_string).collect();
+        high.sort();
+        (high.join(" "), low.join(" "))
+    }
+
+    #[test]
+    fn test_deck_and_parsing() {
+        assert_eq!(deck().len(), 53);
+        assert_eq!(cards("JK AS")[0], PaiGowCard::Joker);
+        assert!(parse_pai_gow_cards("JK JK").is_err());
+    }
+
+    #[test]
+    fn test_joker_as_bug() {
+        let high = |hand: &str| HighHand::new(&cards(hand)).unwrap();
+        // The joker completes a straight flush.
+        match high("JK 9H 8H 7H 6H") {
+            HighHand::Hand(hand) => {
+                assert_eq!(hand.hand_rank, PokerHandRanks::StraightFlush);
+                assert_eq!(hand.cards[0].rank, Ranks::Ten);
+            }
+            HighHand::FiveAces => panic!(),
+        }
+        // Otherwise it is an ace, so it pairs an ace but not a king.
+        assert!(high("JK AS 9D 7C 2H") == high("AH AS 9D 7C 2H"));
+        assert!(high("JK KS 9D 7C 2H") == high("AH KS 9D 7C 2H"));
+        assert!(high("JK AS AD AC AH") > high("10S JS QS KS AS"));
+        assert!(LowHand::new(&cards("JK AD")).unwrap().pair);
+    }
+
+    #[test]
+    fn test_split_validation() {
+        assert!(Split::parse("KS QD 9C 7H 2S", "AH 3D").is_err());
+        assert!(Split::parse("AS QD 9C 7H 2S", "KH 3D").is_ok());
+        assert!(Split::parse("AS QD 9C 7H 2S", "3H 3D").is_err());
+        assert!(Split::parse("4S 4D 9C 7H 2S", "3H 3C").is_ok());
+        assert!(Split::parse("AS QD 9C 7H", "3H 3D 2C").is_err());
+    }
+
+    #[test]
+    fn test_house_way() {
+        // No pair: the second and third highest cards go low.
+        assert_eq!(house_way("AS KD 9C 7H 5S 3D 2C").1, "9C KD");
+        // One pair stays high.
+        assert_eq!(house_way("8S 8D AC 9H 5S 3D 2C").1, "9H AC");
+        // Two pair are split, unless both are small and there is an ace.
+        assert_eq!(house_way("KS KD 8C 8H 5S 3D 2C").1, "8C 8H");
+        assert_eq!(house_way("6S 6D 3C 3H AS 9D 2C").1, "9D AS");
+        // Three pair: the highest pair goes low.
+        assert_eq!(house_way("KS KD 8C 8H 5S 5D 2C").1, "KD KS");
+        // Three aces are split.
+        assert_eq!(house_way("AS AD AC 9H 5S 3D 2C").1, "9H AC");
+        // A full house is split.
+        assert_eq!(house_way("QS QD QC 9H 9S 3D 2C").1, "9H 9S");
+        // With two sets of three of a kind, the higher set is split.
+        let (high, low) = house_way("KS KH KD 5S 5H 5D 2C");
+        assert!(high.contains("5D 5H 5S") && high.matches('K').count() == 1);
+        assert_eq!(low.matches('K').count(), 2);
+        // A straight is played over a pair, keeping the pair low.
+        assert_eq!(house_way("9S 8D 7C 6H 5S 5D KC").1, "5D KC");
+        // The joker plays as an ace in a pair of aces.
+        assert_eq!(house_way("JK AS 9C 7H 5S 3D 2C").1, "7H 9C");
+    }
+
+    #[test]
+    fn test_settlement() {
+        let player = Split::parse("AS AD 9C 7H 2S", "KH QD").unwrap();
+        let banker = Split::parse("KS KD 9D 7C 2H", "QH JD").unwrap();
+        let win = settle(&player, &banker, 100, 5);
+        assert_eq!(
+            (win.outcome, win.commission, win.net),
+            (Outcome::Win, 5, 95)
+        );
+        assert_eq!(settle(&banker, &player, 100, 5).net, -100);
+        // A copy goes to the banker, so winning only the other hand is a push.
+        let copy = Split::parse("KH KC 9S 7D 2D", "AH 3D").unwrap();
+        assert_eq!(settle(&copy, &banker, 100, 5).outcome, Outcome::Push);
+        let copies = Split::parse("KH KC 9S 7D 2D", "QS JC").unwrap();
+        assert_eq!(settle(&copies, &banker, 100, 5).outcome, Outcome::Loss);
+    }
+}
@@ -1,10 +1,14 @@
-use crate::cards::{Card, Ranks, Suits};
+#[cfg(feature = "std")]
+use crate::cards::{parse_hand, Card};
+use crate::cards::{Ranks, Suits};
+#[cfg(feature = "std")]
 use crate::error::PokerHandError;
-use regex::Regex;
-use std::cmp::Ordering;
+#[cfg(feature = "std")]
+use core::cmp::Ordering;
+use core::fmt;
 
 /// Poker hand types, in the order of their relative value.
-#[derive(Debug, Eq, PartialEq, Ord, PartialOrd)]
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Copy, Clone, Hash)]
 pub enum PokerHandRanks {
     HighCard = 1,
     Pair,
@@ -17,12 +21,201 @@ pub enum PokerHandRanks {
     StraightFlush,
 }
 
+impl PokerHandRanks {
+    /// All hand types, from lowest to highest.
+    pub const ALL: [PokerHandRanks; 9] = [
+        PokerHandRanks::HighCard,
+        PokerHandRanks::Pair,
+        PokerHandRanks::TwoPair,
+        PokerHandRanks::ThreeOfAKind,
+        PokerHandRanks::Straight,
+        PokerHandRanks::Flush,
+        PokerHandRanks::FullHouse,
+        PokerHandRanks::FourOfAKind,
+        PokerHandRanks::StraightFlush,
+    ];
+}
+
+impl fmt::Display for PokerHandRanks {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            PokerHandRanks::HighCard => "high card",
+            PokerHandRanks::Pair => "a pair",
+            PokerHandRanks::TwoPair => "two pair",
+            PokerHandRanks::ThreeOfAKind => "three of a kind",
+            PokerHandRanks::Straight => "a straight",
+            PokerHandRanks::Flush => "a flush",
+            PokerHandRanks::FullHouse => "a full house",
+            PokerHandRanks::FourOfAKind => "four of a kind",
+            PokerHandRanks::StraightFlush => "a straight flush",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// A hand type together with the ranks that decide it, as typed fields.
+///
+/// Royal flushes are told apart from other straight flushes. Straights are described by
+/// their highest card, where the ace-low straight, or wheel, is five high.
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Hash)]
+pub enum HandDetail {
+    HighCard {
+        ranks: [Ranks; 5],
+    },
+    Pair {
+        pair: Ranks,
+        kickers: [Ranks; 3],
+    },
+    TwoPair {
+        high_pair: Ranks,
+        low_pair: Ranks,
+        kicker: Ranks,
+    },
+    ThreeOfAKind {
+        trips: Ranks,
+        kickers: [Ranks; 2],
+    },
+    Straight {
+        high: Ranks,
+    },
+    Flush {
+        suit: Suits,
+        ranks: [Ranks; 5],
+    },
+    FullHouse {
+        trips: Ranks,
+        pair: Ranks,
+    },
+    FourOfAKind {
+        quads: Ranks,
+        kicker: Ranks,
+    },
+    StraightFlush {
+        suit: Suits,
+        high: Ranks,
+    },
+    RoyalFlush {
+        suit: Suits,
+    },
+}
+
+impl HandDetail {
+    /// The hand type, where a royal flush is a straight flush.
+    pub fn hand_rank(&self) -> PokerHandRanks {
+        match self {
+            HandDetail::HighCard { .. } => PokerHandRanks::HighCard,
+            HandDetail::Pair { .. } => PokerHandRanks::Pair,
+            HandDetail::TwoPair { .. } => PokerHandRanks::TwoPair,
+            HandDetail::ThreeOfAKind { .. } => PokerHandRanks::ThreeOfAKind,
+            HandDetail::Straight { .. } => PokerHandRanks::Straight,
+            HandDetail::Flush { .. } => PokerHandRanks::Flush,
+            HandDetail::FullHouse { .. } => PokerHandRanks::FullHouse,
+            HandDetail::FourOfAKind { .. } => PokerHandRanks::FourOfAKind,
+            HandDetail::StraightFlush { .. } | HandDetail::RoyalFlush { .. } => {
+                PokerHandRanks::StraightFlush
+            }
+        }
+    }
+
+    pub fn is_royal_flush(&self) -> bool {
+        matches!(self, HandDetail::RoyalFlush { .. })
+    }
+
+    /// Whether the hand is a five high straight flush, also known as a steel wheel.
+    pub fn is_steel_wheel(&self) -> bool {
+        matches!(
+            self,
+            HandDetail::StraightFlush {
+                high: Ranks::Five,
+                ..
+            }
+        )
+    }
+}
+
+impl fmt::Display for HandDetail {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            HandDetail::HighCard { ranks } => write!(f, "{} high", rank_name(ranks[0])),
+            HandDetail::Pair { pair, .. } => write!(f, "a pair of {}", plural_rank_name(pair)),
+            HandDetail::TwoPair {
+                high_pair,
+                low_pair,
+                ..
+            } => write!(
+                f,
+                "two pair, {} and {}",
+                plural_rank_name(high_pair),
+                plural_rank_name(low_pair)
+            ),
+            HandDetail::ThreeOfAKind { trips, .. } => {
+                write!(f, "three of a kind, {}", plural_rank_name(trips))
+            }
+            HandDetail::Straight { high } => write!(f, "a straight, {} high", rank_name(high)),
+            HandDetail::Flush { ranks, .. } => write!(f, "a flush, {} high", rank_name(ranks[0])),
+            HandDetail::FullHouse { trips, pair } => write!(
+                f,
+                "a full house, {} full of {}",
+                plural_rank_name(trips),
+                plural_rank_name(pair)
+            ),
+            HandDetail::FourOfAKind { quads, .. } => {
+                write!(f, "four of a kind, {}", plural_rank_name(quads))
+            }
+            HandDetail::StraightFlush { high, .. } => {
+                write!(f, "a straight flush, {} high", rank_name(high))
+            }
+            HandDetail::RoyalFlush { .. } => write!(f, "a royal flush"),
+        }
+    }
+}
+
+fn rank_name(rank: Ranks) -> &'static str {
+    match rank {
+        Ranks::Two => "two",
+        Ranks::Three => "three",
+        Ranks::Four => "four",
+        Ranks::Five => "five",
+        Ranks::Six => "six",
+        Ranks::Seven => "seven",
+        Ranks::Eight => "eight",
+        Ranks::Nine => "nine",
+        Ranks::Ten => "ten",
+        Ranks::Jack => "jack",
+        Ranks::Queen => "queen",
+        Ranks::King => "king",
+        Ranks::Ace => "ace",
+    }
+}
+
+fn plural_rank_name(rank: Ranks) -> &'static str {
+    match rank {
+        Ranks::Two => "twos",
+        Ranks::Three => "threes",
+        Ranks::Four => "fours",
+        Ranks::Five => "fives",
+        Ranks::Six => "sixes",
+        Ranks::Seven => "sevens",
+        Ranks::Eight => "eights",
+        Ranks::Nine => "nines",
+        Ranks::Ten => "tens",
+        Ranks::Jack => "jacks",
+        Ranks::Queen => "queens",
+        Ranks::King => "kings",
+        Ranks::Ace => "aces",
+    }
+}
+
 /// A poker hand.
 ///
 /// PartialOrd is supported to allow sorting of hands.
 /// 'hand_handle' is a reference to the hand string from the calling environment so that it can
 /// be returned as a reference to the winning hand(s).
-#[derive(Debug)]
+///
+/// The cards are kept in a `Vec`, so `PokerHand` needs the `std` feature. Without it, use
+/// the allocation free `evaluator` instead.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone)]
 pub struct PokerHand<'a> {
     pub hand_handle: &'a str,
     pub hand_rank: PokerHandRanks,
@@ -34,19 +227,100 @@ pub struct PokerHand<'a> {
     pub cards: Vec<Card>,
 }
 
+#[cfg(feature = "std")]
 impl PokerHand<'_> {
     // Construct a new PokerHand from the hand string.
-    pub fn new(hand: &str) -> Result<PokerHand, PokerHandError> {
-        let mut cards: Vec<Card>;
-
+    pub fn new(hand: &str) -> Result<PokerHand<'_>, PokerHandError> {
         // Parse the hand string.
-        match PokerHand::parse_hand_str(hand) {
-            Some(parsed_cards) => {
-                cards = parsed_cards;
+        let cards = parse_hand::<5>(hand.as_bytes())?;
+        PokerHand::classify(hand, cards.to_vec())
+    }
+
+    /// Construct a new PokerHand from five cards rather than a hand string.
+    ///
+    /// The resulting hand has an empty 'hand_handle' as there is no string in the calling
+    /// environment to refer to.
+    pub fn from_cards(cards: &[Card]) -> Result<PokerHand<'static>, PokerHandError> {
+        if cards.len() != 5 {
+            return Err(PokerHandError::new("A poker hand must have five cards"));
+        }
+        PokerHand::classify("", cards.to_vec())
+    }
+
+    /// The best five card hand that can be made from five to seven cards.
+    ///
+    /// This is how hands are formed in community card games, where a player's hole cards
+    /// are combined with the board.
+    pub fn best_of(cards: &[Card]) -> Result<PokerHand<'static>, PokerHandError> {
+        if cards.len() < 5 || cards.len() > 7 {
+            return Err(PokerHandError::new(
+                "The best hand is chosen from 5 to 7 cards",
+            ));
+        }
+        let mut best: Option<PokerHand> = None;
+        // Each five bit subset of the card positions is one candidate hand.
+        for mask in 0u32..(1 << cards.len()) {
+            if mask.count_ones() != 5 {
+                continue;
+            }
+            let subset: Vec<Card> = (0..cards.len())
+                .filter(|i| mask & (1 << i) != 0)
+                .map(|i| cards[i])
+                .collect();
+            let hand = PokerHand::classify("", subset)?;
+            if best.as_ref().map_or(true, |best| hand > *best) {
+                best = Some(hand);
             }
-            None => return Err(PokerHandError::new("Invalid poker hand")),
         }
+        Ok(best.unwrap())
+    }
 
+    /// The hand type and the ranks that decide it.
+    pub fn detail(&self) -> HandDetail {
+        let rank = |i: usize| self.cards[i].rank;
+        let suit = self.cards[0].suit;
+        match self.hand_rank {
+            PokerHandRanks::HighCard => HandDetail::HighCard {
+                ranks: [rank(0), rank(1), rank(2), rank(3), rank(4)],
+            },
+            PokerHandRanks::Pair => HandDetail::Pair {
+                pair: rank(0),
+                kickers: [rank(2), rank(3), rank(4)],
+            },
+            PokerHandRanks::TwoPair => HandDetail::TwoPair {
+                high_pair: rank(0),
+                low_pair: rank(2),
+                kicker: rank(4),
+            },
+            PokerHandRanks::ThreeOfAKind => HandDetail::ThreeOfAKind {
+                trips: rank(0),
+                kickers: [rank(3), rank(4)],
+            },
+            PokerHandRanks::Straight => HandDetail::Straight { high: rank(0) },
+            PokerHandRanks::Flush => HandDetail::Flush {
+                suit,
+                ranks: [rank(0), rank(1), rank(2), rank(3), rank(4)],
+            },
+            PokerHandRanks::FullHouse => HandDetail::FullHouse {
+                trips: rank(0),
+                pair: rank(3),
+            },
+            PokerHandRanks::FourOfAKind => HandDetail::FourOfAKind {
+                quads: rank(0),
+                kicker: rank(4),
+            },
+            PokerHandRanks::StraightFlush if rank(0) == Ranks::Ace => {
+                HandDetail::RoyalFlush { suit }
+            }
+            PokerHandRanks::StraightFlush => HandDetail::StraightFlush {
+                suit,
+                high: rank(0),
+            },
+        }
+    }
+
+    /// Sort and classify the cards, recording the hand rank and the scoring order.
+    fn classify(hand_handle: &str, mut cards: Vec<Card>) -> Result<PokerHand<'_>, PokerHandError> {
         // Sort the cards from highest rank to lowest.
         cards.sort();
         cards.reverse();
@@ -60,16 +334,15 @@ impl PokerHand<'_> {
         let mut hand_rank: PokerHandRanks = PokerHandRanks::HighCard;
 
         PokerHand::check_flush(&cards, &mut hand_rank);
-        if !PokerHand::check_straight(&mut cards, &mut hand_rank) {
-            if !PokerHand::check_four_of_a_kind(&mut cards, &mut hand_rank) {
-                if !PokerHand::check_three_and_full_house(&mut cards, &mut hand_rank) {
-                    PokerHand::check_one_and_two_pairs(&mut cards, &mut hand_rank);
-                }
-            }
+        if !PokerHand::check_straight(&mut cards, &mut hand_rank)
+            && !PokerHand::check_four_of_a_kind(&mut cards, &mut hand_rank)
+            && !PokerHand::check_three_and_full_house(&mut cards, &mut hand_rank)
+        {
+            PokerHand::check_one_and_two_pairs(&mut cards, &mut hand_rank);
         }
 
         Ok(PokerHand {
-            hand_handle: hand,
+            hand_handle,
             hand_rank,
             cards,
         })
@@ -87,7 +360,7 @@ impl PokerHand<'_> {
         false
     }
 
-    fn check_straight(cards: &mut Vec<Card>, hand_rank: &mut PokerHandRanks) -> bool {
+    fn check_straight(cards: &mut [Card], hand_rank: &mut PokerHandRanks) -> bool {
         if (cards[0].rank as isize == cards[1].rank as isize + 1
             && cards[0].rank as isize == cards[2].rank as isize + 2
             && cards[0].rank as isize == cards[3].rank as isize + 3
@@ -106,15 +379,14 @@ impl PokerHand<'_> {
             }
             if cards[0].rank == Ranks::Ace && cards[1].rank == Ranks::Five {
                 // Move the Ace to the end of the hand.
-                let ace = cards.remove(0);
-                cards.push(ace);
+                cards.rotate_left(1);
             }
             return true;
         }
         false
     }
 
-    fn check_four_of_a_kind(cards: &mut Vec<Card>, hand_rank: &mut PokerHandRanks) -> bool {
+    fn check_four_of_a_kind(cards: &mut [Card], hand_rank: &mut PokerHandRanks) -> bool {
         if cards[1].rank == cards[2].rank
             && cards[1].rank == cards[3].rank
             && (cards[1].rank == cards[0].rank || cards[1].rank == cards[4].rank)
@@ -129,7 +401,7 @@ impl PokerHand<'_> {
         false
     }
 
-    fn check_three_and_full_house(cards: &mut Vec<Card>, hand_rank: &mut PokerHandRanks) -> bool {
+    fn check_three_and_full_house(cards: &mut [Card], hand_rank: &mut PokerHandRanks) -> bool {
         if cards[0].rank == cards[1].rank && cards[0].rank == cards[2].rank {
             if cards[3].rank == cards[4].rank {
                 *hand_rank = PokerHandRanks::FullHouse;
@@ -156,7 +428,7 @@ impl PokerHand<'_> {
         false
     }
 
-    fn check_one_and_two_pairs(cards: &mut Vec<Card>, hand_rank: &mut PokerHandRanks) -> bool {
+    fn check_one_and_two_pairs(cards: &mut [Card], hand_rank: &mut PokerHandRanks) -> bool {
         if cards[0].rank == cards[1].rank {
             if cards[2].rank == cards[3].rank {
                 *hand_rank = PokerHandRanks::TwoPair;
@@ -197,35 +469,8 @@ impl PokerHand<'_> {
         false
     }
 
-    fn convert_strings_to_card(rank: &str, suit: &str) -> Card {
-        let card_rank: Ranks = match rank {
-            "2" => Ranks::Two,
-            "3" => Ranks::Three,
-            "4" => Ranks::Four,
-            "5" => Ranks::Five,
-            "6" => Ranks::Six,
-            "7" => Ranks::Seven,
-            "8" => Ranks::Eight,
-            "9" => Ranks::Nine,
-            "10" => Ranks::Ten,
-            "J" => Ranks::Jack,
-            "Q" => Ranks::Queen,
-            "K" => Ranks::King,
-            "A" => Ranks::Ace,
-            _ => panic!("Invalid card rank"),
-        };
-        let card_suit: Suits = match suit {
-            "H" => Suits::Hearts,
-            "S" => Suits::Spades,
-            "C" => Suits::Clubs,
-            "D" => Suits::Diamonds,
-            _ => panic!("Invalid card suit"),
-        };
-        Card::new(card_rank, card_suit)
-    }
-
     /// Check for duplicate cards in a hand.
-    fn check_for_duplicate_cards(cards: &Vec<Card>) -> bool {
+    fn check_for_duplicate_cards(cards: &[Card]) -> bool {
         // NOTE: even though the cards are sorted, we still need to check every pair
         // as the cards are only sorted by rank, so duplicates may not be adjacent.
         // For example, "4C 4S 4C 3S 2H".
@@ -240,36 +485,35 @@ impl PokerHand<'_> {
     }
 
     /// Parse the hand string into a vector of cards.
+    #[cfg(test)]
     fn parse_hand_str(hand: &str) -> Option<Vec<Card>> {
-        let mut cards: Vec<Card> = Vec::with_capacity(5);
-
-        let re = Regex::new(r"^(?<rank1>[2-9]|10|[JQKA])(?<suit1>[HSCD]) (?<rank2>[2-9]|10|[JQKA])(?<suit2>[HSCD]) (?<rank3>[2-9]|10|[JQKA])(?<suit3>[HSCD]) (?<rank4>[2-9]|10|[JQKA])(?<suit4>[HSCD]) (?<rank5>[2-9]|10|[JQKA])(?<suit5>[HSCD])$").unwrap();
-        let Some(caps) = re.captures(hand) else { return None; };
-        cards.push(PokerHand::convert_strings_to_card(
-            &caps["rank1"],
-            &caps["suit1"],
-        ));
-        cards.push(PokerHand::convert_strings_to_card(
-            &caps["rank2"],
-            &caps["suit2"],
-        ));
-        cards.push(PokerHand::convert_strings_to_card(
-            &caps["rank3"],
-            &caps["suit3"],
-        ));
-        cards.push(PokerHand::convert_strings_to_card(
-            &caps["rank4"],
-            &caps["suit4"],
-        ));
-        cards.push(PokerHand::convert_strings_to_card(
-            &caps["rank5"],
-            &caps["suit5"],
-        ));
+        parse_hand::<5>(hand.as_bytes())
+            .ok()
+            .map(|cards| cards.to_vec())
+    }
+}
 
-        Some(cards)
+/// Sort hands from best to worst and group the equal hands together.
+///
+/// Each hand is paired with a key, such as the hand string or a seat number, and the keys
+/// are returned in tiers: the first tier holds the key(s) of the winning hand(s). Any hand
+/// type can be ranked, such as `PokerHand` or another game's hands, by its `PartialOrd`.
+#[cfg(feature = "std")]
+pub fn rank_tiers<T: Copy, H: PartialOrd>(hands: &[(T, H)]) -> Vec<Vec<T>> {
+    // Highest hand first.
+    let mut sorted: Vec<&(T, H)> = hands.iter().collect();
+    sorted.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(Ordering::Equal));
+    let mut tiers: Vec<Vec<T>> = Vec::new();
+    for (i, (key, hand)) in sorted.iter().enumerate() {
+        match tiers.last_mut() {
+            Some(tier) if *hand == sorted[i - 1].1 => tier.push(*key),
+            _ => tiers.push(vec![*key]),
+        }
     }
+    tiers
 }
 
+#[cfg(feature = "std")]
 impl PartialEq for PokerHand<'_> {
     fn eq(&self, other: &Self) -> bool {
         if self.hand_rank != other.hand_rank {
@@ -285,6 +529,7 @@ impl PartialEq for PokerHand<'_> {
     }
 }
 
+#[cfg(feature = "std")]
 impl PartialOrd for PokerHand<'_> {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         if self.hand_rank < other.hand_rank {
@@ -305,7 +550,7 @@ impl PartialOrd for PokerHand<'_> {
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
 mod tests {
     use super::*;
 
@@ -356,6 +601,110 @@ mod tests {
         assert!(hand3 == hand1);
     }
 
+    #[test]
+    fn test_from_cards() {
+        let cards = PokerHand::parse_hand_str("9H 9S 9C 9D 5H").unwrap();
+        let hand = PokerHand::from_cards(&cards).unwrap();
+        assert!(hand.hand_rank == PokerHandRanks::FourOfAKind);
+        assert!(hand == PokerHand::new("9H 9S 9C 9D 5H").unwrap());
+        assert!(PokerHand::from_cards(&cards[..4]).is_err());
+    }
+
+    #[test]
+    fn test_best_of() {
+        let cards = crate::cards::parse_cards("AH KH 2C QH JH 10H 3S").unwrap();
+        let hand = PokerHand::best_of(&cards).unwrap();
+        assert!(hand.hand_rank == PokerHandRanks::StraightFlush);
+        assert!(hand.cards[0].rank == Ranks::Ace);
+        let cards = crate::cards::parse_cards("2C 2D 9S 9H 5C 5D KS").unwrap();
+        let hand = PokerHand::best_of(&cards).unwrap();
+        assert!(hand == PokerHand::new("9S 9H 5C 5D KS").unwrap());
+        assert!(PokerHand::best_of(&cards[..4]).is_err());
+        let duplicates = [cards[0], cards[0], cards[1], cards[2], cards[3], cards[4]];
+        assert!(PokerHand::best_of(&duplicates).is_err());
+    }
+
+    #[test]
+    fn test_rank_tiers() {
+        let hands = vec![
+            (0, PokerHand::new("4D 5S 6S 8D 3C").unwrap()),
+            (1, PokerHand::new("3S 4S 5D 6H JH").unwrap()),
+            (2, PokerHand::new("2S 2C 7S 9H 10H").unwrap()),
+            (3, PokerHand::new("3H 4H 5C 6C JD").unwrap()),
+        ];
+        let tiers = rank_tiers(&hands);
+        assert_eq!(tiers.len(), 3);
+        assert_eq!(tiers[0], vec![2]);
+        assert_eq!(tiers[1].len(), 2);
+        assert!(tiers[1].contains(&1) && tiers[1].contains(&3));
+        assert_eq!(tiers[2], vec![0]);
+    }
+
+    #[test]
+    fn test_hand_detail() {
+        let detail = |hand: &str| PokerHand::new(hand).unwrap().detail();
+        assert_eq!(
+            detail("AS AD AC KH KS"),
+            HandDetail::FullHouse {
+                trips: Ranks::Ace,
+                pair: Ranks::King
+            }
+        );
+        assert_eq!(
+            detail("10H JH QH KH AH"),
+            HandDetail::RoyalFlush {
+                suit: Suits::Hearts
+            }
+        );
+        assert!(detail("AD 2D 3D 4D 5D").is_steel_wheel());
+        assert_eq!(
+            detail("AD 2C 3D 4D 5D"),
+            HandDetail::Straight { high: Ranks::Five }
+        );
+        assert_eq!(
+            detail("7S 7D 7C 7H 2S"),
+            HandDetail::FourOfAKind {
+                quads: Ranks::Seven,
+                kicker: Ranks::Two
+            }
+        );
+        assert_eq!(
+            detail("9S 4D 9C 4H JS"),
+            HandDetail::TwoPair {
+                high_pair: Ranks::Nine,
+                low_pair: Ranks::Four,
+                kicker: Ranks::Jack
+            }
+        );
+        assert_eq!(
+            detail("QS 3D QC 8H 5S"),
+            HandDetail::Pair {
+                pair: Ranks::Queen,
+                kickers: [Ranks::Eight, Ranks::Five, Ranks::Three]
+            }
+        );
+        for rank in PokerHandRanks::ALL {
+            let hand = crate::combinatorics::all_hands()
+                .map(|cards| PokerHand::from_cards(&cards).unwrap())
+                .find(|hand| hand.hand_rank == rank)
+                .unwrap();
+            assert_eq!(hand.detail().hand_rank(), rank);
+        }
+    }
+
+    #[test]
+    fn test_hand_detail_display() {
+        let describe = |hand: &str| PokerHand::new(hand).unwrap().detail().to_string();
+        assert_eq!(
+            describe("AS AD AC KH KS"),
+            "a full house, aces full of kings"
+        );
+        assert_eq!(describe("10H JH QH KH AH"), "a royal flush");
+        assert_eq!(describe("6S 6D 3C 3H 2S"), "two pair, sixes and threes");
+        assert_eq!(describe("AD 2C 3D 4D 5D"), "a straight, five high");
+        assert_eq!(describe("KD 9C 3D 4D 5D"), "king high");
+    }
+
     #[test]
     fn test_parse_hand_str() {
         let hand_str = "9H AS JC 10D 5H";
@@ -378,6 +727,15 @@ mod tests {
         assert_eq!(result, None);
     }
 
+    #[test]
+    fn test_parse_error_reported() {
+        let error = PokerHand::new("9H AS JC 12D 5H").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "PokerHandError: ParseError: Invalid card rank at byte 9"
+        );
+    }
+
     #[test]
     fn test_parse_hand_str_invalid_suit() {
         let hand_str = "9H AS JK 10D 5H";
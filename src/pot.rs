@@ -0,0 +1,399 @@
+//! Main and side pots, and their distribution at showdown.
+//!
+//! When a player is all in for less than the other players have bet, they can only win
+//! the part of each bet that they matched. The chips are therefore split into a main pot,
+//! which every remaining player can win, and side pots, which only the players who
+//! contributed to them can win. Each pot is awarded to the best hand among its eligible
+//! players, split evenly on a tie, with any odd chips awarded according to an `OddChipRule`.
+
+use crate::cards::Card;
+use crate::error::PokerHandError;
+use crate::poker_hand::{rank_tiers, PokerHand};
+
+/// The chips a player put into the pot over the whole hand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Contribution {
+    pub seat: usize,
+    pub amount: u64,
+    /// A player who folded leaves their chips in the pot but cannot win any of it.
+    pub folded: bool,
+}
+
+impl Contribution {
+    pub fn new(seat: usize, amount: u64, folded: bool) -> Self {
+        Self {
+            seat,
+            amount,
+            folded,
+        }
+    }
+}
+
+/// A main or side pot.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pot {
+    pub amount: u64,
+    /// The seats which can win the pot, in ascending order.
+    pub eligible: Vec<usize>,
+}
+
+/// Chips won from one pot by one player.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Award {
+    /// The index of the pot, where 0 is the main pot.
+    pub pot: usize,
+    pub seat: usize,
+    pub amount: u64,
+}
+
+/// How chips which cannot be split evenly between tied winners are awarded.
+///
+/// Each odd chip goes to a different winner, in the order given by the rule.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OddChipRule {
+    /// The first winners clockwise from the button get the odd chips.
+    /// Seats are numbered clockwise from 0 to `table_size - 1`.
+    LeftOfButton { button: usize, table_size: usize },
+    /// The winners holding the highest card get the odd chips, with ties between cards of
+    /// the same rank broken by suit: spades, hearts, diamonds, then clubs.
+    HighCardBySuit,
+}
+
+/// Split the contributions into the main pot followed by any side pots.
+///
+/// A new pot starts at every distinct amount contributed by a player who has not folded.
+/// Folded players' chips are added to the pots they contributed to, and pots with no
+/// chips are dropped. If every player has folded, the chips are returned in a single pot
+/// with no eligible seats, which cannot be distributed.
+pub fn build_pots(contributions: &[Contribution]) -> Vec<Pot> {
+    let mut levels: Vec<u64> = contributions
+        .iter()
+        .filter(|c| !c.folded)
+        .map(|c| c.amount)
+        .collect();
+    levels.sort_unstable();
+    levels.dedup();
+    if levels.is_empty() {
+        let amount: u64 = contributions.iter().map(|c| c.amount).sum();
+        if amount == 0 {
+            return Vec::new();
+        }
+        return vec![Pot {
+            amount,
+            eligible: Vec::new(),
+        }];
+    }
+
+    let mut pots: Vec<Pot> = Vec::new();
+    let mut previous = 0;
+    for (i, &level) in levels.iter().enumerate() {
+        // Anything above the highest live contribution was never called, but if a folded
+        // player put it in, it still belongs in the last pot.
+        let top = if i + 1 == levels.len() {
+            u64::MAX
+        } else {
+            level
+        };
+        let amount: u64 = contributions
+            .iter()
+            .map(|c| c.amount.min(top) - c.amount.min(previous))
+            .sum();
+        let mut eligible: Vec<usize> = contributions
+            .iter()
+            .filter(|c| !c.folded && c.amount >= level)
+            .map(|c| c.seat)
+            .collect();
+        eligible.sort_unstable();
+        if amount > 0 {
+            pots.push(Pot { amount, eligible });
+        }
+        previous = level;
+    }
+    pots
+}
+
+/// Award each pot to the best of the eligible hands.
+///
+/// `hands` holds the shown hand of every player who has not folded, keyed by seat.
+pub fn distribute(
+    pots: &[Pot],
+    hands: &[(usize, PokerHand)],
+    rule: &OddChipRule,
+) -> Result<Vec<Award>, PokerHandError> {
+    if let OddChipRule::LeftOfButton { button, table_size } = rule {
+        if button >= table_size {
+            return Err(PokerHandError::new(
+                "The button must be a seat at the table",
+            ));
+        }
+    }
+    let tiers = rank_tiers(hands);
+    let order = odd_chip_order(hands, rule);
+    distribute_by_tiers(pots, &tiers, &order)
+}
+
+/// Award each pot to the highest tier of players eligible for it.
+///
+/// `tiers` ranks the seats from best to worst with ties grouped together, as returned by
+/// `rank_tiers`. Odd chips go to the winners in the order they appear in `odd_chip_order`.
+pub fn distribute_by_tiers(
+    pots: &[Pot],
+    tiers: &[Vec<usize>],
+    odd_chip_order: &[usize],
+) -> Result<Vec<Award>, PokerHandError> {
+    let mut awards = Vec::new();
+    for (index, pot) in pots.iter().enumerate() {
+        let mut winners: Vec<usize> = tiers
+            .iter()
+            .map(|tier| {
+                tier.iter()
+                    .copied()
+                    .filter(|seat| pot.eligible.contains(seat))
+                    .collect::<Vec<usize>>()
+            })
+            .find(|winners| !winners.is_empty())
+            .ok_or_else(|| PokerHandError::new("No eligible hand for the pot"))?;
+        winners.sort_by_key(|seat| {
+            odd_chip_order
+                .iter()
+                .position(|s| s == seat)
+                .unwrap_or(usize::MAX)
+        });
+
+        let share = pot.amount / winners.len() as u64;
+        let odd_chips = (pot.amount % winners.len() as u64) as usize;
+        for (i, &seat) in winners.iter().enumerate() {
+            let amount = share + if i < odd_chips { 1 } else { 0 };
+            if amount > 0 {
+                awards.push(Award {
+                    pot: index,
+                    seat,
+                    amount,
+                });
+            }
+        }
+    }
+    Ok(awards)
+}
+
+/// Build the pots from the contributions and award them to the shown hands.
+pub fn settle(
+    contributions: &[Contribution],
+    hands: &[(usize, PokerHand)],
+    rule: &OddChipRule,
+) -> Result<Vec<Award>, PokerHandError> {
+    distribute(&build_pots(contributions), hands, rule)
+}
+
+/// The total won by each seat, in ascending seat order.
+pub fn totals(awards: &[Award]) -> Vec<(usize, u64)> {
+    let mut totals: Vec<(usize, u64)> = Vec::new();
+    for award in awards {
+        match totals.iter_mut().find(|(seat, _)| *seat == award.seat) {
+            Some((_, total)) => *total += award.amount,
+            None => totals.push((award.seat, award.amount)),
+        }
+    }
+    totals.sort_unstable();
+    totals
+}
+
+/// The order in which seats receive odd chips under the rule.
+fn odd_chip_order(hands: &[(usize, PokerHand)], rule: &OddChipRule) -> Vec<usize> {
+    let mut seats: Vec<usize> = hands.iter().map(|(seat, _)| *seat).collect();
+    match rule {
+        OddChipRule::LeftOfButton { button, table_size } => {
+            // Distance clockwise from the seat after the button.
+            seats.sort_by_key(|seat| (seat + table_size - button - 1) % table_size);
+        }
+        OddChipRule::HighCardBySuit => {
+            let high_card = |seat: &usize| -> Option<Card> {
+                let (_, hand) = hands.iter().find(|(s, _)| s == seat)?;
+                hand.cards
+                    .iter()
+                    .copied()
+                    .max_by_key(|card| (card.rank, card.suit))
+            };
+            seats.sort_by_key(|seat| {
+                std::cmp::Reverse(high_card(seat).map(|card| (card.rank, card.suit)))
+            });
+        }
+    }
+    seats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hand(cards: &str) -> PokerHand<'_> {
+        PokerHand::new(cards).unwrap()
+    }
+
+    #[test]
+    fn test_single_pot() {
+        let contributions = [
+            Contribution::new(0, 100, false),
+            Contribution::new(1, 100, false),
+            Contribution::new(2, 40, true),
+        ];
+        let pots = build_pots(&contributions);
+        assert_eq!(
+            pots,
+            vec![Pot {
+                amount: 240,
+                eligible: vec![0, 1]
+            }]
+        );
+    }
+
+    #[test]
+    fn test_side_pots() {
+        // Seat 0 is all in for 50, seat 1 for 200, and seat 2 covers both.
+        let contributions = [
+            Contribution::new(0, 50, false),
+            Contribution::new(1, 200, false),
+            Contribution::new(2, 300, false),
+            Contribution::new(3, 120, true),
+        ];
+        let pots = build_pots(&contributions);
+        assert_eq!(pots.len(), 3);
+        assert_eq!(pots[0].amount, 200);
+        assert_eq!(pots[0].eligible, vec![0, 1, 2]);
+        assert_eq!(pots[1].amount, 370);
+        assert_eq!(pots[1].eligible, vec![1, 2]);
+        assert_eq!(pots[2].amount, 100);
+        assert_eq!(pots[2].eligible, vec![2]);
+        let total: u64 = pots.iter().map(|pot| pot.amount).sum();
+        assert_eq!(total, 670);
+    }
+
+    #[test]
+    fn test_short_stack_wins_main_pot_only() {
+        let contributions = [
+            Contribution::new(0, 50, false),
+            Contribution::new(1, 200, false),
+            Contribution::new(2, 200, false),
+        ];
+        let hands = [
+            (0, hand("AS AD KC 7H 2S")),
+            (1, hand("KS KD QC 7C 2D")),
+            (2, hand("QS QD JC 8H 3S")),
+        ];
+        let awards = settle(&contributions, &hands, &OddChipRule::HighCardBySuit).unwrap();
+        assert_eq!(totals(&awards), vec![(0, 150), (1, 300)]);
+    }
+
+    #[test]
+    fn test_split_pot_odd_chip_left_of_button() {
+        let contributions = [
+            Contribution::new(0, 51, false),
+            Contribution::new(1, 51, true),
+            Contribution::new(3, 51, false),
+        ];
+        let hands = [(0, hand("AS KD QC JH 9S")), (3, hand("AD KS QH JC 9C"))];
+        // Seat 3 is the first winner after the button in seat 2.
+        let rule = OddChipRule::LeftOfButton {
+            button: 2,
+            table_size: 6,
+        };
+        let awards = settle(&contributions, &hands, &rule).unwrap();
+        assert_eq!(totals(&awards), vec![(0, 76), (3, 77)]);
+        // With the button on seat 5, seat 0 is first.
+        let rule = OddChipRule::LeftOfButton {
+            button: 5,
+            table_size: 6,
+        };
+        let awards = settle(&contributions, &hands, &rule).unwrap();
+        assert_eq!(totals(&awards), vec![(0, 77), (3, 76)]);
+    }
+
+    #[test]
+    fn test_split_pot_odd_chip_high_card_by_suit() {
+        let contributions = [
+            Contribution::new(0, 50, false),
+            Contribution::new(1, 51, false),
+        ];
+        // Both hands are ace high, but seat 1 holds the ace of spades.
+        let hands = [(0, hand("AH KD QC JH 9S")), (1, hand("AS KS QH JC 9C"))];
+        let awards = settle(&contributions, &hands, &OddChipRule::HighCardBySuit).unwrap();
+        assert_eq!(
+            awards,
+            vec![
+                Award {
+                    pot: 0,
+                    seat: 1,
+                    amount: 50
+                },
+                Award {
+                    pot: 0,
+                    seat: 0,
+                    amount: 50
+                },
+                Award {
+                    pot: 1,
+                    seat: 1,
+                    amount: 1
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_three_way_split_with_side_pot() {
+        let pots = [
+            Pot {
+                amount: 100,
+                eligible: vec![0, 1, 2],
+            },
+            Pot {
+                amount: 40,
+                eligible: vec![1, 2],
+            },
+        ];
+        let tiers = vec![vec![0], vec![1, 2]];
+        let awards = distribute_by_tiers(&pots, &tiers, &[2, 1, 0]).unwrap();
+        assert_eq!(totals(&awards), vec![(0, 100), (1, 20), (2, 20)]);
+        // Nobody eligible for the side pot showed a hand.
+        assert!(distribute_by_tiers(&pots, &[vec![0]], &[0]).is_err());
+    }
+
+    #[test]
+    fn test_everyone_folded() {
+        let contributions = [
+            Contribution::new(0, 30, true),
+            Contribution::new(1, 50, true),
+        ];
+        let pots = build_pots(&contributions);
+        assert_eq!(
+            pots,
+            vec![Pot {
+                amount: 80,
+                eligible: vec![]
+            }]
+        );
+        assert!(settle(&contributions, &[], &OddChipRule::HighCardBySuit).is_err());
+        assert!(build_pots(&[Contribution::new(0, 0, true)]).is_empty());
+    }
+
+    #[test]
+    fn test_invalid_odd_chip_rule() {
+        let contributions = [
+            Contribution::new(0, 25, false),
+            Contribution::new(1, 25, false),
+        ];
+        let hands = [(0, hand("AH KD 9S 7C 2D")), (1, hand("AS KC 9D 7H 2C"))];
+        for (button, table_size) in [(0, 0), (2, 2), (5, 2)] {
+            let rule = OddChipRule::LeftOfButton { button, table_size };
+            assert!(settle(&contributions, &hands, &rule).is_err());
+        }
+        let rule = OddChipRule::LeftOfButton {
+            button: 1,
+            table_size: 2,
+        };
+        assert_eq!(
+            totals(&settle(&contributions, &hands, &rule).unwrap()),
+            vec![(0, 25), (1, 25)]
+        );
+    }
+}
@@ -0,0 +1,496 @@
+//! Preflop all-in equity between the 169 classes of starting hands.
+//!
+//! The equity of one class against another is averaged over every way the two classes
+//! can be dealt without sharing a card, and over the boards to come. Tools which work with
+//! classes rather than individual holdings, such as push/fold solvers, need the whole
+//! table, which is expensive to compute, so it is computed once and then looked up.
+//!
+//! The crate embeds a table computed exhaustively, over every board for every pair of
+//! holdings, which `PreflopEquity::embedded` loads. It is stored compactly: the equities
+//! of the classes against themselves are always one half and the rest of the table is
+//! symmetric, so only the classes against the classes after them in grid order are kept,
+//! each as a 16 bit fraction.
+
+use crate::cards::{Card, Ranks, Suits};
+use crate::error::PokerHandError;
+use crate::evaluator::evaluate_unchecked;
+use crate::rng::SeededRng;
+use crate::starting_hands::{StartingHand, CLASSES};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// The serialized form of the exhaustively computed table.
+const EMBEDDED: &[u8] = include_bytes!("preflop_equity.bin");
+
+/// The serialized form starts with these bytes, then a version byte.
+const MAGIC: &[u8; 4] = b"PFEQ";
+const VERSION: u8 = 1;
+
+/// The number of class pairs stored: each class against the classes after it.
+const STORED: usize = CLASSES * (CLASSES - 1) / 2;
+
+/// The all-in equity of every starting hand class against every other.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PreflopEquity {
+    /// The equity of the row class against the column class, in grid order.
+    equity: Vec<f64>,
+}
+
+impl PreflopEquity {
+    /// Estimate the table by dealing `samples` random holdings and boards for each pair of
+    /// classes, using the seed.
+    ///
+    /// A class against itself always has an equity of one half, and the equity of one class
+    /// against another is one less the equity of the other against it, so only half of the
+    /// table is sampled.
+    pub fn sampled(samples: usize, seed: u64) -> PreflopEquity {
+        let mut rng = SeededRng::new(seed);
+        let hands: Vec<StartingHand> = StartingHand::all().collect();
+        let combos: Vec<Vec<[Card; 2]>> = hands.iter().map(StartingHand::combos).collect();
+        let deck: Vec<Card> = (0..52).filter_map(Card::from_index).collect();
+        let mut table = PreflopEquity {
+            equity: vec![0.5; CLASSES * CLASSES],
+        };
+        let mut unseen = Vec::with_capacity(48);
+        for i in 0..CLASSES {
+            for j in i + 1..CLASSES {
+                let mut won = 0.0;
+                for _ in 0..samples {
+                    let (ours, theirs) = loop {
+                        let ours = combos[i][rng.below(combos[i].len())];
+                        let theirs = combos[j][rng.below(combos[j].len())];
+                        if !theirs.iter().any(|card| ours.contains(card)) {
+                            break (ours, theirs);
+                        }
+                    };
+                    unseen.clear();
+                    unseen.extend(
+                        deck.iter()
+                            .filter(|card| !ours.contains(card) && !theirs.contains(card)),
+                    );
+                    rng.shuffle_prefix(&mut unseen, 5);
+                    won += showdown(ours, theirs, &unseen[..5]);
+                }
+                table.set(i, j, won / samples.max(1) as f64);
+            }
+        }
+        table
+    }
+
+    /// Compute the table exactly, by dealing every board for every pair of holdings.
+    ///
+    /// Holdings which differ only by a renaming of the suits, such as A♠K♠ against Q♥Q♦
+    /// and A♥K♥ against Q♠Q♦, have the same equity, so each of the 47,008 distinct
+    /// matchups is only dealt out once. This takes the better part of an hour on one core
+    /// in a release build, and is how the embedded table was made; the ignored test
+    /// `test_regenerate_embedded_table` checks that the two still agree.
+    pub fn exhaustive() -> PreflopEquity {
+        let evaluator = SevenCardEvaluator::new();
+        let hands: Vec<StartingHand> = StartingHand::all().collect();
+        let combos: Vec<Vec<[Card; 2]>> = hands.iter().map(StartingHand::combos).collect();
+        let permutations = suit_permutations();
+        let mut matchups: HashMap<[usize; 4], f64> = HashMap::new();
+        let mut table = PreflopEquity {
+            equity: vec![0.5; CLASSES * CLASSES],
+        };
+        for i in 0..CLASSES {
+            for j in i + 1..CLASSES {
+                let (mut total, mut count) = (0.0, 0);
+                for ours in &combos[i] {
+                    for theirs in &combos[j] {
+                        if theirs.iter().any(|card| ours.contains(card)) {
+                            continue;
+                        }
+                        let key = canonical(*ours, *theirs, &permutations);
+                        total += *matchups
+                            .entry(key)
+                            .or_insert_with(|| evaluator.matchup_equity(key));
+                        count += 1;
+                    }
+                }
+                table.set(i, j, total / count as f64);
+            }
+        }
+        table
+    }
+
+    /// The table embedded in the crate, which was computed by `exhaustive`.
+    pub fn embedded() -> &'static PreflopEquity {
+        static TABLE: OnceLock<PreflopEquity> = OnceLock::new();
+        TABLE.get_or_init(|| {
+            // The embedded bytes are checked by the tests.
+            PreflopEquity::from_bytes(EMBEDDED).unwrap()
+        })
+    }
+
+    /// The compact serialized form of the table.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(MAGIC.len() + 1 + 2 * STORED);
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+        for i in 0..CLASSES {
+            for j in i + 1..CLASSES {
+                let fraction = (self.equity_by_index(i, j) * u16::MAX as f64).round() as u16;
+                bytes.extend_from_slice(&fraction.to_le_bytes());
+            }
+        }
+        bytes
+    }
+
+    /// Read a table written by `to_bytes`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<PreflopEquity, PokerHandError> {
+        let header = MAGIC.len() + 1;
+        if bytes.len() != header + 2 * STORED || &bytes[..MAGIC.len()] != MAGIC {
+            return Err(PokerHandError::new("Invalid preflop equity table"));
+        }
+        if bytes[MAGIC.len()] != VERSION {
+            return Err(PokerHandError::new(
+                "Unsupported preflop equity table version",
+            ));
+        }
+        let mut table = PreflopEquity {
+            equity: vec![0.5; CLASSES * CLASSES],
+        };
+        let mut fractions = bytes[header..]
+            .chunks_exact(2)
+            .map(|pair| u16::from_le_bytes([pair[0], pair[1]]) as f64 / u16::MAX as f64);
+        for i in 0..CLASSES {
+            for j in i + 1..CLASSES {
+                table.set(i, j, fractions.next().unwrap());
+            }
+        }
+        Ok(table)
+    }
+
+    /// The equity of `hand` against `opponent`.
+    pub fn equity(&self, hand: StartingHand, opponent: StartingHand) -> f64 {
+        self.equity[hand.index() * CLASSES + opponent.index()]
+    }
+
+    /// The equity by class number, for callers iterating over the whole table.
+    pub fn equity_by_index(&self, hand: usize, opponent: usize) -> f64 {
+        self.equity[hand * CLASSES + opponent]
+    }
+
+    /// The equity of a holding against another, which is the equity of their classes.
+    ///
+    /// The table does not distinguish the suits within a class, so this is the average
+    /// over the class; `equity::heads_up_odds` gives the exact equity of two holdings.
+    pub fn holding_equity(
+        &self,
+        hand: [Card; 2],
+        opponent: [Card; 2],
+    ) -> Result<f64, PokerHandError> {
+        if opponent.iter().any(|card| hand.contains(card)) {
+            return Err(PokerHandError::new("Duplicate cards in hand"));
+        }
+        let hand = StartingHand::from_cards(hand[0], hand[1])?;
+        let opponent = StartingHand::from_cards(opponent[0], opponent[1])?;
+        Ok(self.equity(hand, opponent))
+    }
+
+    /// The equity of a class against a random holding, allowing for the cards the class
+    /// holds.
+    pub fn equity_against_random(&self, hand: StartingHand) -> f64 {
+        let (mut total, mut weight) = (0.0, 0.0);
+        for opponent in StartingHand::all() {
+            let matchups = hand.matchups_against(&opponent) as f64;
+            total += matchups * self.equity(hand, opponent);
+            weight += matchups;
+        }
+        total / weight
+    }
+
+    /// The equity of a holding against a random holding.
+    pub fn holding_equity_against_random(&self, hand: [Card; 2]) -> Result<f64, PokerHandError> {
+        Ok(self.equity_against_random(StartingHand::from_cards(hand[0], hand[1])?))
+    }
+
+    /// Set the equity of class `i` against class `j`, and of `j` against `i`.
+    fn set(&mut self, i: usize, j: usize, equity: f64) {
+        self.equity[i * CLASSES + j] = equity;
+        self.equity[j * CLASSES + i] = 1.0 - equity;
+    }
+}
+
+/// Every renaming of the four suits, as the new suit of each old suit.
+fn suit_permutations() -> Vec<[usize; 4]> {
+    let mut permutations = Vec::with_capacity(24);
+    for a in 0..4 {
+        for b in (0..4).filter(|&b| b != a) {
+            for c in (0..4).filter(|&c| c != a && c != b) {
+                permutations.push([a, b, c, 6 - a - b - c]);
+            }
+        }
+    }
+    permutations
+}
+
+/// The card indices of the matchup, with the suits renamed to give the smallest key, so
+/// that matchups which differ only by suits have the same key.
+fn canonical(ours: [Card; 2], theirs: [Card; 2], permutations: &[[usize; 4]]) -> [usize; 4] {
+    permutations
+        .iter()
+        .map(|permutation| {
+            let index =
+                |card: Card| card.index() - card.suit as usize + permutation[card.suit as usize];
+            let (a, b) = (index(ours[0]), index(ours[1]));
+            let (c, d) = (index(theirs[0]), index(theirs[1]));
+            [a.max(b), a.min(b), c.max(d), c.min(d)]
+        })
+        .min()
+        .unwrap()
+}
+
+/// Keys for each rank, from two up, chosen so that the sums of the keys of any seven cards
+/// with at most four of a rank are all different.
+const RANK_KEYS: [usize; 13] = [
+    0, 1, 5, 22, 98, 453, 2031, 8698, 22854, 83661, 262349, 636345, 1479181,
+];
+
+/// The largest sum of seven rank keys: four aces and three kings.
+const MAX_RANK_KEY: usize = 4 * 1479181 + 3 * 636345;
+
+/// A seven card evaluator for dealing out every board, which looks the hands up rather
+/// than evaluating them.
+///
+/// A hand without a flush is valued by the ranks alone, which are summed into a unique key
+/// for a table of every possible set of seven ranks. Seven cards cannot hold a flush and
+/// a full house or four of a kind, so a hand with a flush is valued by the ranks of the
+/// flush suit alone, in a table indexed by a bit mask of the ranks. Both tables are filled
+/// using `evaluate_unchecked`, so the values are the evaluator's.
+struct SevenCardEvaluator {
+    ranks: Vec<u32>,
+    flushes: Vec<u32>,
+}
+
+/// The state of one player's seven cards as the board is dealt.
+#[derive(Clone, Copy)]
+struct Dealt {
+    key: usize,
+    suit_counts: [u8; 4],
+    suit_masks: [u16; 4],
+}
+
+impl Dealt {
+    fn add(mut self, card: Card) -> Dealt {
+        self.key += RANK_KEYS[card.rank as usize - 2];
+        self.suit_counts[card.suit as usize] += 1;
+        self.suit_masks[card.suit as usize] |= 1 << (card.rank as usize - 2);
+        self
+    }
+}
+
+impl SevenCardEvaluator {
+    fn new() -> SevenCardEvaluator {
+        let mut ranks = vec![0; MAX_RANK_KEY + 1];
+        let mut counts = [0usize; 13];
+        fill_rank_table(&mut ranks, &mut counts, 0, 7);
+
+        let mut flushes = vec![0; 1 << 13];
+        for (mask, value) in flushes.iter_mut().enumerate() {
+            if (5..=7).contains(&mask.count_ones()) {
+                let cards: Vec<Card> = (0..13)
+                    .filter(|bit| mask & (1 << bit) != 0)
+                    .map(|bit| Card::new(Ranks::ALL[bit], Suits::Clubs))
+                    .collect();
+                *value = evaluate_unchecked(&cards).value();
+            }
+        }
+        SevenCardEvaluator { ranks, flushes }
+    }
+
+    fn value(&self, dealt: &Dealt) -> u32 {
+        match dealt.suit_counts.iter().position(|&count| count >= 5) {
+            Some(suit) => self.flushes[dealt.suit_masks[suit] as usize],
+            None => self.ranks[dealt.key],
+        }
+    }
+
+    /// The equity of the first holding against the second, given as card indices, over
+    /// every board.
+    fn matchup_equity(&self, key: [usize; 4]) -> f64 {
+        let cards = key.map(|index| Card::from_index(index).unwrap());
+        let unseen: Vec<Card> = (0..52)
+            .filter_map(Card::from_index)
+            .filter(|card| !cards.contains(card))
+            .collect();
+        let empty = Dealt {
+            key: 0,
+            suit_counts: [0; 4],
+            suit_masks: [0; 4],
+        };
+        let ours = empty.add(cards[0]).add(cards[1]);
+        let theirs = empty.add(cards[2]).add(cards[3]);
+        let mut tally = [0u64; 3];
+        self.deal_boards(&unseen, 0, 5, ours, theirs, &mut tally);
+        let total: u64 = tally.iter().sum();
+        (tally[0] as f64 + tally[1] as f64 / 2.0) / total as f64
+    }
+
+    fn deal_boards(
+        &self,
+        unseen: &[Card],
+        from: usize,
+        needed: usize,
+        ours: Dealt,
+        theirs: Dealt,
+        tally: &mut [u64; 3],
+    ) {
+        if needed == 0 {
+            let outcome = match self.value(&ours).cmp(&self.value(&theirs)) {
+                Ordering::Greater => 0,
+                Ordering::Equal => 1,
+                Ordering::Less => 2,
+            };
+            tally[outcome] += 1;
+            return;
+        }
+        for i in from..=unseen.len() - needed {
+            let card = unseen[i];
+            self.deal_boards(
+                unseen,
+                i + 1,
+                needed - 1,
+                ours.add(card),
+                theirs.add(card),
+                tally,
+            );
+        }
+    }
+}
+
+/// Fill the rank table with the value of every set of seven ranks, choosing the count of
+/// each rank in turn from `rank` up.
+fn fill_rank_table(table: &mut [u32], counts: &mut [usize; 13], rank: usize, left: usize) {
+    if rank == 13 {
+        if left == 0 {
+            // Deal the copies of each rank to different suits, round robin, so that no
+            // suit has more than two cards and there is no flush.
+            let mut cards = Vec::with_capacity(7);
+            for (rank, &count) in counts.iter().enumerate() {
+                for _ in 0..count {
+                    cards.push(Card::new(Ranks::ALL[rank], Suits::ALL[cards.len() % 4]));
+                }
+            }
+            let key: usize = (0..13).map(|rank| counts[rank] * RANK_KEYS[rank]).sum();
+            table[key] = evaluate_unchecked(&cards).value();
+        }
+        return;
+    }
+    for count in 0..=left.min(4) {
+        counts[rank] = count;
+        fill_rank_table(table, counts, rank + 1, left - count);
+    }
+    counts[rank] = 0;
+}
+
+/// One for a win, a half for a tie and nothing for a loss.
+fn showdown(ours: [Card; 2], theirs: [Card; 2], board: &[Card]) -> f64 {
+    let mut our_cards = [ours[0]; 7];
+    let mut their_cards = [theirs[0]; 7];
+    our_cards[1] = ours[1];
+    their_cards[1] = theirs[1];
+    our_cards[2..].copy_from_slice(board);
+    their_cards[2..].copy_from_slice(board);
+    match evaluate_unchecked(&our_cards).cmp(&evaluate_unchecked(&their_cards)) {
+        Ordering::Greater => 1.0,
+        Ordering::Equal => 0.5,
+        Ordering::Less => 0.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards::parse_cards;
+
+    #[test]
+    fn test_sampled_table() {
+        // Few samples keep the test quick, so the estimates are rough.
+        let table = PreflopEquity::sampled(30, 4);
+        let hand = |hand: &str| hand.parse::<StartingHand>().unwrap();
+        let equity = table.equity(hand("AA"), hand("KK"));
+        assert!((equity - 0.82).abs() < 0.12);
+        assert_eq!(table.equity(hand("KK"), hand("AA")), 1.0 - equity);
+        assert_eq!(table.equity(hand("T9s"), hand("T9s")), 0.5);
+        assert!(table.equity(hand("AA"), hand("72o")) > 0.75);
+    }
+
+    #[test]
+    fn test_seven_card_lookups_match_the_evaluator() {
+        // heads_up_odds dealing every board gives a win of 0.4601788 and a tie of
+        // 0.0039315 for AhKh against QsQd.
+        let evaluator = SevenCardEvaluator::new();
+        let cards = parse_cards("AH KH QS QD").unwrap();
+        let key = canonical(
+            [cards[0], cards[1]],
+            [cards[2], cards[3]],
+            &suit_permutations(),
+        );
+        let equity = evaluator.matchup_equity(key);
+        assert!((equity - (0.46017880002616357 + 0.00393154486586494 / 2.0)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_canonical_matchups() {
+        let permutations = suit_permutations();
+        let key = |cards: &str| {
+            let cards = parse_cards(cards).unwrap();
+            canonical([cards[0], cards[1]], [cards[2], cards[3]], &permutations)
+        };
+        assert_eq!(permutations.len(), 24);
+        assert_eq!(key("AS KS QH QD"), key("KH AH QC QS"));
+        assert_ne!(key("AS KS QH QD"), key("AS KS QS QD"));
+    }
+
+    #[test]
+    fn test_serialized_table() {
+        let table = PreflopEquity::sampled(5, 1);
+        let bytes = table.to_bytes();
+        assert_eq!(bytes.len(), 5 + 2 * STORED);
+        let read = PreflopEquity::from_bytes(&bytes).unwrap();
+        for i in 0..CLASSES {
+            for j in 0..CLASSES {
+                let difference = read.equity_by_index(i, j) - table.equity_by_index(i, j);
+                assert!(difference.abs() < 1e-4);
+            }
+        }
+        assert!(PreflopEquity::from_bytes(&bytes[1..]).is_err());
+        let mut version = bytes;
+        version[4] = 2;
+        assert!(PreflopEquity::from_bytes(&version).is_err());
+    }
+
+    #[test]
+    fn test_embedded_table() {
+        let table = PreflopEquity::embedded();
+        let hand = |hand: &str| hand.parse::<StartingHand>().unwrap();
+        let cards = |cards: &str| {
+            let cards = parse_cards(cards).unwrap();
+            [cards[0], cards[1]]
+        };
+        assert!((table.equity(hand("AA"), hand("KK")) - 0.8195).abs() < 0.001);
+        assert!((table.equity(hand("22"), hand("AKo")) - 0.5265).abs() < 0.002);
+        assert!((table.equity_against_random(hand("AA")) - 0.8520).abs() < 0.001);
+        assert!((table.equity_against_random(hand("72o")) - 0.3458).abs() < 0.001);
+
+        let equity = table
+            .holding_equity(cards("AH AD"), cards("KS KC"))
+            .unwrap();
+        assert_eq!(equity, table.equity(hand("AA"), hand("KK")));
+        assert!(table
+            .holding_equity(cards("AH AD"), cards("AH KC"))
+            .is_err());
+        let against_random = table.holding_equity_against_random(cards("7C 2D")).unwrap();
+        assert_eq!(against_random, table.equity_against_random(hand("72o")));
+    }
+
+    /// Run with `cargo test --release -- --ignored test_regenerate_embedded_table`.
+    #[test]
+    #[ignore]
+    fn test_regenerate_embedded_table() {
+        assert!(PreflopEquity::exhaustive().to_bytes() == EMBEDDED);
+    }
+}
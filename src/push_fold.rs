@@ -0,0 +1,194 @@
+//! Heads-up push/fold equilibrium ranges for short stacks.
+//!
+//! With a short stack, the small blind's only sensible plays are to move all in or to
+//! fold, and the big blind's are to call or fold. The solver finds the Nash equilibrium of
+//! that game: jamming and calling ranges neither player can profit from changing, given
+//! the effective stack, the small blind and the ante, all measured in big blinds.
+//!
+//! The hands are the 169 starting hand classes, weighted by how many ways each pair of
+//! classes can be dealt, so the card removal effects of a player's own hand are taken into
+//! account. All-in equities come from a `PreflopEquity` table. The equilibrium is found by
+//! fictitious play: each player repeatedly plays the best response to the average of the
+//! other's strategies so far, and the averages converge to the equilibrium.
+
+use crate::preflop::PreflopEquity;
+use crate::starting_hands::{format_range, StartingHand, CLASSES};
+
+/// The stakes of a heads-up push/fold game, in big blinds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PushFoldGame {
+    /// The effective stack at the start of the hand, before posting.
+    pub stack: f64,
+    pub small_blind: f64,
+    /// Posted by each player.
+    pub ante: f64,
+}
+
+impl PushFoldGame {
+    /// A game with the usual half a big blind small blind and no ante.
+    pub fn new(stack: f64) -> PushFoldGame {
+        PushFoldGame {
+            stack,
+            small_blind: 0.5,
+            ante: 0.0,
+        }
+    }
+}
+
+/// The equilibrium strategies, as the frequency with which each class jams or calls.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PushFoldStrategy {
+    /// The small blind's jamming frequency for each class, in grid order.
+    pub jam: Vec<f64>,
+    /// The big blind's calling frequency for each class, in grid order.
+    pub call: Vec<f64>,
+}
+
+impl PushFoldStrategy {
+    /// Whether the small blind jams the hand at least half the time.
+    pub fn jams(&self, hand: StartingHand) -> bool {
+        self.jam[hand.index()] >= 0.5
+    }
+
+    /// Whether the big blind calls with the hand at least half the time.
+    pub fn calls(&self, hand: StartingHand) -> bool {
+        self.call[hand.index()] >= 0.5
+    }
+
+    /// The hands the small blind jams, in range notation.
+    pub fn jam_range(&self) -> String {
+        format_range(
+            &StartingHand::all()
+                .filter(|&hand| self.jams(hand))
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    /// The hands the big blind calls with, in range notation.
+    pub fn call_range(&self) -> String {
+        format_range(
+            &StartingHand::all()
+                .filter(|&hand| self.calls(hand))
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    /// The fraction of all holdings that the small blind jams.
+    pub fn jam_frequency(&self) -> f64 {
+        frequency(&self.jam)
+    }
+
+    /// The fraction of all holdings that the big blind calls with.
+    pub fn call_frequency(&self) -> f64 {
+        frequency(&self.call)
+    }
+}
+
+fn frequency(strategy: &[f64]) -> f64 {
+    StartingHand::all()
+        .map(|hand| strategy[hand.index()] * hand.combo_count() as f64)
+        .sum::<f64>()
+        / 1326.0
+}
+
+/// Solve the game with `iterations` rounds of fictitious play. A few hundred iterations
+/// settle all but the hands closest to the edges of the ranges.
+pub fn solve(game: &PushFoldGame, equity: &PreflopEquity, iterations: usize) -> PushFoldStrategy {
+    let hands: Vec<StartingHand> = StartingHand::all().collect();
+    // The number of ways each pair of classes can be dealt.
+    let mut weights = vec![0.0; CLASSES * CLASSES];
+    for (i, hand) in hands.iter().enumerate() {
+        for (j, other) in hands.iter().enumerate() {
+            weights[i * CLASSES + j] = hand.matchups_against(other) as f64;
+        }
+    }
+    let weight = |i: usize, j: usize| weights[i * CLASSES + j];
+
+    // Results for the small blind, relative to the start of the hand.
+    let stack = game.stack;
+    let fold = -(game.small_blind + game.ante);
+    let steal = 1.0 + game.ante;
+    let called = |equity: f64| 2.0 * stack * equity - stack;
+
+    let mut strategy = PushFoldStrategy {
+        jam: vec![1.0; CLASSES],
+        call: vec![1.0; CLASSES],
+    };
+    for iteration in 1..=iterations {
+        // The small blind's best response to the average calling strategy.
+        let jam: Vec<f64> = (0..CLASSES)
+            .map(|i| {
+                let (mut value, mut total) = (0.0, 0.0);
+                for j in 0..CLASSES {
+                    let call = strategy.call[j];
+                    value += weight(i, j)
+                        * (call * called(equity.equity_by_index(i, j)) + (1.0 - call) * steal);
+                    total += weight(i, j);
+                }
+                if value / total > fold {
+                    1.0
+                } else {
+                    0.0
+                }
+            })
+            .collect();
+        // The big blind's best response to the average jamming strategy. Folding loses
+        // the big blind and ante, and calling wins what the small blind loses.
+        let call: Vec<f64> = (0..CLASSES)
+            .map(|j| {
+                let (mut value, mut total) = (0.0, 0.0);
+                for i in 0..CLASSES {
+                    let w = weight(i, j) * strategy.jam[i];
+                    value -= w * called(equity.equity_by_index(i, j));
+                    total += w;
+                }
+                if total > 0.0 && value / total > -steal {
+                    1.0
+                } else {
+                    0.0
+                }
+            })
+            .collect();
+        let step = 1.0 / (iteration + 1) as f64;
+        for i in 0..CLASSES {
+            strategy.jam[i] += step * (jam[i] - strategy.jam[i]);
+            strategy.call[i] += step * (call[i] - strategy.call[i]);
+        }
+    }
+    strategy
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_equilibrium_ranges() {
+        // Few samples keep the test quick; the ranges near the edges are rough.
+        let equity = PreflopEquity::sampled(30, 2);
+        let hand = |hand: &str| hand.parse::<StartingHand>().unwrap();
+
+        let ten_big_blinds = solve(&PushFoldGame::new(10.0), &equity, 300);
+        for strong in &["AA", "KK", "AKs", "AKo", "TT"] {
+            assert!(ten_big_blinds.jams(hand(strong)));
+            assert!(ten_big_blinds.calls(hand(strong)));
+        }
+        assert!(!ten_big_blinds.calls(hand("72o")));
+        // The small blind jams about 58% of hands and the big blind calls with about 37%.
+        assert!((ten_big_blinds.jam_frequency() - 0.58).abs() < 0.1);
+        assert!((ten_big_blinds.call_frequency() - 0.37).abs() < 0.1);
+        assert!(ten_big_blinds.jam_range().starts_with("22+, "));
+
+        // Very short stacks jam almost anything, and deep stacks much less.
+        let two_big_blinds = solve(&PushFoldGame::new(2.0), &equity, 100);
+        assert!(two_big_blinds.jam_frequency() > 0.8);
+        let deep = solve(&PushFoldGame::new(25.0), &equity, 100);
+        assert!(deep.jam_frequency() < ten_big_blinds.jam_frequency());
+        // Antes make stealing more attractive.
+        let antes = PushFoldGame {
+            ante: 0.25,
+            ..PushFoldGame::new(10.0)
+        };
+        assert!(solve(&antes, &equity, 100).jam_frequency() > ten_big_blinds.jam_frequency());
+    }
+}
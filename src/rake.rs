@@ -0,0 +1,258 @@
+//! The house's rake from cash game pots.
+//!
+//! Card rooms take a percentage of each pot, up to a cap which usually depends on how
+//! many players were dealt in, so that short-handed games pay less. Under the common "no
+//! flop, no drop" rule, hands which end before the flop are not raked at all.
+//!
+//! The rake is worked out on the whole pot, rounded down to whole chips and capped, and is
+//! then split between the main pot and side pots in proportion to their sizes, with any
+//! chips left over by rounding taken from the main pot first. Winners are then awarded the
+//! pots net of their share of the rake. Every `Rake` records how it was worked out, so the
+//! amount taken from a hand can be audited afterwards.
+
+use crate::error::PokerHandError;
+use crate::poker_hand::PokerHand;
+use crate::pot::{build_pots, distribute, Award, Contribution, OddChipRule, Pot};
+
+/// How much rake is taken.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RakeRule {
+    /// The rake, in hundredths of a percent of the pot: 500 is 5%.
+    pub basis_points: u64,
+    /// The caps, as the number of players dealt in at which each cap starts to apply,
+    /// and the cap.
+    pub caps: Vec<(usize, u64)>,
+    /// Whether hands which end before the flop are not raked.
+    pub no_flop_no_drop: bool,
+}
+
+impl RakeRule {
+    /// An uncapped rake of the given basis points, with no flop, no drop.
+    pub fn new(basis_points: u64) -> Result<RakeRule, PokerHandError> {
+        if basis_points > 10_000 {
+            return Err(PokerHandError::new("The rake cannot be more than the pot"));
+        }
+        Ok(RakeRule {
+            basis_points,
+            caps: Vec::new(),
+            no_flop_no_drop: true,
+        })
+    }
+
+    /// Cap the rake at `cap` chips when at least `players` players are dealt in, until a
+    /// cap for more players applies.
+    pub fn with_cap(mut self, players: usize, cap: u64) -> RakeRule {
+        self.caps.retain(|&(from, _)| from != players);
+        self.caps.push((players, cap));
+        self.caps.sort_unstable();
+        self
+    }
+
+    /// The cap for a hand with this many players dealt in, if there is one.
+    pub fn cap(&self, players: usize) -> Option<u64> {
+        self.caps
+            .iter()
+            .rev()
+            .find(|&&(from, _)| from <= players)
+            .map(|&(_, cap)| cap)
+    }
+
+    /// Work out the rake from the pots of a hand.
+    pub fn take(&self, pots: &[Pot], players: usize, saw_flop: bool) -> Rake {
+        let pot: u64 = pots.iter().map(|pot| pot.amount).sum();
+        // The fields are public, so a rule built directly may ask for more than the pot.
+        let uncapped = (pot as u128 * self.basis_points as u128 / 10_000).min(pot as u128) as u64;
+        let cap = self.cap(players);
+        let waived = self.no_flop_no_drop && !saw_flop;
+        let total = if waived {
+            0
+        } else {
+            cap.map_or(uncapped, |cap| uncapped.min(cap))
+        };
+
+        let mut by_pot: Vec<u64> = pots
+            .iter()
+            .map(|side| {
+                if pot == 0 {
+                    0
+                } else {
+                    (total as u128 * side.amount as u128 / pot as u128) as u64
+                }
+            })
+            .collect();
+        let mut left_over = total - by_pot.iter().sum::<u64>();
+        for (rake, side) in by_pot.iter_mut().zip(pots) {
+            let extra = left_over.min(side.amount - *rake);
+            *rake += extra;
+            left_over -= extra;
+        }
+        Rake {
+            pot,
+            players,
+            uncapped,
+            cap,
+            waived,
+            by_pot,
+        }
+    }
+}
+
+/// The rake taken from a hand, and how it was worked out.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Rake {
+    /// The whole pot before the rake.
+    pub pot: u64,
+    /// The number of players dealt in, which chose the cap.
+    pub players: usize,
+    /// The rake before the cap.
+    pub uncapped: u64,
+    /// The cap which applied, if any.
+    pub cap: Option<u64>,
+    /// True if the hand was not raked because it ended before the flop.
+    pub waived: bool,
+    /// The rake from each pot, main pot first.
+    pub by_pot: Vec<u64>,
+}
+
+impl Rake {
+    /// The chips taken.
+    pub fn total(&self) -> u64 {
+        self.by_pot.iter().sum()
+    }
+
+    /// The pots with their share of the rake taken out.
+    pub fn apply(&self, pots: &[Pot]) -> Vec<Pot> {
+        pots.iter()
+            .zip(self.by_pot.iter().chain(std::iter::repeat(&0)))
+            .map(|(pot, rake)| Pot {
+                amount: pot.amount - rake.min(&pot.amount),
+                eligible: pot.eligible.clone(),
+            })
+            .collect()
+    }
+}
+
+/// Build the pots from the contributions, take the rake, and award what is left to the
+/// shown hands.
+///
+/// The awards and the rake add up to the chips contributed.
+pub fn settle_with_rake(
+    contributions: &[Contribution],
+    hands: &[(usize, PokerHand)],
+    odd_chip_rule: &OddChipRule,
+    rake_rule: &RakeRule,
+    saw_flop: bool,
+) -> Result<(Vec<Award>, Rake), PokerHandError> {
+    let pots = build_pots(contributions);
+    let rake = rake_rule.take(&pots, contributions.len(), saw_flop);
+    let awards = distribute(&rake.apply(&pots), hands, odd_chip_rule)?;
+    Ok((awards, rake))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pot::totals;
+
+    fn pot(amount: u64, eligible: &[usize]) -> Pot {
+        Pot {
+            amount,
+            eligible: eligible.to_vec(),
+        }
+    }
+
+    fn rule() -> RakeRule {
+        RakeRule::new(500)
+            .unwrap()
+            .with_cap(2, 10)
+            .with_cap(3, 20)
+            .with_cap(5, 30)
+    }
+
+    #[test]
+    fn test_percentage_and_caps_by_players() {
+        let rule = rule();
+        assert_eq!(rule.cap(1), None);
+        assert_eq!(rule.cap(2), Some(10));
+        assert_eq!(rule.cap(4), Some(20));
+        assert_eq!(rule.cap(9), Some(30));
+
+        // 5% of 150 is 7.5, rounded down.
+        let rake = rule.take(&[pot(150, &[0, 1])], 2, true);
+        assert_eq!(rake.total(), 7);
+        // 5% of 1000 is 50, capped at 10 heads up and 30 at a full table.
+        assert_eq!(rule.take(&[pot(1000, &[0, 1])], 2, true).total(), 10);
+        let rake = rule.take(&[pot(1000, &[0, 1])], 9, true);
+        assert_eq!((rake.uncapped, rake.cap, rake.total()), (50, Some(30), 30));
+        assert!(RakeRule::new(10_001).is_err());
+
+        // A rule built directly may skip the check, but never takes more than the pot.
+        let greedy = RakeRule {
+            basis_points: 20_000,
+            caps: Vec::new(),
+            no_flop_no_drop: false,
+        };
+        let pots = [pot(100, &[0, 1]), pot(50, &[1])];
+        let rake = greedy.take(&pots, 2, true);
+        assert_eq!((rake.uncapped, rake.total()), (150, 150));
+        assert_eq!(rake.by_pot, vec![100, 50]);
+    }
+
+    #[test]
+    fn test_no_flop_no_drop() {
+        let rake = rule().take(&[pot(300, &[0, 1])], 6, false);
+        assert!(rake.waived);
+        assert_eq!(rake.total(), 0);
+        let always = RakeRule {
+            no_flop_no_drop: false,
+            ..rule()
+        };
+        assert_eq!(always.take(&[pot(300, &[0, 1])], 6, false).total(), 15);
+    }
+
+    #[test]
+    fn test_rake_split_between_side_pots() {
+        // A rake of 30 from pots of 600, 300 and 100, in proportion.
+        let pots = [pot(600, &[0, 1, 2]), pot(300, &[1, 2]), pot(100, &[2])];
+        let rake = rule().take(&pots, 6, true);
+        assert_eq!(rake.by_pot, vec![18, 9, 3]);
+        let raked = rake.apply(&pots);
+        let amounts: Vec<u64> = raked.iter().map(|pot| pot.amount).collect();
+        assert_eq!(amounts, vec![582, 291, 97]);
+
+        // A rake of 7 from pots of 100 and 50: 4.67 and 2.33 round down to 4 and 2, and
+        // the chip left over comes from the main pot.
+        let rake = RakeRule::new(500)
+            .unwrap()
+            .take(&[pot(100, &[0, 1]), pot(50, &[1])], 3, true);
+        assert_eq!(rake.by_pot, vec![5, 2]);
+    }
+
+    #[test]
+    fn test_settle_with_rake() {
+        let contributions = [
+            Contribution::new(0, 100, false),
+            Contribution::new(1, 300, false),
+            Contribution::new(2, 300, false),
+        ];
+        let hands = [
+            (0, PokerHand::new("AH AD AS KC KD").unwrap()),
+            (1, PokerHand::new("2H 3D 4S 5C 7D").unwrap()),
+            (2, PokerHand::new("QH QD 4H 5H 7S").unwrap()),
+        ];
+        let rule = RakeRule::new(500).unwrap().with_cap(2, 20);
+        let (awards, rake) = settle_with_rake(
+            &contributions,
+            &hands,
+            &OddChipRule::HighCardBySuit,
+            &rule,
+            true,
+        )
+        .unwrap();
+        // A rake of 20 from pots of 300 and 400.
+        assert_eq!(rake.by_pot, vec![9, 11]);
+        assert_eq!(totals(&awards), vec![(0, 291), (2, 389)]);
+        let awarded: u64 = awards.iter().map(|award| award.amount).sum();
+        assert_eq!(awarded + rake.total(), rake.pot);
+    }
+}
@@ -0,0 +1,89 @@
+//! A small seeded random number generator.
+//!
+//! Sampling and dealing must be reproducible from a seed, on every platform and across
+//! releases, so the crate carries its own generator rather than depending on one whose
+//! output may change between versions. This is SplitMix64, which is fast and passes the
+//! usual statistical test suites; it is not suitable for cryptographic use.
+
+#[derive(Debug, Clone)]
+pub(crate) struct SeededRng {
+    state: u64,
+}
+
+impl SeededRng {
+    pub(crate) fn new(seed: u64) -> SeededRng {
+        SeededRng { state: seed }
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// A uniformly distributed number in the range [0, 1).
+    pub(crate) fn next_f64(&mut self) -> f64 {
+        // The top 53 bits fill the mantissa exactly.
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// A uniformly distributed number in the range 0..n.
+    pub(crate) fn below(&mut self, n: usize) -> usize {
+        assert!(n > 0, "Cannot choose from an empty range");
+        // Reject the top partial block of values to avoid modulo bias.
+        let n = n as u64;
+        let limit = u64::MAX - u64::MAX % n;
+        loop {
+            let value = self.next_u64();
+            if value < limit {
+                return (value % n) as usize;
+            }
+        }
+    }
+
+    /// Move a uniformly random selection of `count` items, in random order, to the front
+    /// of the slice (a partial Fisher-Yates shuffle).
+    pub(crate) fn shuffle_prefix<T>(&mut self, items: &mut [T], count: usize) {
+        for i in 0..count.min(items.len()) {
+            let j = i + self.below(items.len() - i);
+            items.swap(i, j);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_seed_same_sequence() {
+        let mut a = SeededRng::new(42);
+        let mut b = SeededRng::new(42);
+        let mut c = SeededRng::new(43);
+        let first = a.next_u64();
+        assert_eq!(first, b.next_u64());
+        assert_ne!(first, c.next_u64());
+    }
+
+    #[test]
+    fn test_below_range() {
+        let mut rng = SeededRng::new(1);
+        let mut counts = [0; 3];
+        for _ in 0..3000 {
+            counts[rng.below(3)] += 1;
+        }
+        assert!(counts.iter().all(|&count| count > 900));
+    }
+
+    #[test]
+    fn test_shuffle_is_a_permutation() {
+        let mut rng = SeededRng::new(5);
+        let mut items: Vec<u32> = (0..52).collect();
+        rng.shuffle_prefix(&mut items, 52);
+        assert_ne!(items, (0..52).collect::<Vec<u32>>());
+        items.sort();
+        assert_eq!(items, (0..52).collect::<Vec<u32>>());
+    }
+}
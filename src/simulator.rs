@@ -0,0 +1,421 @@
+//! Bots playing each other at a simulated table.
+//!
+//! An `Agent` is a bot strategy: each time it is its seat's turn it is shown what a player
+//! at the table could see, an `Observation`, and chooses an action. The `Simulator` seats
+//! a list of agents and plays hand after hand between them on a `TableHand`, dealing from
+//! a seeded shuffle so that a run can be repeated exactly, and settles showdowns with the
+//! table's hand comparison.
+//!
+//! Every hand starts with the same stack for every player, and the button moves one seat
+//! each hand so that every agent plays every position equally often. The results are
+//! reported as each agent's win rate in big blinds per hundred hands (bb/100), with a
+//! confidence interval from the variance of its results per hand. Poker is a high variance
+//! game, so telling apart agents which are close in strength takes a great many hands.
+
+use crate::cards::Card;
+use crate::error::TableError;
+use crate::preflop::PreflopEquity;
+use crate::rng::SeededRng;
+use crate::strength::{hand_strength, Enumeration};
+use crate::table::{Action, Event, LegalActions, Street, TableConfig, TableHand};
+
+/// What a player can see when it is their turn: everything but the other players' hole
+/// cards and the cards still to come.
+#[derive(Debug, Clone)]
+pub struct Observation<'a> {
+    pub config: &'a TableConfig,
+    pub seat: usize,
+    pub button: usize,
+    pub hole_cards: [Card; 2],
+    pub street: Street,
+    pub board: &'a [Card],
+    /// Each player's stack, by seat.
+    pub stacks: Vec<u64>,
+    /// Each player's chips put in on the current street, by seat.
+    pub street_bets: Vec<u64>,
+    /// Whether each player has folded, by seat.
+    pub folded: Vec<bool>,
+    pub pot: u64,
+    pub legal: LegalActions,
+    /// Everything that has happened in the hand so far.
+    pub events: &'a [Event],
+}
+
+/// The end of a hand, as the players see it.
+#[derive(Debug, Clone)]
+pub struct HandSummary<'a> {
+    pub board: &'a [Card],
+    pub events: &'a [Event],
+    /// The hole cards shown down, by seat.
+    pub shown: Vec<(usize, [Card; 2])>,
+    /// Each player's chips won or lost, by seat.
+    pub net: Vec<i64>,
+}
+
+/// A bot strategy.
+pub trait Agent {
+    /// Choose an action for the observed seat. An action the table rejects counts against
+    /// the agent as an illegal action, and is replaced by a check or, facing a bet, a fold.
+    fn act(&mut self, observation: &Observation) -> Action;
+
+    /// Told the outcome of every hand, from the agent's seat, for agents which learn.
+    fn hand_complete(&mut self, _seat: usize, _summary: &HandSummary) {}
+}
+
+/// Checks when it can and calls any bet.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CallingStation;
+
+impl Agent for CallingStation {
+    fn act(&mut self, observation: &Observation) -> Action {
+        match observation.legal.call {
+            Some(_) => Action::Call,
+            None => Action::Check,
+        }
+    }
+}
+
+/// Plays strong hands aggressively and folds weak ones.
+///
+/// Preflop a hand's strength is its equity against a random hand from the embedded
+/// `PreflopEquity` table, and after the flop it is its `hand_strength` against one random
+/// opponent. The agent makes the minimum bet or raise with a strength of at least `raise`,
+/// calls with at least `call`, and otherwise checks or folds.
+#[derive(Debug, Clone, Copy)]
+pub struct ThresholdAgent {
+    pub raise: f64,
+    pub call: f64,
+}
+
+impl Agent for ThresholdAgent {
+    fn act(&mut self, observation: &Observation) -> Action {
+        let hole = observation.hole_cards;
+        let strength = if observation.board.is_empty() {
+            PreflopEquity::embedded().holding_equity_against_random(hole)
+        } else {
+            hand_strength(&hole, observation.board, 1, Enumeration::Exhaustive)
+        }
+        .unwrap_or(0.0);
+        let legal = &observation.legal;
+        match legal.raise {
+            Some(range) if strength >= self.raise => {
+                if legal.facing_bet {
+                    Action::Raise(range.min_to)
+                } else {
+                    Action::Bet(range.min_to)
+                }
+            }
+            _ => match legal.call {
+                None => Action::Check,
+                Some(_) if strength >= self.call => Action::Call,
+                Some(_) => Action::Fold,
+            },
+        }
+    }
+}
+
+/// One agent's results from a simulation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AgentResult {
+    pub hands: usize,
+    /// The chips won, or lost if negative.
+    pub net: i64,
+    /// The actions the table rejected.
+    pub illegal_actions: usize,
+    big_blind: u64,
+    sum_of_squares: f64,
+}
+
+impl AgentResult {
+    fn new(big_blind: u64) -> AgentResult {
+        AgentResult {
+            hands: 0,
+            net: 0,
+            illegal_actions: 0,
+            big_blind,
+            sum_of_squares: 0.0,
+        }
+    }
+
+    fn record(&mut self, net: i64) {
+        self.hands += 1;
+        self.net += net;
+        let big_blinds = net as f64 / self.big_blind as f64;
+        self.sum_of_squares += big_blinds * big_blinds;
+    }
+
+    /// The win rate in big blinds per hundred hands.
+    pub fn bb_per_100(&self) -> f64 {
+        if self.hands == 0 {
+            return 0.0;
+        }
+        100.0 * self.net as f64 / self.big_blind as f64 / self.hands as f64
+    }
+
+    /// The standard deviation of the results, in big blinds per hand.
+    pub fn standard_deviation(&self) -> f64 {
+        if self.hands < 2 {
+            return 0.0;
+        }
+        let n = self.hands as f64;
+        let mean = self.net as f64 / self.big_blind as f64 / n;
+        ((self.sum_of_squares - n * mean * mean) / (n - 1.0))
+            .max(0.0)
+            .sqrt()
+    }
+
+    /// The standard error of the win rate, in big blinds per hundred hands.
+    pub fn standard_error(&self) -> f64 {
+        if self.hands == 0 {
+            return 0.0;
+        }
+        100.0 * self.standard_deviation() / (self.hands as f64).sqrt()
+    }
+
+    /// The confidence interval of the win rate in big blinds per hundred hands, `z`
+    /// standard errors either side: 1.96 for 95% confidence.
+    pub fn confidence_interval(&self, z: f64) -> (f64, f64) {
+        let margin = z * self.standard_error();
+        (self.bb_per_100() - margin, self.bb_per_100() + margin)
+    }
+}
+
+/// A table of agents playing hand after hand.
+pub struct Simulator {
+    config: TableConfig,
+    stack: u64,
+    agents: Vec<Box<dyn Agent>>,
+    rng: SeededRng,
+    button: usize,
+    results: Vec<AgentResult>,
+}
+
+impl Simulator {
+    /// Seat the agents, in order, each with `stack` chips at the start of every hand.
+    pub fn new(
+        config: &TableConfig,
+        stack: u64,
+        agents: Vec<Box<dyn Agent>>,
+        seed: u64,
+    ) -> Result<Simulator, TableError> {
+        if agents.len() < 2 || 2 * agents.len() + 5 > 52 {
+            return Err(TableError::InvalidSetup(
+                "A simulation needs 2 to 23 agents".to_string(),
+            ));
+        }
+        if stack == 0 {
+            return Err(TableError::InvalidSetup(
+                "Stacks must not be empty".to_string(),
+            ));
+        }
+        let results = vec![AgentResult::new(config.big_blind); agents.len()];
+        Ok(Simulator {
+            config: config.clone(),
+            stack,
+            agents,
+            rng: SeededRng::new(seed),
+            button: 0,
+            results,
+        })
+    }
+
+    /// Play more hands.
+    pub fn run(&mut self, hands: usize) -> Result<(), TableError> {
+        for _ in 0..hands {
+            self.play_hand()?;
+        }
+        Ok(())
+    }
+
+    /// The results so far, by seat.
+    pub fn results(&self) -> &[AgentResult] {
+        &self.results
+    }
+
+    fn play_hand(&mut self) -> Result<(), TableError> {
+        let players = self.agents.len();
+        let mut deck: Vec<Card> = (0..52).filter_map(Card::from_index).collect();
+        self.rng.shuffle_prefix(&mut deck, 2 * players + 5);
+        let hole_cards: Vec<[Card; 2]> = (0..players)
+            .map(|seat| [deck[2 * seat], deck[2 * seat + 1]])
+            .collect();
+        let mut board = [deck[0]; 5];
+        board.copy_from_slice(&deck[2 * players..2 * players + 5]);
+        let stacks = vec![self.stack; players];
+        let mut hand = TableHand::new(&self.config, &stacks, self.button, &hole_cards, board)?;
+
+        let mut agents: Vec<&mut dyn Agent> = self
+            .agents
+            .iter_mut()
+            .map(|agent| agent.as_mut() as &mut dyn Agent)
+            .collect();
+        let illegal_actions = play_out(&mut hand, &hole_cards, &mut agents)?;
+        for (seat, result) in self.results.iter_mut().enumerate() {
+            result.illegal_actions += illegal_actions[seat];
+            result.record(hand.seats()[seat].stack as i64 - self.stack as i64);
+        }
+        self.button = (self.button + 1) % players;
+        Ok(())
+    }
+}
+
+/// Play a hand to the end, asking the agent in each seat for its actions, and then tell
+/// the agents the outcome. Returns the number of illegal actions by each seat.
+pub(crate) fn play_out(
+    hand: &mut TableHand,
+    hole_cards: &[[Card; 2]],
+    agents: &mut [&mut dyn Agent],
+) -> Result<Vec<usize>, TableError> {
+    let mut illegal_actions = vec![0; agents.len()];
+    while let (Some(seat), Some(legal)) = (hand.to_act(), hand.legal_actions()) {
+        let action = {
+            let seats = hand.seats();
+            let observation = Observation {
+                config: hand.config(),
+                seat,
+                button: hand.button(),
+                hole_cards: hole_cards[seat],
+                street: hand.street(),
+                board: hand.board(),
+                stacks: seats.iter().map(|s| s.stack).collect(),
+                street_bets: seats.iter().map(|s| s.street_bet).collect(),
+                folded: seats.iter().map(|s| s.folded).collect(),
+                pot: hand.pot(),
+                legal,
+                events: hand.events(),
+            };
+            agents[seat].act(&observation)
+        };
+        if hand.act(seat, action).is_err() {
+            illegal_actions[seat] += 1;
+            let fallback = match legal.call {
+                Some(_) => Action::Fold,
+                None => Action::Check,
+            };
+            hand.act(seat, fallback)?;
+        }
+    }
+
+    let net: Vec<i64> = hand
+        .seats()
+        .iter()
+        .zip(hand.starting_stacks())
+        .map(|(seat, &stack)| seat.stack as i64 - stack as i64)
+        .collect();
+    let shown = hand
+        .result()
+        .map(|result| {
+            result
+                .shown
+                .iter()
+                .map(|&(seat, _)| (seat, hole_cards[seat]))
+                .collect()
+        })
+        .unwrap_or_default();
+    let summary = HandSummary {
+        board: hand.board(),
+        events: hand.events(),
+        shown,
+        net,
+    };
+    for (seat, agent) in agents.iter_mut().enumerate() {
+        agent.hand_complete(seat, &summary);
+    }
+    Ok(illegal_actions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Always bets nothing, which the table rejects.
+    struct Broken;
+
+    impl Agent for Broken {
+        fn act(&mut self, _observation: &Observation) -> Action {
+            Action::Bet(0)
+        }
+    }
+
+    /// Plays like a calling station, checking what it is shown.
+    struct Auditor;
+
+    impl Agent for Auditor {
+        fn act(&mut self, observation: &Observation) -> Action {
+            assert_eq!(observation.legal.seat, observation.seat);
+            assert_eq!(observation.board.len(), observation.street.board_cards());
+            CallingStation.act(observation)
+        }
+
+        fn hand_complete(&mut self, _seat: usize, summary: &HandSummary) {
+            assert_eq!(summary.net.iter().sum::<i64>(), 0);
+        }
+    }
+
+    #[test]
+    fn test_zero_sum_results() {
+        let agents: Vec<Box<dyn Agent>> = vec![
+            Box::new(CallingStation),
+            Box::new(Auditor),
+            Box::new(ThresholdAgent {
+                raise: 0.8,
+                call: 0.5,
+            }),
+        ];
+        let mut simulator = Simulator::new(&TableConfig::no_limit(1, 2), 200, agents, 3).unwrap();
+        simulator.run(200).unwrap();
+        let results = simulator.results();
+        assert!(results.iter().all(|result| result.hands == 200));
+        assert_eq!(results.iter().map(|result| result.net).sum::<i64>(), 0);
+        assert!(results.iter().all(|result| result.illegal_actions == 0));
+        let (low, high) = results[0].confidence_interval(1.96);
+        assert!(low < results[0].bb_per_100() && results[0].bb_per_100() < high);
+    }
+
+    #[test]
+    fn test_seeded_runs_repeat() {
+        let run = |seed| {
+            let agents: Vec<Box<dyn Agent>> =
+                vec![Box::new(CallingStation), Box::new(CallingStation)];
+            let mut simulator =
+                Simulator::new(&TableConfig::no_limit(1, 2), 100, agents, seed).unwrap();
+            simulator.run(100).unwrap();
+            simulator.results()[0].net
+        };
+        assert_eq!(run(5), run(5));
+        assert_ne!(run(5), run(6));
+    }
+
+    #[test]
+    fn test_illegal_actions_fold_or_check() {
+        let agents: Vec<Box<dyn Agent>> = vec![Box::new(Broken), Box::new(CallingStation)];
+        let mut simulator = Simulator::new(&TableConfig::no_limit(1, 2), 100, agents, 1).unwrap();
+        simulator.run(10).unwrap();
+        let results = simulator.results();
+        assert!(results[0].illegal_actions >= 10);
+        assert_eq!(results[1].illegal_actions, 0);
+        // The broken agent folds its small blinds and checks its big blinds down.
+        assert!(results[0].net < 0);
+    }
+
+    #[test]
+    fn test_setup_errors() {
+        let config = TableConfig::no_limit(1, 2);
+        let one: Vec<Box<dyn Agent>> = vec![Box::new(CallingStation)];
+        assert!(Simulator::new(&config, 100, one, 1).is_err());
+        let two: Vec<Box<dyn Agent>> = vec![Box::new(CallingStation), Box::new(CallingStation)];
+        assert!(Simulator::new(&config, 0, two, 1).is_err());
+    }
+
+    #[test]
+    fn test_win_rate_statistics() {
+        let mut result = AgentResult::new(2);
+        for net in &[4, -2, 4, -2] {
+            result.record(*net);
+        }
+        // Half a big blind a hand on average, with a standard deviation of √3 big blinds.
+        assert_eq!(result.bb_per_100(), 50.0);
+        assert!((result.standard_deviation() - 3f64.sqrt()).abs() < 1e-12);
+        assert!((result.standard_error() - 100.0 * 3f64.sqrt() / 2.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,375 @@
+//! The 169 classes of Texas Hold'em starting hands, and range notation.
+//!
+//! Before the flop the suits only matter in whether the two hole cards share one, so the
+//! 1326 possible holdings fall into 169 classes: 13 pairs such as "QQ", 78 suited hands
+//! such as "AKs" and 78 offsuit hands such as "AKo".
+//!
+//! Classes are numbered 0..169 by their place in the usual 13 by 13 grid, with aces in the
+//! first row and column: pairs on the diagonal, suited hands above it and offsuit hands
+//! below it, so "AA" is 0, "AKs" is 1, "AKo" is 13 and "22" is 168.
+//!
+//! Ranges are written in the notation used by most poker tools, as a comma separated list
+//! of classes and runs of classes: "TT+" is every pair from tens up, "A2s+" is every suited
+//! ace, "K9o-K6o" is the offsuit kings from nine down to six, and "AK" is both "AKs" and
+//! "AKo".
+
+use crate::cards::{Card, Ranks, Suits};
+use crate::error::PokerHandError;
+use std::fmt;
+use std::str::FromStr;
+
+/// The number of starting hand classes.
+pub const CLASSES: usize = 169;
+
+/// A class of starting hands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StartingHand {
+    /// The higher rank, or the rank of a pair.
+    pub high: Ranks,
+    pub low: Ranks,
+    /// Whether the cards share a suit. Always false for a pair.
+    pub suited: bool,
+}
+
+/// The row or column of a rank in the grid, with aces first.
+fn grid_position(rank: Ranks) -> usize {
+    Ranks::Ace as usize - rank as usize
+}
+
+fn rank_char(rank: Ranks) -> char {
+    Card::new(rank, Suits::Clubs)
+        .to_short_string()
+        .chars()
+        .next()
+        .unwrap()
+}
+
+fn parse_rank(rank: char) -> Result<Ranks, PokerHandError> {
+    Card::from_short_str(&format!("{}c", rank))
+        .map(|card| card.rank)
+        .map_err(|_| PokerHandError::new("Invalid starting hand rank"))
+}
+
+impl StartingHand {
+    /// The class of two hole cards.
+    pub fn from_cards(first: Card, second: Card) -> Result<StartingHand, PokerHandError> {
+        if first == second {
+            return Err(PokerHandError::new("Duplicate cards in hand"));
+        }
+        Ok(StartingHand {
+            high: first.rank.max(second.rank),
+            low: first.rank.min(second.rank),
+            suited: first.suit == second.suit,
+        })
+    }
+
+    /// The class with the given number, in grid order.
+    pub fn from_index(index: usize) -> Option<StartingHand> {
+        if index >= CLASSES {
+            return None;
+        }
+        let (row, column) = (index / 13, index % 13);
+        let rank = |position: usize| Ranks::ALL[12 - position];
+        Some(StartingHand {
+            high: rank(row.min(column)),
+            low: rank(row.max(column)),
+            suited: row < column,
+        })
+    }
+
+    /// The number of the class in grid order.
+    pub fn index(&self) -> usize {
+        let (high, low) = (grid_position(self.high), grid_position(self.low));
+        if self.suited {
+            high * 13 + low
+        } else {
+            low * 13 + high
+        }
+    }
+
+    /// All 169 classes in grid order.
+    pub fn all() -> impl Iterator<Item = StartingHand> {
+        (0..CLASSES).filter_map(StartingHand::from_index)
+    }
+
+    pub fn is_pair(&self) -> bool {
+        self.high == self.low
+    }
+
+    /// Every holding in the class: 6 for a pair, 4 suited or 12 offsuit.
+    pub fn combos(&self) -> Vec<[Card; 2]> {
+        let mut combos = Vec::with_capacity(12);
+        for &first in &Suits::ALL {
+            for &second in &Suits::ALL {
+                let keep = if self.is_pair() {
+                    first < second
+                } else {
+                    (first == second) == self.suited
+                };
+                if keep {
+                    combos.push([Card::new(self.high, first), Card::new(self.low, second)]);
+                }
+            }
+        }
+        combos
+    }
+
+    /// The number of ways the two classes can be dealt to two players, leaving out the
+    /// holdings that share a card.
+    pub fn matchups_against(&self, other: &StartingHand) -> usize {
+        let others = other.combos();
+        self.combos()
+            .iter()
+            .map(|combo| {
+                others
+                    .iter()
+                    .filter(|theirs| !theirs.iter().any(|card| combo.contains(card)))
+                    .count()
+            })
+            .sum()
+    }
+
+    /// The number of holdings in the class.
+    pub fn combo_count(&self) -> usize {
+        match (self.is_pair(), self.suited) {
+            (true, _) => 6,
+            (false, true) => 4,
+            (false, false) => 12,
+        }
+    }
+}
+
+impl fmt::Display for StartingHand {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", rank_char(self.high), rank_char(self.low))?;
+        match (self.is_pair(), self.suited) {
+            (true, _) => Ok(()),
+            (false, true) => write!(f, "s"),
+            (false, false) => write!(f, "o"),
+        }
+    }
+}
+
+/// Parse a class such as "QQ", "AKs" or "T9o".
+impl FromStr for StartingHand {
+    type Err = PokerHandError;
+
+    fn from_str(hand: &str) -> Result<Self, Self::Err> {
+        let chars: Vec<char> = hand.chars().collect();
+        let invalid = || PokerHandError::new("Invalid starting hand");
+        if chars.len() < 2 || chars.len() > 3 {
+            return Err(invalid());
+        }
+        let (first, second) = (parse_rank(chars[0])?, parse_rank(chars[1])?);
+        let suited = match (chars.get(2), first == second) {
+            (None, true) => false,
+            (Some('s'), false) => true,
+            (Some('o'), false) => false,
+            _ => return Err(invalid()),
+        };
+        Ok(StartingHand {
+            high: first.max(second),
+            low: first.min(second),
+            suited,
+        })
+    }
+}
+
+/// Write a set of classes in range notation, pairs first, then suited and offsuit hands,
+/// highest first.
+pub fn format_range(hands: &[StartingHand]) -> String {
+    let mut included = [false; CLASSES];
+    for hand in hands {
+        included[hand.index()] = true;
+    }
+    let has = |high: Ranks, low: Ranks, suited: bool| {
+        included[StartingHand { high, low, suited }.index()]
+    };
+    let mut parts = Vec::new();
+
+    // Runs of pairs, where a run up to aces is written with a plus.
+    let pairs: Vec<Ranks> = Ranks::ALL
+        .iter()
+        .rev()
+        .copied()
+        .filter(|&rank| has(rank, rank, false))
+        .collect();
+    for run in runs(&pairs) {
+        let (top, bottom) = (run[0], run[run.len() - 1]);
+        let pair = |rank: Ranks| StartingHand {
+            high: rank,
+            low: rank,
+            suited: false,
+        };
+        parts.push(if top == Ranks::Ace && run.len() > 1 {
+            format!("{}+", pair(bottom))
+        } else if run.len() == 1 {
+            pair(top).to_string()
+        } else {
+            format!("{}-{}", pair(top), pair(bottom))
+        });
+    }
+
+    // Runs of kickers under each high card, where a run up to the highest kicker is
+    // written with a plus.
+    for &suited in &[true, false] {
+        for &high in Ranks::ALL.iter().rev() {
+            let kickers: Vec<Ranks> = Ranks::ALL
+                .iter()
+                .rev()
+                .copied()
+                .filter(|&low| low < high && has(high, low, suited))
+                .collect();
+            for run in runs(&kickers) {
+                let hand = |low: Ranks| StartingHand { high, low, suited };
+                let (top, bottom) = (run[0], run[run.len() - 1]);
+                parts.push(if top as u8 + 1 == high as u8 && run.len() > 1 {
+                    format!("{}+", hand(bottom))
+                } else if run.len() == 1 {
+                    hand(top).to_string()
+                } else {
+                    format!("{}-{}", hand(top), hand(bottom))
+                });
+            }
+        }
+    }
+    parts.join(", ")
+}
+
+/// Split ranks in descending order into runs of consecutive ranks.
+fn runs(ranks: &[Ranks]) -> Vec<Vec<Ranks>> {
+    let mut runs: Vec<Vec<Ranks>> = Vec::new();
+    for &rank in ranks {
+        match runs.last_mut() {
+            Some(run) if run[run.len() - 1] as u8 == rank as u8 + 1 => run.push(rank),
+            _ => runs.push(vec![rank]),
+        }
+    }
+    runs
+}
+
+/// Parse a range such as "TT+, A2s+, KQo, K9s-K6s" into its classes, in grid order.
+///
+/// A class without "s" or "o", such as "AK", means both the suited and offsuit hands.
+pub fn parse_range(range: &str) -> Result<Vec<StartingHand>, PokerHandError> {
+    let mut included = [false; CLASSES];
+    for part in range
+        .split(',')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+    {
+        let (part, plus) = match part.strip_suffix('+') {
+            Some(part) => (part, true),
+            None => (part, false),
+        };
+        let (first, last) = part.split_once('-').unwrap_or((part, part));
+        for (first, last) in expand_suits(first)?.into_iter().zip(expand_suits(last)?) {
+            let valid = first.is_pair() == last.is_pair()
+                && first.suited == last.suited
+                && (first.is_pair() || first.high == last.high);
+            if !valid {
+                return Err(PokerHandError::new("Invalid range"));
+            }
+            // The ranks that vary: the pair rank, or the kicker.
+            let (mut from, mut to) = if first.is_pair() {
+                (first.high, last.high)
+            } else {
+                (first.low, last.low)
+            };
+            if from > to {
+                std::mem::swap(&mut from, &mut to);
+            }
+            if plus {
+                to = if first.is_pair() {
+                    Ranks::Ace
+                } else {
+                    Ranks::ALL[first.high as usize - 3]
+                };
+            }
+            for &rank in Ranks::ALL
+                .iter()
+                .filter(|&&rank| rank >= from && rank <= to)
+            {
+                let hand = if first.is_pair() {
+                    StartingHand {
+                        high: rank,
+                        low: rank,
+                        suited: false,
+                    }
+                } else {
+                    StartingHand { low: rank, ..first }
+                };
+                included[hand.index()] = true;
+            }
+        }
+    }
+    Ok((0..CLASSES)
+        .filter(|&index| included[index])
+        .filter_map(StartingHand::from_index)
+        .collect())
+}
+
+/// A class, or both the suited and offsuit classes if neither is given.
+fn expand_suits(hand: &str) -> Result<Vec<StartingHand>, PokerHandError> {
+    let mut chars = hand.chars();
+    let both = hand.len() == 2 && chars.next() != chars.next();
+    if both {
+        Ok(vec![
+            format!("{}s", hand).parse()?,
+            format!("{}o", hand).parse()?,
+        ])
+    } else {
+        Ok(vec![hand.parse()?])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hand(hand: &str) -> StartingHand {
+        hand.parse().unwrap()
+    }
+
+    #[test]
+    fn test_classes() {
+        assert_eq!(hand("AA").index(), 0);
+        assert_eq!(hand("AKs").index(), 1);
+        assert_eq!(hand("AKo").index(), 13);
+        assert_eq!(hand("22").index(), 168);
+        for index in 0..CLASSES {
+            assert_eq!(StartingHand::from_index(index).unwrap().index(), index);
+        }
+        let combos: usize = StartingHand::all().map(|hand| hand.combo_count()).sum();
+        assert_eq!(combos, 1326);
+        assert_eq!(hand("KTo").combos().len(), 12);
+        assert_eq!(hand("AA").matchups_against(&hand("KK")), 36);
+        assert_eq!(hand("AA").matchups_against(&hand("AKs")), 6 * 2);
+        assert_eq!(hand("AKo").matchups_against(&hand("AKo")), 84);
+        assert_eq!(hand("QJs").to_string(), "QJs");
+        assert_eq!(hand("JQs"), hand("QJs"));
+        assert!("AAs".parse::<StartingHand>().is_err());
+        assert!("AK".parse::<StartingHand>().is_err());
+        let cards = crate::cards::parse_cards("10H 9H").unwrap();
+        assert_eq!(
+            StartingHand::from_cards(cards[0], cards[1]).unwrap(),
+            hand("T9s")
+        );
+    }
+
+    #[test]
+    fn test_range_notation() {
+        let range = parse_range("TT+, 66-44, A2s+, K9s-K6s, KQo, 72o").unwrap();
+        assert_eq!(range.len(), 5 + 3 + 12 + 4 + 1 + 1);
+        assert_eq!(format_range(&range), "TT+, 66-44, A2s+, K9s-K6s, KQo, 72o");
+        let both = parse_range("AK, 98").unwrap();
+        assert_eq!(format_range(&both), "AKs, 98s, AKo, 98o");
+        assert_eq!(parse_range("JTs+").unwrap().len(), 1);
+        assert_eq!(format_range(&parse_range("K2o+").unwrap()), "K2o+");
+        assert_eq!(format_range(&[]), "");
+        assert!(parse_range("AKs-QJs").is_err());
+        assert!(parse_range("AXs").is_err());
+        let all: Vec<StartingHand> = StartingHand::all().collect();
+        assert_eq!(parse_range(&format_range(&all)).unwrap(), all);
+    }
+}
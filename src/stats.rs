@@ -0,0 +1,470 @@
+//! Player statistics over a collection of hand histories, as shown by poker trackers.
+//!
+//! * VPIP: how often a player voluntarily puts chips in the pot before the flop.
+//! * PFR: how often a player raises before the flop.
+//! * 3-bet: how often a player re-raises a single raise before the flop, out of the times
+//!   they had the chance.
+//! * WTSD: how often a player who sees the flop goes to showdown.
+//! * W$SD: how often a player who goes to showdown wins it.
+//!
+//! Showdowns are judged by comparing the shown hands with `PokerHand`, so a player who ties
+//! for the best hand wins the showdown. When a hand at the showdown was mucked, the recorded
+//! awards are used instead.
+
+use crate::error::PokerHandError;
+use crate::hand_history::HandHistory;
+use crate::poker_hand::rank_tiers;
+use crate::table::{Action, Event, Street};
+
+/// A player's position at the table for one hand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Position {
+    /// Any seat before the middle positions.
+    Early,
+    /// The two seats before the cutoff, often called the lojack and hijack.
+    Middle,
+    /// The seat before the button.
+    Cutoff,
+    /// The button. Heads up, the button also posts the small blind.
+    Button,
+    SmallBlind,
+    BigBlind,
+}
+
+impl Position {
+    /// The position of the player with index `seat` in the hand's `players`.
+    pub fn of(hand: &HandHistory, seat: usize) -> Position {
+        if seat == hand.button {
+            return Position::Button;
+        }
+        for event in &hand.events {
+            match *event {
+                Event::PostSmallBlind { seat: s, .. } if s == seat => return Position::SmallBlind,
+                Event::PostBigBlind { seat: s, .. } if s == seat => return Position::BigBlind,
+                _ => {}
+            }
+        }
+        let players = hand.players.len();
+        match (hand.button + players - seat) % players {
+            1 => Position::Cutoff,
+            2 | 3 => Position::Middle,
+            _ => Position::Early,
+        }
+    }
+}
+
+/// Which hands, and which of a player's hands, to include in the statistics.
+///
+/// The default includes everything.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StatsFilter {
+    /// Only count the hands a player played from these positions. Empty means all.
+    pub positions: Vec<Position>,
+    /// Only count hands with this big blind.
+    pub big_blind: Option<u64>,
+    /// Only count hands played at or after this time, written as in `HandHistory::date`.
+    /// A date alone, such as "2023/01/05", counts from the start of that day.
+    pub from: Option<String>,
+    /// Only count hands played before this time.
+    pub until: Option<String>,
+}
+
+impl StatsFilter {
+    fn includes_hand(&self, hand: &HandHistory) -> bool {
+        if self
+            .big_blind
+            .is_some_and(|big_blind| big_blind != hand.big_blind)
+        {
+            return false;
+        }
+        if self.from.is_none() && self.until.is_none() {
+            return true;
+        }
+        match &hand.date {
+            Some(date) => {
+                self.from.as_ref().map_or(true, |from| date >= from)
+                    && self.until.as_ref().map_or(true, |until| date < until)
+            }
+            None => false,
+        }
+    }
+
+    fn includes_seat(&self, hand: &HandHistory, seat: usize) -> bool {
+        self.positions.is_empty() || self.positions.contains(&Position::of(hand, seat))
+    }
+}
+
+/// The counts behind a player's statistics.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PlayerStats {
+    pub name: String,
+    pub hands: u32,
+    /// Hands where the player called, bet or raised before the flop.
+    pub voluntarily_played: u32,
+    /// Hands where the player bet or raised before the flop.
+    pub preflop_raised: u32,
+    /// Hands where the player acted facing exactly one raise before the flop.
+    pub three_bet_chances: u32,
+    /// Hands where the player re-raised a single raise before the flop.
+    pub three_bet: u32,
+    pub saw_flop: u32,
+    pub went_to_showdown: u32,
+    pub won_at_showdown: u32,
+    /// The chips won, less the chips put in the pot.
+    pub net_won: i64,
+}
+
+impl PlayerStats {
+    pub fn vpip(&self) -> f64 {
+        ratio(self.voluntarily_played, self.hands)
+    }
+
+    pub fn pfr(&self) -> f64 {
+        ratio(self.preflop_raised, self.hands)
+    }
+
+    pub fn three_bet_frequency(&self) -> f64 {
+        ratio(self.three_bet, self.three_bet_chances)
+    }
+
+    /// Went to showdown, as a fraction of the hands where the player saw the flop.
+    pub fn wtsd(&self) -> f64 {
+        ratio(self.went_to_showdown, self.saw_flop)
+    }
+
+    /// Won at showdown, as a fraction of the showdowns the player went to.
+    pub fn wsd(&self) -> f64 {
+        ratio(self.won_at_showdown, self.went_to_showdown)
+    }
+
+    /// The chips won per hundred hands, in big blinds of `big_blind` chips.
+    pub fn big_blinds_per_100(&self, big_blind: u64) -> f64 {
+        if self.hands == 0 || big_blind == 0 {
+            return 0.0;
+        }
+        self.net_won as f64 / big_blind as f64 * 100.0 / self.hands as f64
+    }
+}
+
+fn ratio(count: u32, total: u32) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        count as f64 / total as f64
+    }
+}
+
+/// Compute each player's statistics across the hands, in order of name.
+///
+/// Players are identified by name, so the same player is tracked across tables.
+/// Hands whose chips do not add up, such as a corrupt import, are skipped.
+pub fn player_stats<'a, I>(hands: I, filter: &StatsFilter) -> Vec<PlayerStats>
+where
+    I: IntoIterator<Item = &'a HandHistory>,
+{
+    let mut stats: Vec<PlayerStats> = Vec::new();
+    for hand in hands {
+        if !filter.includes_hand(hand) {
+            continue;
+        }
+        let summary = match HandSummary::new(hand) {
+            Ok(summary) => summary,
+            Err(_) => continue,
+        };
+        for (seat, player) in hand.players.iter().enumerate() {
+            if !filter.includes_seat(hand, seat) {
+                continue;
+            }
+            let index = match stats.iter().position(|s| s.name == player.name) {
+                Some(index) => index,
+                None => {
+                    stats.push(PlayerStats {
+                        name: player.name.clone(),
+                        ..PlayerStats::default()
+                    });
+                    stats.len() - 1
+                }
+            };
+            summary.add_to(seat, &mut stats[index]);
+        }
+    }
+    stats.sort_by(|a, b| a.name.cmp(&b.name));
+    stats
+}
+
+/// What each player did in one hand.
+struct HandSummary {
+    voluntarily_played: Vec<bool>,
+    preflop_raised: Vec<bool>,
+    three_bet_chance: Vec<bool>,
+    three_bet: Vec<bool>,
+    saw_flop: Vec<bool>,
+    went_to_showdown: Vec<bool>,
+    won_at_showdown: Vec<bool>,
+    net_won: Vec<i64>,
+}
+
+impl HandSummary {
+    fn new(hand: &HandHistory) -> Result<HandSummary, PokerHandError> {
+        // The contributions check that every event refers to a seat at the table.
+        let contributions = hand.contributions()?;
+        let players = hand.players.len();
+        if hand.awards.iter().any(|award| award.seat >= players) {
+            return Err(PokerHandError::new(
+                "An award refers to a seat not at the table",
+            ));
+        }
+        let mut summary = HandSummary {
+            voluntarily_played: vec![false; players],
+            preflop_raised: vec![false; players],
+            three_bet_chance: vec![false; players],
+            three_bet: vec![false; players],
+            saw_flop: vec![false; players],
+            went_to_showdown: vec![false; players],
+            won_at_showdown: vec![false; players],
+            net_won: vec![0; players],
+        };
+
+        let mut folded = vec![false; players];
+        let mut raises = 0;
+        for event in &hand.events {
+            match *event {
+                Event::Act {
+                    street: Street::Preflop,
+                    seat,
+                    action,
+                    ..
+                } => {
+                    if raises == 1 {
+                        summary.three_bet_chance[seat] = true;
+                    }
+                    match action {
+                        Action::Fold => folded[seat] = true,
+                        Action::Check => {}
+                        Action::Call => summary.voluntarily_played[seat] = true,
+                        Action::Bet(_) | Action::Raise(_) => {
+                            summary.voluntarily_played[seat] = true;
+                            summary.preflop_raised[seat] = true;
+                            if raises == 1 {
+                                summary.three_bet[seat] = true;
+                            }
+                            raises += 1;
+                        }
+                    }
+                }
+                Event::Act {
+                    seat,
+                    action: Action::Fold,
+                    ..
+                } => folded[seat] = true,
+                Event::Deal {
+                    street: Street::Flop,
+                } => summary.saw_flop = folded.iter().map(|&folded| !folded).collect(),
+                _ => {}
+            }
+        }
+
+        let live = hand.showdown_players();
+        if live.len() > 1 {
+            let winners = showdown_winners(hand, &live);
+            for &seat in &live {
+                summary.went_to_showdown[seat] = true;
+                summary.won_at_showdown[seat] = winners.contains(&seat);
+            }
+        }
+        for contribution in contributions {
+            summary.net_won[contribution.seat] -= contribution.amount as i64;
+        }
+        for award in &hand.awards {
+            summary.net_won[award.seat] += award.amount as i64;
+        }
+        Ok(summary)
+    }
+
+    fn add_to(&self, seat: usize, stats: &mut PlayerStats) {
+        let count = |flags: &[bool]| flags[seat] as u32;
+        stats.hands += 1;
+        stats.voluntarily_played += count(&self.voluntarily_played);
+        stats.preflop_raised += count(&self.preflop_raised);
+        stats.three_bet_chances += count(&self.three_bet_chance);
+        stats.three_bet += count(&self.three_bet);
+        stats.saw_flop += count(&self.saw_flop);
+        stats.went_to_showdown += count(&self.went_to_showdown);
+        stats.won_at_showdown += count(&self.won_at_showdown);
+        stats.net_won += self.net_won[seat];
+    }
+}
+
+/// The players holding the best hand at the showdown, or, if some hands were not shown,
+/// the players who collected chips.
+fn showdown_winners(hand: &HandHistory, live: &[usize]) -> Vec<usize> {
+    match hand.showdown_hands() {
+        Ok(hands) => rank_tiers(&hands).into_iter().next().unwrap_or_default(),
+        Err(_) => live
+            .iter()
+            .copied()
+            .filter(|&seat| hand.awards.iter().any(|award| award.seat == seat))
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HANDS: &str = "PokerStars Hand #1: Hold'em No Limit (1/2) - 2023/01/05 10:00:00
+Table 'T' 4-max Seat #1 is the button
+Seat 1: ann (200 in chips)
+Seat 2: ben (200 in chips)
+Seat 3: cat (200 in chips)
+Seat 4: dan (200 in chips)
+ben: posts small blind 1
+cat: posts big blind 2
+*** HOLE CARDS ***
+dan: raises 4 to 6
+ann: raises 12 to 18
+ben: folds
+cat: folds
+dan: calls 12
+*** FLOP *** [AS 7D 2C]
+dan: checks
+ann: bets 20
+dan: calls 20
+*** TURN *** [AS 7D 2C] [9H]
+dan: checks
+ann: checks
+*** RIVER *** [AS 7D 2C 9H] [KC]
+dan: checks
+ann: checks
+*** SHOW DOWN ***
+dan: shows [AH QD] (a pair)
+ann: shows [KS KD] (three of a kind)
+ann collected 79 from pot
+*** SUMMARY ***
+Total pot 79 | Rake 0
+
+PokerStars Hand #2: Hold'em No Limit (1/2) - 2023/01/06 10:00:00
+Table 'T' 4-max Seat #2 is the button
+Seat 1: ann (239 in chips)
+Seat 2: ben (199 in chips)
+Seat 3: cat (198 in chips)
+Seat 4: dan (162 in chips)
+cat: posts small blind 1
+dan: posts big blind 2
+*** HOLE CARDS ***
+ann: folds
+ben: calls 2
+cat: calls 1
+dan: checks
+*** FLOP *** [5S 5D 8C]
+cat: bets 4
+dan: folds
+ben: folds
+Uncalled bet (4) returned to cat
+cat collected 6 from pot
+*** SUMMARY ***
+Total pot 6 | Rake 0
+";
+
+    fn hands() -> Vec<HandHistory> {
+        crate::import::import_hands(HANDS)
+            .into_iter()
+            .map(|hand| hand.result.unwrap())
+            .collect()
+    }
+
+    fn find<'a>(stats: &'a [PlayerStats], name: &str) -> &'a PlayerStats {
+        stats.iter().find(|s| s.name == name).unwrap()
+    }
+
+    #[test]
+    fn test_positions() {
+        let hands = hands();
+        assert_eq!(Position::of(&hands[0], 0), Position::Button);
+        assert_eq!(Position::of(&hands[0], 1), Position::SmallBlind);
+        assert_eq!(Position::of(&hands[0], 2), Position::BigBlind);
+        assert_eq!(Position::of(&hands[0], 3), Position::Cutoff);
+        assert_eq!(Position::of(&hands[1], 0), Position::Cutoff);
+    }
+
+    #[test]
+    fn test_player_stats() {
+        let stats = player_stats(&hands(), &StatsFilter::default());
+        let names: Vec<&str> = stats.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["ann", "ben", "cat", "dan"]);
+
+        let ann = find(&stats, "ann");
+        assert_eq!(ann.hands, 2);
+        assert_eq!(ann.vpip(), 0.5);
+        assert_eq!(ann.pfr(), 0.5);
+        assert_eq!((ann.three_bet, ann.three_bet_chances), (1, 1));
+        assert_eq!(ann.wtsd(), 1.0);
+        assert_eq!(ann.wsd(), 1.0);
+        assert_eq!(ann.net_won, 79 - 38);
+
+        let dan = find(&stats, "dan");
+        assert_eq!((dan.preflop_raised, dan.three_bet_chances), (1, 0));
+        assert_eq!(dan.saw_flop, 2);
+        assert_eq!((dan.went_to_showdown, dan.won_at_showdown), (1, 0));
+        assert_eq!(dan.wtsd(), 0.5);
+        assert_eq!(dan.net_won, -38 - 2);
+
+        let cat = find(&stats, "cat");
+        assert_eq!(cat.vpip(), 0.5);
+        assert_eq!(cat.net_won, -2 + 4);
+        assert_eq!(cat.big_blinds_per_100(2), 50.0);
+    }
+
+    #[test]
+    fn test_filters() {
+        let hands = hands();
+        let filter = StatsFilter {
+            from: Some("2023/01/06".to_string()),
+            ..StatsFilter::default()
+        };
+        assert_eq!(find(&player_stats(&hands, &filter), "ann").hands, 1);
+        let filter = StatsFilter {
+            until: Some("2023/01/06".to_string()),
+            ..StatsFilter::default()
+        };
+        assert_eq!(find(&player_stats(&hands, &filter), "ann").hands, 1);
+        let filter = StatsFilter {
+            big_blind: Some(4),
+            ..StatsFilter::default()
+        };
+        assert!(player_stats(&hands, &filter).is_empty());
+        let filter = StatsFilter {
+            positions: vec![Position::Button],
+            ..StatsFilter::default()
+        };
+        let stats = player_stats(&hands, &filter);
+        let names: Vec<&str> = stats.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["ann", "ben"]);
+    }
+
+    #[test]
+    fn test_inconsistent_hand_skipped() {
+        let mut hands = hands();
+        hands[0].events.push(Event::ReturnUncalled {
+            seat: 0,
+            amount: 1000,
+        });
+        let stats = player_stats(&hands, &StatsFilter::default());
+        assert_eq!(find(&stats, "ann").hands, 1);
+
+        // Hands referring to seats not at the table are skipped rather than panicking.
+        let mut hands = self::hands();
+        hands[0].events.insert(
+            0,
+            Event::Act {
+                street: Street::Preflop,
+                seat: 9,
+                action: Action::Fold,
+                amount: 0,
+                all_in: false,
+            },
+        );
+        hands[1].awards[0].seat = 9;
+        assert!(player_stats(&hands, &StatsFilter::default()).is_empty());
+    }
+}
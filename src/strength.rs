@@ -0,0 +1,299 @@
+//! Hand strength and hand potential metrics for Texas Hold'em bots.
+//!
+//! These are the metrics described by Billings et al. in "Opponent Modeling in Poker":
+//!
+//! * Hand strength (HS) is the probability that the hand is currently the best against a
+//!   uniformly random opponent holding.
+//! * Positive potential (PPot) is the probability that a hand which is behind or tied now
+//!   is ahead once all the board cards are dealt.
+//! * Negative potential (NPot) is the probability that a hand which is ahead or tied now
+//!   falls behind once all the board cards are dealt.
+//! * Effective hand strength (EHS) combines the three: HS × (1 − NPot) + (1 − HS) × PPot.
+//!
+//! Against several opponents the hand strength is HS raised to the number of opponents,
+//! treating the opponent holdings as independent, while the potentials are measured
+//! against a single opponent.
+
+use crate::cards::Card;
+use crate::error::PokerHandError;
+use crate::evaluator::{evaluate_unchecked, HandValue};
+use crate::rng::SeededRng;
+use std::cmp::Ordering;
+
+/// How the opponent holdings and the cards to come are chosen.
+#[derive(Debug, Clone, Copy)]
+pub enum Enumeration {
+    /// Consider every possible opponent holding and every possible run out.
+    Exhaustive,
+    /// Consider a number of random opponent holdings and run outs, drawn using the seed.
+    Sampled { samples: usize, seed: u64 },
+}
+
+/// The hand strength and potential of a hand on the flop or the turn.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HandPotential {
+    /// The hand strength against the given number of opponents.
+    pub hand_strength: f64,
+    /// The probability of improving to the best hand when behind or tied.
+    pub positive_potential: f64,
+    /// The probability of falling behind when ahead or tied.
+    pub negative_potential: f64,
+    /// The hand strength, adjusted for the potential of the cards to come.
+    pub effective_hand_strength: f64,
+}
+
+// Indices into the tallies, from the player's point of view.
+const AHEAD: usize = 0;
+const TIED: usize = 1;
+const BEHIND: usize = 2;
+
+/// The immediate hand strength of the hole cards on a flop, turn or river board.
+pub fn hand_strength(
+    hole: &[Card],
+    board: &[Card],
+    opponents: u32,
+    enumeration: Enumeration,
+) -> Result<f64, PokerHandError> {
+    check_cards(hole, board, 3..=5, opponents)?;
+    let unseen = unseen_cards(hole, board);
+    let ours = value_of(hole, board, &[]);
+
+    let mut tally = [0u64; 3];
+    for_each_deal(&unseen, 0, enumeration, |opponent, _| {
+        tally[compare(ours, value_of(opponent, board, &[]))] += 1;
+    });
+    Ok(strength(&tally).powi(opponents as i32))
+}
+
+/// The hand strength, positive and negative potential and effective hand strength of the
+/// hole cards on a flop or turn board.
+pub fn hand_potential(
+    hole: &[Card],
+    board: &[Card],
+    opponents: u32,
+    enumeration: Enumeration,
+) -> Result<HandPotential, PokerHandError> {
+    check_cards(hole, board, 3..=4, opponents)?;
+    let unseen = unseen_cards(hole, board);
+    let ours_now = value_of(hole, board, &[]);
+
+    // Counts of (current state, final state) pairs, and the totals for each current state.
+    let mut potential = [[0u64; 3]; 3];
+    let mut totals = [0u64; 3];
+    for_each_deal(&unseen, 5 - board.len(), enumeration, |opponent, runout| {
+        let now = compare(ours_now, value_of(opponent, board, &[]));
+        let later = compare(
+            value_of(hole, board, runout),
+            value_of(opponent, board, runout),
+        );
+        potential[now][later] += 1;
+        totals[now] += 1;
+    });
+
+    let ratio = |numerator: f64, denominator: f64| {
+        if denominator > 0.0 {
+            numerator / denominator
+        } else {
+            0.0
+        }
+    };
+    let p = |now: usize, later: usize| potential[now][later] as f64;
+    let t = |now: usize| totals[now] as f64;
+    let positive_potential = ratio(
+        p(BEHIND, AHEAD) + p(BEHIND, TIED) / 2.0 + p(TIED, AHEAD) / 2.0,
+        t(BEHIND) + t(TIED) / 2.0,
+    );
+    let negative_potential = ratio(
+        p(AHEAD, BEHIND) + p(TIED, BEHIND) / 2.0 + p(AHEAD, TIED) / 2.0,
+        t(AHEAD) + t(TIED) / 2.0,
+    );
+    let hand_strength = strength(&totals).powi(opponents as i32);
+    Ok(HandPotential {
+        hand_strength,
+        positive_potential,
+        negative_potential,
+        effective_hand_strength: hand_strength * (1.0 - negative_potential)
+            + (1.0 - hand_strength) * positive_potential,
+    })
+}
+
+/// Call `visit` with opponent hole cards and `runout` further board cards, drawn from the
+/// unseen cards, either for every combination or for the requested number of samples.
+fn for_each_deal<F>(unseen: &[Card], runout: usize, enumeration: Enumeration, mut visit: F)
+where
+    F: FnMut(&[Card], &[Card]),
+{
+    match enumeration {
+        Enumeration::Exhaustive => {
+            let n = unseen.len();
+            for i in 0..n {
+                for j in i + 1..n {
+                    let opponent = [unseen[i], unseen[j]];
+                    let rest: Vec<Card> = unseen
+                        .iter()
+                        .enumerate()
+                        .filter(|&(k, _)| k != i && k != j)
+                        .map(|(_, &card)| card)
+                        .collect();
+                    match runout {
+                        0 => visit(&opponent, &[]),
+                        1 => rest.iter().for_each(|&card| visit(&opponent, &[card])),
+                        _ => {
+                            for (k, &first) in rest.iter().enumerate() {
+                                for &second in &rest[k + 1..] {
+                                    visit(&opponent, &[first, second]);
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        Enumeration::Sampled { samples, seed } => {
+            let mut rng = SeededRng::new(seed);
+            let mut deck = unseen.to_vec();
+            let needed = 2 + runout;
+            for _ in 0..samples {
+                rng.shuffle_prefix(&mut deck, needed);
+                visit(&deck[..2], &deck[2..needed]);
+            }
+        }
+    }
+}
+
+/// The value of the best hand from the hole cards, board and run out, without allocating.
+fn value_of(hole: &[Card], board: &[Card], runout: &[Card]) -> HandValue {
+    let mut cards = [hole[0]; 7];
+    let mut len = 0;
+    for &card in hole.iter().chain(board).chain(runout) {
+        cards[len] = card;
+        len += 1;
+    }
+    evaluate_unchecked(&cards[..len])
+}
+
+fn compare(ours: HandValue, theirs: HandValue) -> usize {
+    match ours.cmp(&theirs) {
+        Ordering::Greater => AHEAD,
+        Ordering::Equal => TIED,
+        Ordering::Less => BEHIND,
+    }
+}
+
+/// The fraction of deals where the hand is ahead, counting ties as half.
+fn strength(tally: &[u64; 3]) -> f64 {
+    let total: u64 = tally.iter().sum();
+    if total == 0 {
+        return 0.0;
+    }
+    (tally[AHEAD] as f64 + tally[TIED] as f64 / 2.0) / total as f64
+}
+
+fn unseen_cards(hole: &[Card], board: &[Card]) -> Vec<Card> {
+    (0..52)
+        .filter_map(Card::from_index)
+        .filter(|card| !hole.contains(card) && !board.contains(card))
+        .collect()
+}
+
+fn check_cards(
+    hole: &[Card],
+    board: &[Card],
+    board_sizes: std::ops::RangeInclusive<usize>,
+    opponents: u32,
+) -> Result<(), PokerHandError> {
+    if hole.len() != 2 {
+        return Err(PokerHandError::new("A player must have two hole cards"));
+    }
+    if !board_sizes.contains(&board.len()) {
+        return Err(PokerHandError::new("Invalid number of board cards"));
+    }
+    if opponents == 0 {
+        return Err(PokerHandError::new("There must be at least one opponent"));
+    }
+    let all = [hole, board].concat();
+    for (i, card) in all.iter().enumerate() {
+        if all[i + 1..].contains(card) {
+            return Err(PokerHandError::new("Duplicate cards in hand"));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards::parse_cards;
+
+    #[test]
+    fn test_nuts_on_the_river() {
+        let hole = parse_cards("AH KH").unwrap();
+        let board = parse_cards("QH JH 10H 2C 3D").unwrap();
+        let hs = hand_strength(&hole, &board, 3, Enumeration::Exhaustive).unwrap();
+        assert_eq!(hs, 1.0);
+    }
+
+    #[test]
+    fn test_hand_strength_on_the_river() {
+        // Of the 990 holdings the opponent can have, 129 beat our kings with a seven kicker:
+        // 78 two pairs, 13 sets or trip kings, 6 pairs of aces and 32 kings with a better
+        // kicker. The 6 holdings of a king and a seven tie.
+        let hole = parse_cards("KD 7C").unwrap();
+        let board = parse_cards("KS 9H 4D 2C 8S").unwrap();
+        let hs = hand_strength(&hole, &board, 1, Enumeration::Exhaustive).unwrap();
+        assert_eq!(hs, (855.0 + 6.0 / 2.0) / 990.0);
+        let hs3 = hand_strength(&hole, &board, 3, Enumeration::Exhaustive).unwrap();
+        assert!((hs3 - hs.powi(3)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_potential_of_a_flush_draw_on_the_turn() {
+        let hole = parse_cards("AH 3H").unwrap();
+        let board = parse_cards("KH 8H 2D 7C").unwrap();
+        let potential = hand_potential(&hole, &board, 1, Enumeration::Exhaustive).unwrap();
+        // Ace high is behind every pair, and hits one of nine hearts, or an ace or a three.
+        assert!(potential.positive_potential > 0.2);
+        assert!(potential.effective_hand_strength > potential.hand_strength);
+        assert!((0.0..=1.0).contains(&potential.negative_potential));
+    }
+
+    #[test]
+    fn test_sampled_potential_is_close_to_exhaustive() {
+        let hole = parse_cards("QS QD").unwrap();
+        let board = parse_cards("JH 9H 2C 5D").unwrap();
+        let exact = hand_potential(&hole, &board, 1, Enumeration::Exhaustive).unwrap();
+        let sampled = Enumeration::Sampled {
+            samples: 20_000,
+            seed: 11,
+        };
+        let estimate = hand_potential(&hole, &board, 1, sampled).unwrap();
+        assert!((exact.hand_strength - estimate.hand_strength).abs() < 0.02);
+        assert!((exact.negative_potential - estimate.negative_potential).abs() < 0.02);
+        // The same seed gives the same answer.
+        assert_eq!(estimate, hand_potential(&hole, &board, 1, sampled).unwrap());
+    }
+
+    #[test]
+    fn test_flop_potential_sampled() {
+        let hole = parse_cards("9C 10C").unwrap();
+        let board = parse_cards("JC QD 2C").unwrap();
+        let sampled = Enumeration::Sampled {
+            samples: 5_000,
+            seed: 3,
+        };
+        let potential = hand_potential(&hole, &board, 2, sampled).unwrap();
+        assert!(potential.positive_potential > 0.4);
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        let hole = parse_cards("AH 3H").unwrap();
+        let flop = parse_cards("KH 8H 2D").unwrap();
+        let river = parse_cards("KH 8H 2D 7C 9C").unwrap();
+        let exhaustive = Enumeration::Exhaustive;
+        assert!(hand_potential(&hole, &river, 1, exhaustive).is_err());
+        assert!(hand_strength(&hole, &flop, 0, exhaustive).is_err());
+        assert!(hand_strength(&hole, &flop[..2], 1, exhaustive).is_err());
+        assert!(hand_strength(&hole, &parse_cards("AH 8H 2D").unwrap(), 1, exhaustive).is_err());
+    }
+}
@@ -0,0 +1,942 @@
+//! A betting round state machine for no-limit and pot-limit community card games.
+//!
+//! `TableHand` plays out a single hand: it posts antes, blinds and an optional straddle,
+//! tracks whose turn it is and which actions are legal, moves from street to street, and
+//! finally awards the pots at showdown using `PokerHand` comparison, net of any rake set
+//! by the `TableConfig`. It is deterministic and does no I/O: the cards are dealt up front
+//! by the caller and are only revealed as the hand progresses. Illegal actions are
+//! rejected with a `TableError`, leaving the hand unchanged.
+//!
+//! Seats are numbered clockwise from 0. Bets and raises are given as the total the player
+//! is putting in on the current street ("raise to"), as is usual for no-limit games.
+
+use crate::cards::Card;
+use crate::error::TableError;
+use crate::poker_hand::PokerHand;
+use crate::pot::{build_pots, distribute, Award, Contribution, OddChipRule, Pot};
+use crate::rake::{Rake, RakeRule};
+
+/// The betting structure, which sets the maximum bet or raise.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BettingStructure {
+    /// Players may bet up to their whole stack.
+    NoLimit,
+    /// Players may bet up to the size of the pot, after calling.
+    PotLimit,
+}
+
+/// The blinds, antes and betting structure for a hand.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableConfig {
+    pub structure: BettingStructure,
+    pub small_blind: u64,
+    pub big_blind: u64,
+    /// Posted by every player before the deal. Antes are dead money and do not count
+    /// towards calling a bet.
+    pub ante: u64,
+    /// A blind raise posted by the player after the big blind, who then acts last preflop.
+    pub straddle: Option<u64>,
+    /// The rake taken from the pot, if any.
+    pub rake: Option<RakeRule>,
+}
+
+impl TableConfig {
+    /// A no-limit game with the given blinds and no ante or straddle.
+    pub fn no_limit(small_blind: u64, big_blind: u64) -> Self {
+        Self {
+            structure: BettingStructure::NoLimit,
+            small_blind,
+            big_blind,
+            ante: 0,
+            straddle: None,
+            rake: None,
+        }
+    }
+
+    /// A pot-limit game with the given blinds and no ante or straddle.
+    pub fn pot_limit(small_blind: u64, big_blind: u64) -> Self {
+        Self {
+            structure: BettingStructure::PotLimit,
+            ..Self::no_limit(small_blind, big_blind)
+        }
+    }
+}
+
+/// The betting rounds of a hand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Street {
+    Preflop,
+    Flop,
+    Turn,
+    River,
+    Showdown,
+}
+
+impl Street {
+    /// The number of board cards visible on the street.
+    pub fn board_cards(self) -> usize {
+        match self {
+            Street::Preflop => 0,
+            Street::Flop => 3,
+            Street::Turn => 4,
+            Street::River | Street::Showdown => 5,
+        }
+    }
+
+    fn next(self) -> Street {
+        match self {
+            Street::Preflop => Street::Flop,
+            Street::Flop => Street::Turn,
+            Street::Turn => Street::River,
+            Street::River | Street::Showdown => Street::Showdown,
+        }
+    }
+}
+
+/// A player's action. Bets and raises give the total for the street, not the increase.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Fold,
+    Check,
+    Call,
+    Bet(u64),
+    Raise(u64),
+}
+
+/// A record of something that happened during the hand, in order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    PostAnte {
+        seat: usize,
+        amount: u64,
+    },
+    PostSmallBlind {
+        seat: usize,
+        amount: u64,
+    },
+    PostBigBlind {
+        seat: usize,
+        amount: u64,
+    },
+    PostStraddle {
+        seat: usize,
+        amount: u64,
+    },
+    /// A player's action, with the chips it added to the pot.
+    Act {
+        street: Street,
+        seat: usize,
+        action: Action,
+        amount: u64,
+        all_in: bool,
+    },
+    /// The part of a bet nobody called, given back to the bettor.
+    ReturnUncalled {
+        seat: usize,
+        amount: u64,
+    },
+    /// A new street, once its board cards are dealt.
+    Deal {
+        street: Street,
+    },
+}
+
+/// The range of totals a player may bet or raise to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RaiseRange {
+    pub min_to: u64,
+    pub max_to: u64,
+}
+
+/// The actions available to the player whose turn it is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LegalActions {
+    pub seat: usize,
+    /// True if there is a bet to call, so a bet is made with `Action::Raise`.
+    pub facing_bet: bool,
+    /// The chips needed to call, capped at the player's stack, or None if they can check.
+    pub call: Option<u64>,
+    /// The range the player may bet or raise to, if they may bet or raise at all.
+    pub raise: Option<RaiseRange>,
+}
+
+/// A player's chips and cards for the hand.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Seat {
+    pub stack: u64,
+    pub hole_cards: [Card; 2],
+    /// Chips put in on the current street, excluding the ante.
+    pub street_bet: u64,
+    /// Chips put in over the whole hand, including the ante.
+    pub contributed: u64,
+    pub folded: bool,
+    acted: bool,
+    can_raise: bool,
+}
+
+impl Seat {
+    pub fn is_all_in(&self) -> bool {
+        !self.folded && self.stack == 0
+    }
+
+    /// A player who can still make betting decisions.
+    fn is_active(&self) -> bool {
+        !self.folded && self.stack > 0
+    }
+
+    /// Move up to `amount` chips from the stack into the pot, returning the chips moved.
+    fn put_in(&mut self, amount: u64, counts_as_bet: bool) -> u64 {
+        let amount = amount.min(self.stack);
+        self.stack -= amount;
+        self.contributed += amount;
+        if counts_as_bet {
+            self.street_bet += amount;
+        }
+        amount
+    }
+}
+
+/// The outcome of a completed hand.
+#[derive(Debug, Clone)]
+pub struct HandResult {
+    /// The chips won from each pot.
+    pub awards: Vec<Award>,
+    /// The best hand of each player at showdown, by seat. Empty if everyone else folded.
+    pub shown: Vec<(usize, PokerHand<'static>)>,
+    /// The rake taken before the awards, which is empty if the table takes no rake.
+    pub rake: Rake,
+}
+
+/// A single hand being played at the table.
+#[derive(Debug, Clone)]
+pub struct TableHand {
+    config: TableConfig,
+    button: usize,
+    starting_stacks: Vec<u64>,
+    seats: Vec<Seat>,
+    board: [Card; 5],
+    street: Street,
+    /// The street bet every player must match.
+    current_bet: u64,
+    /// The size of the last full bet or raise, which the next raise must at least match.
+    min_raise: u64,
+    next: Option<usize>,
+    events: Vec<Event>,
+    result: Option<HandResult>,
+}
+
+impl TableHand {
+    /// Start a hand: post the antes and blinds, and find the first player to act.
+    ///
+    /// `stacks` and `hole_cards` are given by seat, and the board holds the five community
+    /// cards, which are revealed street by street.
+    pub fn new(
+        config: &TableConfig,
+        stacks: &[u64],
+        button: usize,
+        hole_cards: &[[Card; 2]],
+        board: [Card; 5],
+    ) -> Result<TableHand, TableError> {
+        let players = stacks.len();
+        check_setup(config, stacks, button, hole_cards, &board)?;
+
+        let seats = stacks
+            .iter()
+            .zip(hole_cards)
+            .map(|(&stack, &hole_cards)| Seat {
+                stack,
+                hole_cards,
+                street_bet: 0,
+                contributed: 0,
+                folded: false,
+                acted: false,
+                can_raise: true,
+            })
+            .collect();
+        let opening_bet = config.straddle.unwrap_or(config.big_blind);
+        let mut hand = TableHand {
+            config: config.clone(),
+            button,
+            starting_stacks: stacks.to_vec(),
+            seats,
+            board,
+            street: Street::Preflop,
+            current_bet: opening_bet,
+            min_raise: opening_bet,
+            next: None,
+            events: Vec::new(),
+            result: None,
+        };
+
+        if config.ante > 0 {
+            for seat in 0..players {
+                let amount = hand.seats[seat].put_in(config.ante, false);
+                hand.events.push(Event::PostAnte { seat, amount });
+            }
+        }
+        // Heads up, the button posts the small blind.
+        let small_blind = if players == 2 {
+            button
+        } else {
+            (button + 1) % players
+        };
+        let big_blind = (small_blind + 1) % players;
+        let amount = hand.seats[small_blind].put_in(config.small_blind, true);
+        hand.events.push(Event::PostSmallBlind {
+            seat: small_blind,
+            amount,
+        });
+        let amount = hand.seats[big_blind].put_in(config.big_blind, true);
+        hand.events.push(Event::PostBigBlind {
+            seat: big_blind,
+            amount,
+        });
+        let mut last_blind = big_blind;
+        if let Some(straddle) = config.straddle {
+            last_blind = (big_blind + 1) % players;
+            let amount = hand.seats[last_blind].put_in(straddle, true);
+            hand.events.push(Event::PostStraddle {
+                seat: last_blind,
+                amount,
+            });
+        }
+
+        hand.advance_from(last_blind);
+        Ok(hand)
+    }
+
+    /// The current street, or `Street::Showdown` once the hand has gone to showdown.
+    pub fn street(&self) -> Street {
+        self.street
+    }
+
+    /// The blinds, antes and betting structure of the hand.
+    pub fn config(&self) -> &TableConfig {
+        &self.config
+    }
+
+    /// The players' stacks before the antes and blinds were posted, by seat.
+    pub fn starting_stacks(&self) -> &[u64] {
+        &self.starting_stacks
+    }
+
+    /// The seat of the dealer button.
+    pub fn button(&self) -> usize {
+        self.button
+    }
+
+    /// The board cards dealt so far.
+    pub fn board(&self) -> &[Card] {
+        &self.board[..self.street.board_cards()]
+    }
+
+    /// The players' chips and cards, by seat.
+    pub fn seats(&self) -> &[Seat] {
+        &self.seats
+    }
+
+    /// The total chips in the pot, including the bets on the current street.
+    pub fn pot(&self) -> u64 {
+        self.seats.iter().map(|seat| seat.contributed).sum()
+    }
+
+    /// Everything that has happened in the hand so far.
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+
+    /// The seat whose turn it is, or None once the hand is complete.
+    pub fn to_act(&self) -> Option<usize> {
+        self.next
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.result.is_some()
+    }
+
+    /// The outcome of the hand, once it is complete.
+    pub fn result(&self) -> Option<&HandResult> {
+        self.result.as_ref()
+    }
+
+    /// The actions available to the player whose turn it is.
+    pub fn legal_actions(&self) -> Option<LegalActions> {
+        let seat = self.next?;
+        let player = &self.seats[seat];
+        let to_call = self.current_bet.saturating_sub(player.street_bet);
+        let others_can_act = self
+            .seats
+            .iter()
+            .enumerate()
+            .any(|(other, s)| other != seat && s.is_active());
+        let raise = if player.can_raise && player.stack > to_call && others_can_act {
+            let all_in_to = player.street_bet + player.stack;
+            let mut max_to = all_in_to;
+            if self.config.structure == BettingStructure::PotLimit {
+                // Call, then raise by the size of the pot.
+                max_to = max_to.min(self.current_bet + self.pot() + to_call);
+            }
+            let min_to = (self.current_bet + self.min_raise).min(all_in_to);
+            Some(RaiseRange {
+                min_to,
+                max_to: max_to.max(min_to),
+            })
+        } else {
+            None
+        };
+        Some(LegalActions {
+            seat,
+            facing_bet: self.current_bet > 0,
+            call: if to_call > 0 {
+                Some(to_call.min(player.stack))
+            } else {
+                None
+            },
+            raise,
+        })
+    }
+
+    /// Apply an action for the player whose turn it is.
+    pub fn act(&mut self, seat: usize, action: Action) -> Result<(), TableError> {
+        if self.result.is_some() {
+            return Err(TableError::HandComplete);
+        }
+        let legal = self.legal_actions().ok_or(TableError::HandComplete)?;
+        if seat != legal.seat {
+            return Err(TableError::OutOfTurn {
+                expected: legal.seat,
+                actual: seat,
+            });
+        }
+
+        let amount = match action {
+            Action::Fold => {
+                self.seats[seat].folded = true;
+                0
+            }
+            Action::Check => {
+                if let Some(to_call) = legal.call {
+                    return Err(TableError::CannotCheck { to_call });
+                }
+                0
+            }
+            Action::Call => {
+                let to_call = legal.call.ok_or(TableError::NothingToCall)?;
+                self.seats[seat].put_in(to_call, true)
+            }
+            Action::Bet(to) | Action::Raise(to) => {
+                if matches!(action, Action::Bet(_)) == legal.facing_bet {
+                    return Err(if legal.facing_bet {
+                        TableError::CannotBet
+                    } else {
+                        TableError::CannotRaise
+                    });
+                }
+                let range = legal.raise.ok_or(TableError::CannotRaise)?;
+                if to > range.max_to {
+                    return Err(TableError::BetTooLarge {
+                        maximum: range.max_to,
+                    });
+                }
+                if to < range.min_to {
+                    return Err(TableError::BetTooSmall {
+                        minimum: range.min_to,
+                    });
+                }
+                self.raise_to(seat, to)
+            }
+        };
+        self.seats[seat].acted = true;
+        let all_in = self.seats[seat].is_all_in();
+        self.events.push(Event::Act {
+            street: self.street,
+            seat,
+            action,
+            amount,
+            all_in,
+        });
+        self.advance_from(seat);
+        Ok(())
+    }
+
+    /// Put in chips to bring the seat's street bet up to `to`, reopening the betting if it
+    /// is a full raise.
+    fn raise_to(&mut self, seat: usize, to: u64) -> u64 {
+        let street_bet = self.seats[seat].street_bet;
+        let amount = self.seats[seat].put_in(to - street_bet, true);
+        let raise = to - self.current_bet;
+        let full_raise = raise >= self.min_raise;
+        if full_raise {
+            self.min_raise = raise;
+        }
+        self.current_bet = to;
+        for (other, player) in self.seats.iter_mut().enumerate() {
+            if other == seat {
+                continue;
+            }
+            // An all in for less than a full raise makes everyone act again, but does not
+            // reopen the raising for players who have already acted.
+            if full_raise {
+                player.can_raise = true;
+            } else if player.acted {
+                player.can_raise = false;
+            }
+            player.acted = false;
+        }
+        amount
+    }
+
+    /// Find the next player to act after `seat`, moving on to the next street or to the end
+    /// of the hand if the betting round is over.
+    fn advance_from(&mut self, seat: usize) {
+        let players = self.seats.len();
+        let live = self.seats.iter().filter(|s| !s.folded).count();
+        if live > 1 {
+            for offset in 1..=players {
+                let candidate = (seat + offset) % players;
+                if self.needs_to_act(candidate) {
+                    self.next = Some(candidate);
+                    return;
+                }
+            }
+        }
+        self.end_round();
+    }
+
+    fn needs_to_act(&self, seat: usize) -> bool {
+        let player = &self.seats[seat];
+        if !player.is_active() {
+            return false;
+        }
+        // A lone player who has matched every bet has nobody left to bet against.
+        let highest_other = self
+            .seats
+            .iter()
+            .enumerate()
+            .filter(|&(other, s)| other != seat && !s.folded)
+            .map(|(_, s)| s.street_bet)
+            .max()
+            .unwrap_or(0);
+        let others_active = self
+            .seats
+            .iter()
+            .enumerate()
+            .any(|(other, s)| other != seat && s.is_active());
+        if !others_active && player.street_bet >= highest_other {
+            return false;
+        }
+        !player.acted || player.street_bet < self.current_bet
+    }
+
+    fn end_round(&mut self) {
+        self.next = None;
+        self.return_uncalled_bet();
+
+        let live: Vec<usize> = (0..self.seats.len())
+            .filter(|&seat| !self.seats[seat].folded)
+            .collect();
+        if live.len() == 1 {
+            // Everyone else folded, so the last player wins without showing.
+            let winner = live[0];
+            let pot = [Pot {
+                amount: self.pot(),
+                eligible: vec![winner],
+            }];
+            let rake = self.take_rake(&pot);
+            let amount = rake.apply(&pot)[0].amount;
+            self.seats[winner].stack += amount;
+            self.result = Some(HandResult {
+                awards: vec![Award {
+                    pot: 0,
+                    seat: winner,
+                    amount,
+                }],
+                shown: Vec::new(),
+                rake,
+            });
+            return;
+        }
+
+        for seat in self.seats.iter_mut() {
+            seat.street_bet = 0;
+            seat.acted = false;
+            seat.can_raise = true;
+        }
+        self.current_bet = 0;
+        self.min_raise = self.config.big_blind;
+        self.street = self.street.next();
+        if self.street == Street::Showdown {
+            self.showdown();
+            return;
+        }
+        self.events.push(Event::Deal {
+            street: self.street,
+        });
+        // Postflop, the first player left of the button acts first.
+        self.advance_from(self.button);
+    }
+
+    /// Give back the part of the highest bet that nobody else matched.
+    fn return_uncalled_bet(&mut self) {
+        let mut bets: Vec<(u64, usize)> = self
+            .seats
+            .iter()
+            .enumerate()
+            .map(|(seat, s)| (s.street_bet, seat))
+            .collect();
+        bets.sort_unstable_by(|a, b| b.cmp(a));
+        let (highest, seat) = bets[0];
+        let second = bets.get(1).map_or(0, |&(bet, _)| bet);
+        if highest > second {
+            let amount = highest - second;
+            let player = &mut self.seats[seat];
+            player.stack += amount;
+            player.street_bet -= amount;
+            player.contributed -= amount;
+            self.events.push(Event::ReturnUncalled { seat, amount });
+        }
+    }
+
+    fn showdown(&mut self) {
+        let contributions: Vec<Contribution> = self
+            .seats
+            .iter()
+            .enumerate()
+            .map(|(seat, s)| Contribution::new(seat, s.contributed, s.folded))
+            .collect();
+        let shown: Vec<(usize, PokerHand<'static>)> = self
+            .seats
+            .iter()
+            .enumerate()
+            .filter(|(_, s)| !s.folded)
+            .map(|(seat, s)| {
+                let cards = [&s.hole_cards[..], &self.board[..]].concat();
+                // The cards were checked for duplicates when the hand was set up.
+                (seat, PokerHand::best_of(&cards).unwrap())
+            })
+            .collect();
+        let rule = OddChipRule::LeftOfButton {
+            button: self.button,
+            table_size: self.seats.len(),
+        };
+        let pots = build_pots(&contributions);
+        let rake = self.take_rake(&pots);
+        // Every live player has a hand, so every pot has an eligible winner.
+        let awards = distribute(&rake.apply(&pots), &shown, &rule).unwrap();
+        for award in &awards {
+            self.seats[award.seat].stack += award.amount;
+        }
+        self.result = Some(HandResult {
+            awards,
+            shown,
+            rake,
+        });
+    }
+
+    /// The rake from the pots under the table's rule, if it has one.
+    fn take_rake(&self, pots: &[Pot]) -> Rake {
+        match &self.config.rake {
+            Some(rule) => rule.take(pots, self.seats.len(), self.street != Street::Preflop),
+            None => Rake {
+                pot: pots.iter().map(|pot| pot.amount).sum(),
+                players: self.seats.len(),
+                by_pot: vec![0; pots.len()],
+                ..Rake::default()
+            },
+        }
+    }
+}
+
+fn check_setup(
+    config: &TableConfig,
+    stacks: &[u64],
+    button: usize,
+    hole_cards: &[[Card; 2]],
+    board: &[Card; 5],
+) -> Result<(), TableError> {
+    let invalid = |message: &str| Err(TableError::InvalidSetup(message.to_string()));
+    let players = stacks.len();
+    if players < 2 {
+        return invalid("A hand needs at least two players");
+    }
+    if hole_cards.len() != players {
+        return invalid("Every player needs hole cards");
+    }
+    if button >= players {
+        return invalid("The button must be on one of the seats");
+    }
+    if stacks.contains(&0) {
+        return invalid("Every player needs chips");
+    }
+    if config.big_blind == 0 || config.small_blind > config.big_blind {
+        return invalid("The big blind must be positive and at least the small blind");
+    }
+    if let Some(straddle) = config.straddle {
+        if players < 3 || straddle < config.big_blind {
+            return invalid("A straddle needs three players and must be at least the big blind");
+        }
+    }
+    let mut seen: u64 = 0;
+    for card in hole_cards.iter().flatten().chain(board) {
+        let bit = 1u64 << card.index();
+        if seen & bit != 0 {
+            return invalid("Duplicate cards in the deal");
+        }
+        seen |= bit;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards::parse_cards;
+    use crate::poker_hand::PokerHandRanks;
+
+    /// Deal hole cards to each seat from a hand string, and a five card board.
+    fn deal(hole_cards: &[&str], board: &str) -> (Vec<[Card; 2]>, [Card; 5]) {
+        let hole_cards = hole_cards
+            .iter()
+            .map(|cards| {
+                let cards = parse_cards(cards).unwrap();
+                [cards[0], cards[1]]
+            })
+            .collect();
+        let board = parse_cards(board).unwrap();
+        (
+            hole_cards,
+            [board[0], board[1], board[2], board[3], board[4]],
+        )
+    }
+
+    fn three_handed(config: &TableConfig, stacks: &[u64]) -> TableHand {
+        let (hole_cards, board) = deal(&["AS AD", "KS KD", "7C 2D"], "AH 9C 5S 3D JC");
+        TableHand::new(config, stacks, 0, &hole_cards, board).unwrap()
+    }
+
+    #[test]
+    fn test_blinds_and_preflop_order() {
+        let hand = three_handed(&TableConfig::no_limit(1, 2), &[100, 100, 100]);
+        assert_eq!(hand.seats()[1].street_bet, 1);
+        assert_eq!(hand.seats()[2].street_bet, 2);
+        assert_eq!(hand.pot(), 3);
+        // Under the gun is the button when three handed.
+        let legal = hand.legal_actions().unwrap();
+        assert_eq!(legal.seat, 0);
+        assert_eq!(legal.call, Some(2));
+        assert_eq!(
+            legal.raise,
+            Some(RaiseRange {
+                min_to: 4,
+                max_to: 100
+            })
+        );
+    }
+
+    #[test]
+    fn test_heads_up_button_posts_small_blind_and_acts_first() {
+        let (hole_cards, board) = deal(&["AS AD", "KS KD"], "AH 9C 5S 3D JC");
+        let mut hand = TableHand::new(
+            &TableConfig::no_limit(1, 2),
+            &[50, 50],
+            1,
+            &hole_cards,
+            board,
+        )
+        .unwrap();
+        assert_eq!(hand.seats()[1].street_bet, 1);
+        assert_eq!(hand.to_act(), Some(1));
+        hand.act(1, Action::Call).unwrap();
+        // The big blind has the option.
+        assert_eq!(hand.to_act(), Some(0));
+        hand.act(0, Action::Check).unwrap();
+        // After the flop the big blind acts first.
+        assert_eq!(hand.street(), Street::Flop);
+        assert_eq!(hand.board().len(), 3);
+        assert_eq!(hand.to_act(), Some(0));
+    }
+
+    #[test]
+    fn test_illegal_actions_are_rejected() {
+        let mut hand = three_handed(&TableConfig::no_limit(1, 2), &[100, 100, 100]);
+        assert_eq!(
+            hand.act(1, Action::Call),
+            Err(TableError::OutOfTurn {
+                expected: 0,
+                actual: 1
+            })
+        );
+        assert_eq!(
+            hand.act(0, Action::Check),
+            Err(TableError::CannotCheck { to_call: 2 })
+        );
+        assert_eq!(hand.act(0, Action::Bet(6)), Err(TableError::CannotBet));
+        assert_eq!(
+            hand.act(0, Action::Raise(3)),
+            Err(TableError::BetTooSmall { minimum: 4 })
+        );
+        assert_eq!(
+            hand.act(0, Action::Raise(101)),
+            Err(TableError::BetTooLarge { maximum: 100 })
+        );
+        // Nothing changed.
+        assert_eq!(hand.pot(), 3);
+        hand.act(0, Action::Raise(6)).unwrap();
+        // The next raise must be by at least four more.
+        assert_eq!(
+            hand.act(1, Action::Raise(9)),
+            Err(TableError::BetTooSmall { minimum: 10 })
+        );
+    }
+
+    #[test]
+    fn test_pot_limit_maximum() {
+        let mut hand = three_handed(&TableConfig::pot_limit(1, 2), &[100, 100, 100]);
+        // Calling 2 makes the pot 5, so the pot sized raise is to 7.
+        assert_eq!(hand.legal_actions().unwrap().raise.unwrap().max_to, 7);
+        hand.act(0, Action::Raise(7)).unwrap();
+        // The small blind calls 6 making the pot 16, then raises 16 more to 23.
+        assert_eq!(hand.legal_actions().unwrap().raise.unwrap().max_to, 23);
+    }
+
+    #[test]
+    fn test_everyone_folds_to_the_big_blind() {
+        let mut hand = three_handed(&TableConfig::no_limit(1, 2), &[100, 100, 100]);
+        hand.act(0, Action::Fold).unwrap();
+        hand.act(1, Action::Fold).unwrap();
+        assert!(hand.is_complete());
+        assert_eq!(hand.act(2, Action::Check), Err(TableError::HandComplete));
+        let result = hand.result().unwrap();
+        assert!(result.shown.is_empty());
+        assert_eq!(result.awards[0].amount, 2);
+        assert_eq!(hand.seats()[2].stack, 101);
+        // The unmatched part of the big blind is returned.
+        assert!(hand
+            .events()
+            .contains(&Event::ReturnUncalled { seat: 2, amount: 1 }));
+    }
+
+    #[test]
+    fn test_all_in_runs_out_the_board_with_side_pot() {
+        let mut hand = three_handed(&TableConfig::no_limit(1, 2), &[30, 100, 100]);
+        hand.act(0, Action::Raise(30)).unwrap();
+        hand.act(1, Action::Raise(100)).unwrap();
+        hand.act(2, Action::Call).unwrap();
+        // Nobody can act, so the board is run out.
+        assert!(hand.is_complete());
+        assert_eq!(hand.street(), Street::Showdown);
+        let result = hand.result().unwrap();
+        assert_eq!(result.shown.len(), 3);
+        // Aces make trips and win the main pot; kings win the side pot.
+        let seat_0 = result.shown.iter().find(|(seat, _)| *seat == 0).unwrap();
+        assert!(seat_0.1.hand_rank == PokerHandRanks::ThreeOfAKind);
+        assert_eq!(hand.seats()[0].stack, 90);
+        assert_eq!(hand.seats()[1].stack, 140);
+        assert_eq!(hand.seats()[2].stack, 0);
+    }
+
+    #[test]
+    fn test_short_all_in_does_not_reopen_raising() {
+        let mut hand = three_handed(&TableConfig::no_limit(5, 10), &[200, 200, 45]);
+        hand.act(0, Action::Raise(30)).unwrap();
+        hand.act(1, Action::Call).unwrap();
+        // The big blind goes all in for 45, a raise of only 15.
+        hand.act(2, Action::Raise(45)).unwrap();
+        let legal = hand.legal_actions().unwrap();
+        assert_eq!(legal.seat, 0);
+        assert_eq!(legal.call, Some(15));
+        assert_eq!(legal.raise, None);
+        assert_eq!(
+            hand.act(0, Action::Raise(100)),
+            Err(TableError::CannotRaise)
+        );
+        hand.act(0, Action::Call).unwrap();
+        hand.act(1, Action::Call).unwrap();
+        assert_eq!(hand.street(), Street::Flop);
+        assert_eq!(hand.pot(), 135);
+    }
+
+    #[test]
+    fn test_antes_and_straddle() {
+        let config = TableConfig {
+            ante: 1,
+            straddle: Some(4),
+            ..TableConfig::no_limit(1, 2)
+        };
+        let (hole_cards, board) = deal(&["AS AD", "KS KD", "7C 2D", "8H 8S"], "AH 9C 5S 3D JC");
+        let mut hand = TableHand::new(&config, &[100; 4], 0, &hole_cards, board).unwrap();
+        assert_eq!(hand.pot(), 4 + 1 + 2 + 4);
+        // The straddle is in seat 3, so seat 0 acts first and the straddler acts last.
+        assert_eq!(hand.to_act(), Some(0));
+        assert_eq!(hand.legal_actions().unwrap().call, Some(4));
+        assert_eq!(hand.legal_actions().unwrap().raise.unwrap().min_to, 8);
+        hand.act(0, Action::Call).unwrap();
+        hand.act(1, Action::Fold).unwrap();
+        hand.act(2, Action::Call).unwrap();
+        assert_eq!(hand.to_act(), Some(3));
+        hand.act(3, Action::Check).unwrap();
+        assert_eq!(hand.street(), Street::Flop);
+        // Postflop, a bet must be at least the big blind.
+        assert_eq!(hand.to_act(), Some(2));
+        assert_eq!(hand.act(2, Action::Raise(2)), Err(TableError::CannotRaise));
+        assert_eq!(
+            hand.act(2, Action::Bet(1)),
+            Err(TableError::BetTooSmall { minimum: 2 })
+        );
+        hand.act(2, Action::Bet(2)).unwrap();
+    }
+
+    #[test]
+    fn test_invalid_setup() {
+        let (hole_cards, board) = deal(&["AS AD", "KS KD"], "AS 9C 5S 3D JC");
+        let result = TableHand::new(
+            &TableConfig::no_limit(1, 2),
+            &[50, 50],
+            0,
+            &hole_cards,
+            board,
+        );
+        assert!(matches!(result, Err(TableError::InvalidSetup(_))));
+        let (hole_cards, board) = deal(&["AS AD", "KS KD"], "AH 9C 5S 3D JC");
+        let result = TableHand::new(
+            &TableConfig::no_limit(1, 2),
+            &[50, 50],
+            2,
+            &hole_cards,
+            board,
+        );
+        assert!(matches!(result, Err(TableError::InvalidSetup(_))));
+    }
+
+    #[test]
+    fn test_rake() {
+        let config = TableConfig {
+            rake: Some(RakeRule::new(500).unwrap().with_cap(3, 5)),
+            ..TableConfig::no_limit(1, 2)
+        };
+        // 5% of a pot of 120 is 6, capped at 5 three handed.
+        let mut hand = three_handed(&config, &[100, 100, 100]);
+        hand.act(0, Action::Raise(40)).unwrap();
+        hand.act(1, Action::Call).unwrap();
+        hand.act(2, Action::Call).unwrap();
+        for _ in 0..3 {
+            for seat in [1, 2, 0] {
+                hand.act(seat, Action::Check).unwrap();
+            }
+        }
+        let result = hand.result().unwrap();
+        assert_eq!(result.rake.total(), 5);
+        assert_eq!(result.rake.uncapped, 6);
+        assert_eq!(result.awards[0].amount, 115);
+        assert_eq!(hand.seats()[0].stack, 175);
+
+        // No flop, no drop.
+        let mut hand = three_handed(&config, &[100, 100, 100]);
+        hand.act(0, Action::Raise(6)).unwrap();
+        hand.act(1, Action::Fold).unwrap();
+        hand.act(2, Action::Fold).unwrap();
+        let result = hand.result().unwrap();
+        assert!(result.rake.waived);
+        assert_eq!(hand.seats()[0].stack, 103);
+    }
+}
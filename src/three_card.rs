@@ -0,0 +1,280 @@
+//! Three Card Poker, the casino table game.
+//!
+//! Hands have three cards, and because a straight is harder to make than a flush with
+//! three cards, the hand types are ordered differently from five card poker: a straight
+//! beats a flush, and three of a kind beats a straight. The ace can be high or low in a
+//! straight, so A-2-3 is the lowest straight and Q-K-A the highest.
+//!
+//! The player bets an ante and then either folds or makes a play bet equal to the ante.
+//! The dealer qualifies with queen high or better. If the dealer does not qualify, the
+//! ante wins and the play bet is returned. Otherwise the higher hand wins both bets. The
+//! ante bonus pays for a straight or better whatever the dealer holds, and the optional
+//! pair plus bet pays for a pair or better whether or not the player plays.
+
+use crate::cards::{parse_cards, Card, Ranks};
+use crate::error::PokerHandError;
+use std::cmp::Ordering;
+
+/// Three card hand types, in the order of their relative value.
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Copy, Clone, Hash)]
+pub enum ThreeCardRanks {
+    HighCard,
+    Pair,
+    Flush,
+    Straight,
+    ThreeOfAKind,
+    StraightFlush,
+}
+
+/// A three card hand.
+///
+/// Like `PokerHand`, hands compare equal when they have the same value, whatever the suits.
+#[derive(Debug, Clone)]
+pub struct ThreeCardHand {
+    pub hand_rank: ThreeCardRanks,
+    /// The cards in scoring order, as for `PokerHand`: a pair comes before the kicker,
+    /// and the ace of an A-2-3 straight comes last.
+    pub cards: [Card; 3],
+}
+
+impl ThreeCardHand {
+    /// Parse a hand such as "QH 10S 4D".
+    pub fn new(hand: &str) -> Result<ThreeCardHand, PokerHandError> {
+        ThreeCardHand::from_cards(&parse_cards(hand)?)
+    }
+
+    pub fn from_cards(cards: &[Card]) -> Result<ThreeCardHand, PokerHandError> {
+        if cards.len() != 3 {
+            return Err(PokerHandError::new(
+                "A three card hand must have three cards",
+            ));
+        }
+        if cards[0] == cards[1] || cards[0] == cards[2] || cards[1] == cards[2] {
+            return Err(PokerHandError::new("Duplicate cards in hand"));
+        }
+        let mut cards = [cards[0], cards[1], cards[2]];
+        cards.sort_by_key(|card| std::cmp::Reverse(card.rank));
+        let [high, middle, low] = cards.map(|card| card.rank as u8);
+
+        let flush = cards.iter().all(|card| card.suit == cards[0].suit);
+        let wheel = [high, middle, low] == [Ranks::Ace as u8, 3, 2];
+        let straight = (high == middle + 1 && middle == low + 1) || wheel;
+        if wheel {
+            cards.rotate_left(1);
+        }
+        let hand_rank = if straight && flush {
+            ThreeCardRanks::StraightFlush
+        } else if high == low {
+            ThreeCardRanks::ThreeOfAKind
+        } else if straight {
+            ThreeCardRanks::Straight
+        } else if flush {
+            ThreeCardRanks::Flush
+        } else if high == middle || middle == low {
+            if middle == low {
+                cards.rotate_left(1);
+            }
+            ThreeCardRanks::Pair
+        } else {
+            ThreeCardRanks::HighCard
+        };
+        Ok(ThreeCardHand { hand_rank, cards })
+    }
+
+    /// Whether a dealer holding this hand qualifies: queen high or better.
+    pub fn dealer_qualifies(&self) -> bool {
+        self.hand_rank > ThreeCardRanks::HighCard || self.cards[0].rank >= Ranks::Queen
+    }
+}
+
+impl Ord for ThreeCardHand {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let ranks = |hand: &ThreeCardHand| hand.cards.map(|card| card.rank);
+        self.hand_rank
+            .cmp(&other.hand_rank)
+            .then_with(|| ranks(self).cmp(&ranks(other)))
+    }
+}
+
+impl PartialEq for ThreeCardHand {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for ThreeCardHand {}
+
+impl PartialOrd for ThreeCardHand {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// The ante bonus and pair plus pays, as multiples of the bet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThreeCardPaytable {
+    pub ante_bonus: Vec<(ThreeCardRanks, u64)>,
+    pub pair_plus: Vec<(ThreeCardRanks, u64)>,
+}
+
+impl ThreeCardPaytable {
+    /// The most common paytable: ante bonus 5/4/1 and pair plus 40/30/6/3/1.
+    pub fn standard() -> ThreeCardPaytable {
+        ThreeCardPaytable {
+            ante_bonus: vec![
+                (ThreeCardRanks::StraightFlush, 5),
+                (ThreeCardRanks::ThreeOfAKind, 4),
+                (ThreeCardRanks::Straight, 1),
+            ],
+            pair_plus: vec![
+                (ThreeCardRanks::StraightFlush, 40),
+                (ThreeCardRanks::ThreeOfAKind, 30),
+                (ThreeCardRanks::Straight, 6),
+                (ThreeCardRanks::Flush, 3),
+                (ThreeCardRanks::Pair, 1),
+            ],
+        }
+    }
+}
+
+fn pay_for(pays: &[(ThreeCardRanks, u64)], rank: ThreeCardRanks) -> u64 {
+    pays.iter()
+        .find(|(r, _)| *r == rank)
+        .map_or(0, |(_, pay)| *pay)
+}
+
+/// The player's bets on one hand. The play bet, if made, equals the ante.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ThreeCardBets {
+    pub ante: u64,
+    pub pair_plus: u64,
+    /// Whether the player made the play bet rather than folding.
+    pub play: bool,
+}
+
+/// The player's win (positive) or loss (negative) on each bet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ThreeCardResult {
+    pub ante: i64,
+    pub play: i64,
+    pub ante_bonus: i64,
+    pub pair_plus: i64,
+}
+
+impl ThreeCardResult {
+    pub fn total(&self) -> i64 {
+        self.ante + self.play + self.ante_bonus + self.pair_plus
+    }
+}
+
+/// Settle the player's bets against the dealer's hand.
+pub fn settle(
+    player: &ThreeCardHand,
+    dealer: &ThreeCardHand,
+    bets: &ThreeCardBets,
+    paytable: &ThreeCardPaytable,
+) -> ThreeCardResult {
+    let ante = bets.ante as i64;
+    let mut result = ThreeCardResult::default();
+    if bets.pair_plus > 0 {
+        let pay = pay_for(&paytable.pair_plus, player.hand_rank);
+        result.pair_plus = if pay > 0 {
+            (bets.pair_plus * pay) as i64
+        } else {
+            -(bets.pair_plus as i64)
+        };
+    }
+    if !bets.play {
+        result.ante = -ante;
+        return result;
+    }
+    result.ante_bonus = (bets.ante * pay_for(&paytable.ante_bonus, player.hand_rank)) as i64;
+    if !dealer.dealer_qualifies() {
+        result.ante = ante;
+        return result;
+    }
+    let outcome = match player.cmp(dealer) {
+        Ordering::Greater => ante,
+        Ordering::Equal => 0,
+        Ordering::Less => -ante,
+    };
+    result.ante = outcome;
+    result.play = outcome;
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hand(cards: &str) -> ThreeCardHand {
+        ThreeCardHand::new(cards).unwrap()
+    }
+
+    #[test]
+    fn test_hand_ranks() {
+        assert_eq!(hand("QH KH AH").hand_rank, ThreeCardRanks::StraightFlush);
+        assert_eq!(hand("7H 7S 7D").hand_rank, ThreeCardRanks::ThreeOfAKind);
+        assert_eq!(hand("AH 2S 3D").hand_rank, ThreeCardRanks::Straight);
+        assert_eq!(hand("AH 5H 9H").hand_rank, ThreeCardRanks::Flush);
+        assert_eq!(hand("4C 9H 4D").hand_rank, ThreeCardRanks::Pair);
+        assert_eq!(hand("KC 9H 4D").hand_rank, ThreeCardRanks::HighCard);
+        assert!(ThreeCardHand::new("KC 9H").is_err());
+        assert!(ThreeCardHand::new("KC 9H 9H").is_err());
+    }
+
+    #[test]
+    fn test_ordering() {
+        // A straight beats a flush, and trips beat a straight.
+        assert!(hand("4C 5H 6D") > hand("AH 5H 9H"));
+        assert!(hand("2C 2H 2D") > hand("QC KH AD"));
+        // A-2-3 is the lowest straight.
+        assert!(hand("AH 2S 3D") < hand("2C 3H 4D"));
+        assert!(hand("QC KH AD") > hand("JC QH KD"));
+        // Pairs compare on the pair, then the kicker.
+        assert!(hand("9C 9H 2D") > hand("8C 8H AD"));
+        assert!(hand("9C 9H 5D") > hand("9S 9D 4D"));
+        assert_eq!(hand("KC 9H 4D"), hand("KD 9S 4H"));
+    }
+
+    #[test]
+    fn test_dealer_qualification() {
+        assert!(hand("QC 5H 2D").dealer_qualifies());
+        assert!(!hand("JC 10H 8D").dealer_qualifies());
+        assert!(hand("2C 2H 3D").dealer_qualifies());
+    }
+
+    #[test]
+    fn test_settlement() {
+        let paytable = ThreeCardPaytable::standard();
+        let bets = ThreeCardBets {
+            ante: 10,
+            pair_plus: 5,
+            play: true,
+        };
+        // A straight beats a qualifying dealer, and collects the ante bonus and pair plus.
+        let result = settle(&hand("4C 5H 6D"), &hand("KC KH 2D"), &bets, &paytable);
+        assert_eq!(
+            result,
+            ThreeCardResult {
+                ante: 10,
+                play: 10,
+                ante_bonus: 10,
+                pair_plus: 30,
+            }
+        );
+        // The dealer does not qualify: the ante wins and the play bet is returned.
+        let result = settle(&hand("2C 5H 9D"), &hand("JC 10H 8D"), &bets, &paytable);
+        assert_eq!((result.ante, result.play, result.pair_plus), (10, 0, -5));
+        // The dealer wins.
+        let result = settle(&hand("2C 5H 9D"), &hand("QC 10H 8D"), &bets, &paytable);
+        assert_eq!(result.total(), -25);
+        // Folding loses the ante, but the pair plus bet still pays.
+        let fold = ThreeCardBets {
+            play: false,
+            ..bets
+        };
+        let result = settle(&hand("2C 2H 9D"), &hand("QC 10H 8D"), &fold, &paytable);
+        assert_eq!(result.total(), -10 + 5);
+    }
+}
@@ -0,0 +1,799 @@
+//! Sit-and-go and multi-table tournaments.
+//!
+//! A `Tournament` seats its entrants at tables of a fixed size and tracks their stacks
+//! from hand to hand. The blinds and antes rise through a schedule of levels, each lasting
+//! a number of hands counted across all the tables, as a stand-in for a clock. Hands are
+//! played with `TableHand`, so pots and side pots are awarded to the best hands by the
+//! table engine's tiered ranking.
+//!
+//! Players who lose all their chips are eliminated. When several players are eliminated
+//! in the same hand, the player who started the hand with more chips finishes higher, and
+//! players who started it with the same stack tie for the best of their places and split
+//! the prizes for the places they share. After every hand the tables are balanced: a
+//! table is broken whenever the players left fit at one fewer table, and players move
+//! from the fullest table to the emptiest until they differ by at most one. The player
+//! who would be the next big blind is the one moved, as in a card room. The last player
+//! left wins, and the prize pool is paid out by a `PayoutStructure`.
+
+use crate::cards::Card;
+use crate::error::{PokerHandError, TableError};
+use crate::rng::SeededRng;
+use crate::simulator::{play_out, Agent};
+use crate::table::{TableConfig, TableHand};
+
+/// The blinds and ante for a level, and the number of hands it lasts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlindLevel {
+    pub small_blind: u64,
+    pub big_blind: u64,
+    pub ante: u64,
+    pub hands: usize,
+}
+
+/// The shares of the prize pool paid to the top places.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PayoutStructure {
+    percentages: Vec<f64>,
+}
+
+impl PayoutStructure {
+    /// The percentage of the prize pool paid to each place, from first place down. The
+    /// percentages must add up to 100 and may not increase.
+    pub fn new(percentages: &[f64]) -> Result<PayoutStructure, PokerHandError> {
+        if percentages.is_empty() || percentages.iter().any(|p| p.is_nan() || *p <= 0.0) {
+            return Err(PokerHandError::new("Payouts must be positive"));
+        }
+        if percentages.windows(2).any(|pair| pair[1] > pair[0]) {
+            return Err(PokerHandError::new(
+                "Payouts may not increase down the places",
+            ));
+        }
+        if (percentages.iter().sum::<f64>() - 100.0).abs() > 1e-9 {
+            return Err(PokerHandError::new("Payouts must add up to 100%"));
+        }
+        Ok(PayoutStructure {
+            percentages: percentages.to_vec(),
+        })
+    }
+
+    /// The usual sit-and-go payouts: winner takes all heads up and with up to five
+    /// players, 65/35 with six to eight, and 50/30/20 with nine or more.
+    pub fn sit_and_go(entrants: usize) -> PayoutStructure {
+        let percentages: &[f64] = match entrants {
+            0..=5 => &[100.0],
+            6..=8 => &[65.0, 35.0],
+            _ => &[50.0, 30.0, 20.0],
+        };
+        PayoutStructure {
+            percentages: percentages.to_vec(),
+        }
+    }
+
+    /// The number of places paid.
+    pub fn places_paid(&self) -> usize {
+        self.percentages.len()
+    }
+
+    /// The prize for each place paid, from first place down. Chips lost to rounding down
+    /// go to first place, so the prizes add up to the prize pool.
+    pub fn prizes(&self, prize_pool: u64) -> Vec<u64> {
+        let mut prizes: Vec<u64> = self
+            .percentages
+            .iter()
+            .map(|percentage| (prize_pool as f64 * percentage / 100.0).floor() as u64)
+            .collect();
+        let paid: u64 = prizes.iter().sum();
+        prizes[0] += prize_pool.saturating_sub(paid);
+        prizes
+    }
+}
+
+/// A player knocked out of the tournament.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Elimination {
+    pub player: usize,
+    /// The finishing place, shared by players who tied.
+    pub place: usize,
+}
+
+/// A player moved to another table to break or balance the tables.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Move {
+    pub player: usize,
+    pub from_table: usize,
+    pub to_table: usize,
+}
+
+/// What happened to the field after a hand.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HandOutcome {
+    /// The players eliminated, best place first.
+    pub eliminations: Vec<Elimination>,
+    pub moves: Vec<Move>,
+    /// The tables broken.
+    pub broken_tables: Vec<usize>,
+}
+
+/// A player's finishing place and prize.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Standing {
+    pub player: usize,
+    pub place: usize,
+    pub prize: u64,
+}
+
+#[derive(Debug, Clone)]
+struct Table {
+    /// The player in each seat.
+    seats: Vec<Option<usize>>,
+    /// The button's seat.
+    button: usize,
+    broken: bool,
+    /// The players in the hand being played, in seat order.
+    in_hand: Option<Vec<usize>>,
+}
+
+impl Table {
+    fn players(&self) -> Vec<usize> {
+        self.seats.iter().flatten().copied().collect()
+    }
+
+    fn count(&self) -> usize {
+        self.seats.iter().flatten().count()
+    }
+
+    /// The occupied seats, in order round the table from the seat after `from`.
+    fn occupied_after(&self, from: usize) -> Vec<usize> {
+        let size = self.seats.len();
+        (1..=size)
+            .map(|offset| (from + offset) % size)
+            .filter(|&seat| self.seats[seat].is_some())
+            .collect()
+    }
+}
+
+/// A tournament in progress.
+#[derive(Debug, Clone)]
+pub struct Tournament {
+    schedule: Vec<BlindLevel>,
+    payouts: PayoutStructure,
+    prize_pool: u64,
+    stacks: Vec<u64>,
+    places: Vec<Option<usize>>,
+    tables: Vec<Table>,
+    level: usize,
+    hands_in_level: usize,
+}
+
+impl Tournament {
+    /// Seat the entrants, numbered from 0, round robin at as few tables of `table_size`
+    /// seats as will hold them, each with the starting stack.
+    pub fn new(
+        entrants: usize,
+        starting_stack: u64,
+        table_size: usize,
+        schedule: &[BlindLevel],
+        payouts: PayoutStructure,
+        prize_pool: u64,
+    ) -> Result<Tournament, TableError> {
+        let invalid = |message: &str| Err(TableError::InvalidSetup(message.to_string()));
+        if entrants < 2 {
+            return invalid("A tournament needs at least two entrants");
+        }
+        if !(2..=23).contains(&table_size) {
+            return invalid("Tables must seat 2 to 23 players");
+        }
+        if starting_stack == 0 {
+            return invalid("Every player needs chips");
+        }
+        if schedule.is_empty()
+            || schedule
+                .iter()
+                .any(|level| level.big_blind == 0 || level.small_blind > level.big_blind)
+        {
+            return invalid("Every level needs a big blind of at least the small blind");
+        }
+        if payouts.places_paid() > entrants {
+            return invalid("More places are paid than there are entrants");
+        }
+        let table_count = (entrants + table_size - 1) / table_size;
+        let mut tables = vec![
+            Table {
+                seats: vec![None; table_size],
+                button: 0,
+                broken: false,
+                in_hand: None,
+            };
+            table_count
+        ];
+        for player in 0..entrants {
+            tables[player % table_count].seats[player / table_count] = Some(player);
+        }
+        Ok(Tournament {
+            schedule: schedule.to_vec(),
+            payouts,
+            prize_pool,
+            stacks: vec![starting_stack; entrants],
+            places: vec![None; entrants],
+            tables,
+            level: 0,
+            hands_in_level: 0,
+        })
+    }
+
+    /// The current level. The last level of the schedule lasts until the end.
+    pub fn level(&self) -> BlindLevel {
+        self.schedule[self.level]
+    }
+
+    /// The index of the current level in the schedule.
+    pub fn level_index(&self) -> usize {
+        self.level
+    }
+
+    /// The tables still in play.
+    pub fn tables(&self) -> Vec<usize> {
+        (0..self.tables.len())
+            .filter(|&table| !self.tables[table].broken)
+            .collect()
+    }
+
+    /// The players at a table, in seat order.
+    pub fn players_at(&self, table: usize) -> Vec<usize> {
+        self.tables
+            .get(table)
+            .map(Table::players)
+            .unwrap_or_default()
+    }
+
+    /// A player's chips.
+    pub fn stack(&self, player: usize) -> u64 {
+        self.stacks[player]
+    }
+
+    /// The number of players still in the tournament.
+    pub fn remaining(&self) -> usize {
+        self.stacks.iter().filter(|&&stack| stack > 0).count()
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.remaining() == 1
+    }
+
+    /// Start a hand at a table at the current level. The hole cards are given for the
+    /// players in the order of `players_at`, which is also the order of the hand's seats.
+    pub fn start_hand(
+        &mut self,
+        table: usize,
+        hole_cards: &[[Card; 2]],
+        board: [Card; 5],
+    ) -> Result<TableHand, TableError> {
+        let invalid = |message: &str| Err(TableError::InvalidSetup(message.to_string()));
+        if self.is_complete() {
+            return Err(TableError::HandComplete);
+        }
+        match self.tables.get(table) {
+            None => return invalid("There is no such table"),
+            Some(table) if table.broken => return invalid("The table has been broken"),
+            Some(table) if table.in_hand.is_some() => {
+                return invalid("A hand is already being played at the table")
+            }
+            Some(_) => {}
+        }
+        let seating = &self.tables[table];
+        let players = seating.players();
+        if players.len() < 2 {
+            return invalid("A hand needs at least two players");
+        }
+        // The button is on the first occupied seat at or after the button's seat.
+        let button_seat = seating.occupied_after(seating.button + seating.seats.len() - 1)[0];
+        let button = players
+            .iter()
+            .position(|&player| seating.seats[button_seat] == Some(player))
+            .unwrap_or(0);
+        let level = self.level();
+        let config = TableConfig {
+            ante: level.ante,
+            ..TableConfig::no_limit(level.small_blind, level.big_blind)
+        };
+        let stacks: Vec<u64> = players.iter().map(|&player| self.stacks[player]).collect();
+        let hand = TableHand::new(&config, &stacks, button, hole_cards, board)?;
+        self.tables[table].button = button_seat;
+        self.tables[table].in_hand = Some(players);
+        Ok(hand)
+    }
+
+    /// Record the end of a hand started with `start_hand`: update the stacks, eliminate
+    /// the players who went broke, balance the tables and move the blinds on.
+    pub fn finish_hand(
+        &mut self,
+        table: usize,
+        hand: &TableHand,
+    ) -> Result<HandOutcome, TableError> {
+        let players = match self.tables.get(table).and_then(|t| t.in_hand.clone()) {
+            Some(players) if hand.is_complete() && hand.seats().len() == players.len() => players,
+            _ => {
+                return Err(TableError::InvalidSetup(
+                    "The hand is not a completed hand from the table".to_string(),
+                ))
+            }
+        };
+        self.tables[table].in_hand = None;
+        for (seat, &player) in players.iter().enumerate() {
+            self.stacks[player] = hand.seats()[seat].stack;
+        }
+
+        let mut outcome = HandOutcome {
+            eliminations: self.eliminate(&players, hand.starting_stacks()),
+            ..HandOutcome::default()
+        };
+        for elimination in &outcome.eliminations {
+            for seat in self.tables[table].seats.iter_mut() {
+                if *seat == Some(elimination.player) {
+                    *seat = None;
+                }
+            }
+        }
+        if self.is_complete() {
+            if let Some(winner) = self.stacks.iter().position(|&stack| stack > 0) {
+                self.places[winner] = Some(1);
+            }
+        }
+
+        let seating = &self.tables[table];
+        if let Some(&next) = seating.occupied_after(seating.button).first() {
+            self.tables[table].button = next;
+        }
+        self.balance(&mut outcome);
+
+        self.hands_in_level += 1;
+        if self.hands_in_level >= self.level().hands && self.level + 1 < self.schedule.len() {
+            self.level += 1;
+            self.hands_in_level = 0;
+        }
+        Ok(outcome)
+    }
+
+    /// Give the players who went broke in a hand their places, best first.
+    fn eliminate(&mut self, players: &[usize], starting_stacks: &[u64]) -> Vec<Elimination> {
+        let mut busted: Vec<(usize, u64)> = players
+            .iter()
+            .zip(starting_stacks)
+            .filter(|&(&player, _)| self.stacks[player] == 0)
+            .map(|(&player, &stack)| (player, stack))
+            .collect();
+        busted.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        // Everyone left after the hand finishes above the players who went broke.
+        let survivors = self.remaining();
+        let mut eliminations = Vec::new();
+        for (i, &(player, stack)) in busted.iter().enumerate() {
+            let tied_above = busted[..i].iter().filter(|&&(_, s)| s == stack).count();
+            let place = survivors + 1 + i - tied_above;
+            self.places[player] = Some(place);
+            eliminations.push(Elimination { player, place });
+        }
+        eliminations
+    }
+
+    /// Break tables while the players fit at fewer, then even out the tables, leaving
+    /// alone tables with a hand in progress.
+    fn balance(&mut self, outcome: &mut HandOutcome) {
+        let table_size = self.tables[0].seats.len();
+        loop {
+            let open = self.tables();
+            let remaining = self.remaining();
+            if open.len() < 2 || remaining > (open.len() - 1) * table_size {
+                break;
+            }
+            // Break the table with the fewest players, the last of those tied.
+            let breaking = open
+                .iter()
+                .copied()
+                .filter(|&table| self.tables[table].in_hand.is_none())
+                .min_by_key(|&table| (self.tables[table].count(), std::cmp::Reverse(table)));
+            let breaking = match breaking {
+                Some(table) => table,
+                None => break,
+            };
+            let players = self.tables[breaking].players();
+            self.tables[breaking].broken = true;
+            self.tables[breaking]
+                .seats
+                .iter_mut()
+                .for_each(|seat| *seat = None);
+            outcome.broken_tables.push(breaking);
+            for player in players {
+                let to_table = self
+                    .tables()
+                    .into_iter()
+                    .filter(|&table| self.tables[table].count() < table_size)
+                    .min_by_key(|&table| (self.tables[table].count(), table));
+                if let Some(to_table) = to_table {
+                    self.seat(player, to_table);
+                    outcome.moves.push(Move {
+                        player,
+                        from_table: breaking,
+                        to_table,
+                    });
+                }
+            }
+        }
+        loop {
+            let idle: Vec<usize> = self
+                .tables()
+                .into_iter()
+                .filter(|&table| self.tables[table].in_hand.is_none())
+                .collect();
+            let fullest = idle
+                .iter()
+                .copied()
+                .max_by_key(|&table| (self.tables[table].count(), std::cmp::Reverse(table)));
+            let emptiest = idle
+                .iter()
+                .copied()
+                .min_by_key(|&table| (self.tables[table].count(), table));
+            let (from_table, to_table) = match (fullest, emptiest) {
+                (Some(from), Some(to))
+                    if self.tables[from].count() > self.tables[to].count() + 1 =>
+                {
+                    (from, to)
+                }
+                _ => break,
+            };
+            // The player two seats after the button is the next big blind.
+            let from = &self.tables[from_table];
+            let occupied = from.occupied_after(from.button);
+            let seat = occupied[1.min(occupied.len() - 1)];
+            let player = from.seats[seat].unwrap_or_default();
+            self.tables[from_table].seats[seat] = None;
+            self.seat(player, to_table);
+            outcome.moves.push(Move {
+                player,
+                from_table,
+                to_table,
+            });
+        }
+    }
+
+    /// Put a player in the first empty seat at a table.
+    fn seat(&mut self, player: usize, table: usize) {
+        if let Some(seat) = self.tables[table]
+            .seats
+            .iter_mut()
+            .find(|seat| seat.is_none())
+        {
+            *seat = Some(player);
+        }
+    }
+
+    /// The finishing places and prizes of the players eliminated so far, and of the
+    /// winner once the tournament is complete, best place first. Players who tie split
+    /// the prizes for the places they share, with any chips left over going one each to
+    /// the tied players in order.
+    pub fn standings(&self) -> Vec<Standing> {
+        let prizes = self.payouts.prizes(self.prize_pool);
+        let prize = |place: usize| prizes.get(place - 1).copied().unwrap_or(0);
+        let mut standings: Vec<Standing> = self
+            .places
+            .iter()
+            .enumerate()
+            .filter_map(|(player, place)| place.map(|place| (player, place)))
+            .map(|(player, place)| {
+                let tied = self.places.iter().filter(|&&p| p == Some(place)).count();
+                let shared: u64 = (place..place + tied).map(prize).sum();
+                // The players before this one who tied for the same place.
+                let ahead = self.places[..player]
+                    .iter()
+                    .filter(|&&p| p == Some(place))
+                    .count() as u64;
+                let odd_chip = u64::from(ahead < shared % tied as u64);
+                Standing {
+                    player,
+                    place,
+                    prize: shared / tied as u64 + odd_chip,
+                }
+            })
+            .collect();
+        standings.sort_by_key(|standing| (standing.place, standing.player));
+        standings
+    }
+
+    /// Play the tournament to the end, dealing from a seeded shuffle, with each player's
+    /// actions chosen by their agent, indexed by player. The tables take turns to play a
+    /// hand.
+    pub fn play(
+        &mut self,
+        agents: &mut [Box<dyn Agent>],
+        seed: u64,
+    ) -> Result<Vec<Standing>, TableError> {
+        if agents.len() != self.stacks.len() {
+            return Err(TableError::InvalidSetup(
+                "Every player needs an agent".to_string(),
+            ));
+        }
+        let mut rng = SeededRng::new(seed);
+        while !self.is_complete() {
+            for table in self.tables() {
+                if self.is_complete() || self.tables[table].broken {
+                    continue;
+                }
+                let players = self.players_at(table);
+                if players.len() < 2 {
+                    continue;
+                }
+                let mut deck: Vec<Card> = (0..52).filter_map(Card::from_index).collect();
+                rng.shuffle_prefix(&mut deck, 2 * players.len() + 5);
+                let hole_cards: Vec<[Card; 2]> = (0..players.len())
+                    .map(|seat| [deck[2 * seat], deck[2 * seat + 1]])
+                    .collect();
+                let mut board = [deck[0]; 5];
+                board.copy_from_slice(&deck[2 * players.len()..2 * players.len() + 5]);
+                let mut hand = self.start_hand(table, &hole_cards, board)?;
+
+                let mut by_player: Vec<Option<&mut Box<dyn Agent>>> =
+                    agents.iter_mut().map(Some).collect();
+                let mut seated: Vec<&mut dyn Agent> = players
+                    .iter()
+                    .filter_map(|&player| by_player[player].take())
+                    .map(|agent| agent.as_mut() as &mut dyn Agent)
+                    .collect();
+                play_out(&mut hand, &hole_cards, &mut seated)?;
+                self.finish_hand(table, &hand)?;
+            }
+        }
+        Ok(self.standings())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards::parse_cards;
+    use crate::simulator::{CallingStation, Observation};
+    use crate::table::Action;
+
+    /// Moves all in whenever it can, and calls otherwise.
+    struct Shover;
+
+    impl Agent for Shover {
+        fn act(&mut self, observation: &Observation) -> Action {
+            let legal = &observation.legal;
+            match legal.raise {
+                Some(range) if legal.facing_bet => Action::Raise(range.max_to),
+                Some(range) => Action::Bet(range.max_to),
+                None => CallingStation.act(observation),
+            }
+        }
+    }
+
+    const LEVELS: [BlindLevel; 2] = [
+        BlindLevel {
+            small_blind: 10,
+            big_blind: 20,
+            ante: 0,
+            hands: 2,
+        },
+        BlindLevel {
+            small_blind: 20,
+            big_blind: 40,
+            ante: 5,
+            hands: 2,
+        },
+    ];
+
+    fn deal(cards: &str) -> (Vec<[Card; 2]>, [Card; 5]) {
+        let cards = parse_cards(cards).unwrap();
+        let hole_cards = cards[5..]
+            .chunks(2)
+            .map(|pair| [pair[0], pair[1]])
+            .collect();
+        let mut board = [cards[0]; 5];
+        board.copy_from_slice(&cards[..5]);
+        (hole_cards, board)
+    }
+
+    fn play(tournament: &mut Tournament, cards: &str, actions: &[(usize, Action)]) -> HandOutcome {
+        let (hole_cards, board) = deal(cards);
+        let mut hand = tournament.start_hand(0, &hole_cards, board).unwrap();
+        for &(seat, action) in actions {
+            hand.act(seat, action).unwrap();
+        }
+        tournament.finish_hand(0, &hand).unwrap()
+    }
+
+    #[test]
+    fn test_payouts() {
+        let payouts = PayoutStructure::new(&[50.0, 30.0, 20.0]).unwrap();
+        assert_eq!(payouts.prizes(1000), vec![500, 300, 200]);
+        let payouts = PayoutStructure::new(&[65.0, 35.0]).unwrap();
+        assert_eq!(payouts.prizes(101), vec![66, 35]);
+        assert_eq!(PayoutStructure::sit_and_go(9).places_paid(), 3);
+        assert!(PayoutStructure::new(&[40.0, 60.0]).is_err());
+        assert!(PayoutStructure::new(&[50.0, 30.0]).is_err());
+        assert!(PayoutStructure::new(&[]).is_err());
+    }
+
+    #[test]
+    fn test_seating_and_levels() {
+        let payouts = PayoutStructure::sit_and_go(20);
+        let tournament = Tournament::new(20, 1500, 9, &LEVELS, payouts.clone(), 2000).unwrap();
+        let counts: Vec<usize> = tournament
+            .tables()
+            .into_iter()
+            .map(|table| tournament.players_at(table).len())
+            .collect();
+        assert_eq!(counts, vec![7, 7, 6]);
+        assert_eq!(tournament.level(), LEVELS[0]);
+        assert!(Tournament::new(1, 1500, 9, &LEVELS, payouts.clone(), 0).is_err());
+        assert!(Tournament::new(20, 1500, 9, &[], payouts, 0).is_err());
+    }
+
+    #[test]
+    fn test_eliminations_in_the_same_hand_ordered_by_starting_stack() {
+        let payouts = PayoutStructure::new(&[70.0, 30.0]).unwrap();
+        let mut tournament = Tournament::new(3, 100, 9, &LEVELS, payouts, 1000).unwrap();
+
+        // Player 0 on the button raises and the blinds fold, leaving stacks of 130, 90
+        // and 80.
+        let outcome = play(
+            &mut tournament,
+            "KS QD 9C 5H 4S 2C 3D 6C 8D JC 10D",
+            &[(0, Action::Raise(40)), (1, Action::Fold), (2, Action::Fold)],
+        );
+        assert!(outcome.eliminations.is_empty());
+        assert_eq!(
+            [0, 1, 2].map(|player| tournament.stack(player)),
+            [130, 90, 80]
+        );
+
+        // Player 1 on the button moves in, and player 2 in the small blind and player 0 in
+        // the big blind call. Player 0's aces win both pots.
+        let outcome = play(
+            &mut tournament,
+            "KS QD 9C 5H 4S AH AD 7C 2D 8S 3H",
+            &[(1, Action::Raise(90)), (2, Action::Call), (0, Action::Call)],
+        );
+        // Player 1 started the hand with more chips than player 2, so finishes higher.
+        assert_eq!(
+            outcome.eliminations,
+            vec![
+                Elimination {
+                    player: 1,
+                    place: 2
+                },
+                Elimination {
+                    player: 2,
+                    place: 3
+                },
+            ]
+        );
+        assert!(tournament.is_complete());
+        assert_eq!(tournament.stack(0), 300);
+        let prizes: Vec<(usize, usize, u64)> = tournament
+            .standings()
+            .iter()
+            .map(|standing| (standing.player, standing.place, standing.prize))
+            .collect();
+        assert_eq!(prizes, vec![(0, 1, 700), (1, 2, 300), (2, 3, 0)]);
+        assert!(tournament
+            .start_hand(0, &[], deal("KS QD 9C 5H 4S").1)
+            .is_err());
+    }
+
+    #[test]
+    fn test_tied_eliminations_split_the_prizes() {
+        let payouts = PayoutStructure::new(&[50.0, 30.0, 20.0]).unwrap();
+        let mut tournament = Tournament::new(3, 100, 9, &LEVELS, payouts, 1000).unwrap();
+        let outcome = play(
+            &mut tournament,
+            "KS QD 9C 5H 4S AH AD 7C 2D 8S 3H",
+            &[
+                (0, Action::Raise(100)),
+                (1, Action::Call),
+                (2, Action::Call),
+            ],
+        );
+        let places: Vec<usize> = outcome.eliminations.iter().map(|e| e.place).collect();
+        assert_eq!(places, vec![2, 2]);
+        let prizes: Vec<u64> = tournament.standings().iter().map(|s| s.prize).collect();
+        assert_eq!(prizes, vec![500, 250, 250]);
+
+        // Prizes of 303 and 202 split into 253 and 252, so the prize pool is paid in full.
+        let payouts = PayoutStructure::new(&[50.0, 30.0, 20.0]).unwrap();
+        let mut tournament = Tournament::new(3, 100, 9, &LEVELS, payouts, 1010).unwrap();
+        play(
+            &mut tournament,
+            "KS QD 9C 5H 4S AH AD 7C 2D 8S 3H",
+            &[
+                (0, Action::Raise(100)),
+                (1, Action::Call),
+                (2, Action::Call),
+            ],
+        );
+        let prizes: Vec<(usize, u64)> = tournament
+            .standings()
+            .iter()
+            .map(|standing| (standing.player, standing.prize))
+            .collect();
+        assert_eq!(prizes, vec![(0, 505), (1, 253), (2, 252)]);
+    }
+
+    #[test]
+    fn test_levels_rise_and_the_button_moves() {
+        let payouts = PayoutStructure::sit_and_go(3);
+        let mut tournament = Tournament::new(3, 1000, 9, &LEVELS, payouts, 0).unwrap();
+        let folds = [(0, Action::Fold), (1, Action::Fold)];
+        play(&mut tournament, "KS QD 9C 5H 4S 2C 3D 6C 8D JC 10D", &folds);
+        assert_eq!(tournament.level_index(), 0);
+        // Player 1 has the button, so player 2 posts the small blind.
+        let (hole_cards, board) = deal("KS QD 9C 5H 4S 2C 3D 6C 8D JC 10D");
+        let hand = tournament.start_hand(0, &hole_cards, board).unwrap();
+        assert_eq!(hand.button(), 1);
+        assert!(tournament.start_hand(0, &hole_cards, board).is_err());
+        let mut hand = hand;
+        hand.act(1, Action::Fold).unwrap();
+        hand.act(2, Action::Fold).unwrap();
+        tournament.finish_hand(0, &hand).unwrap();
+        assert_eq!(tournament.level(), LEVELS[1]);
+        // The last level lasts until the end.
+        let (hole_cards, board) = deal("KS QD 9C 5H 4S 2C 3D 6C 8D JC 10D");
+        let hand = tournament.start_hand(0, &hole_cards, board).unwrap();
+        assert_eq!(hand.pot(), 3 * 5 + 20 + 40);
+    }
+
+    #[test]
+    fn test_breaking_and_balancing_tables() {
+        let payouts = PayoutStructure::sit_and_go(6);
+        let mut tournament = Tournament::new(6, 100, 3, &LEVELS, payouts, 0).unwrap();
+        assert_eq!(tournament.players_at(0), vec![0, 2, 4]);
+        assert_eq!(tournament.players_at(1), vec![1, 3, 5]);
+        let bust = |tournament: &mut Tournament, players: &[usize]| {
+            for &player in players {
+                tournament.stacks[player] = 0;
+                for table in tournament.tables.iter_mut() {
+                    for seat in table.seats.iter_mut() {
+                        if *seat == Some(player) {
+                            *seat = None;
+                        }
+                    }
+                }
+            }
+            let all: Vec<usize> = players.to_vec();
+            let starting = vec![100; all.len()];
+            tournament.eliminate(&all, &starting);
+            let mut outcome = HandOutcome::default();
+            tournament.balance(&mut outcome);
+            outcome
+        };
+
+        // Losing two players from one table leaves one against three, so a player moves.
+        let outcome = bust(&mut tournament, &[0, 2]);
+        assert_eq!(outcome.moves.len(), 1);
+        assert_eq!(outcome.moves[0].from_table, 1);
+        assert_eq!(tournament.players_at(0).len(), 2);
+        assert_eq!(tournament.players_at(1).len(), 2);
+
+        // With three players left they fit at one table, so the other is broken.
+        let outcome = bust(&mut tournament, &[1]);
+        assert_eq!(outcome.broken_tables.len(), 1);
+        assert_eq!(tournament.tables().len(), 1);
+        assert_eq!(tournament.players_at(tournament.tables()[0]).len(), 3);
+    }
+
+    #[test]
+    fn test_playing_a_tournament_to_the_end() {
+        let payouts = PayoutStructure::sit_and_go(12);
+        let mut tournament = Tournament::new(12, 200, 6, &LEVELS, payouts, 1200).unwrap();
+        let mut agents: Vec<Box<dyn Agent>> = (0..12)
+            .map(|_| Box::new(Shover) as Box<dyn Agent>)
+            .collect();
+        let standings = tournament.play(&mut agents, 11).unwrap();
+        assert!(tournament.is_complete());
+        assert_eq!(standings.len(), 12);
+        assert_eq!(standings[0].place, 1);
+        assert_eq!(tournament.stack(standings[0].player), 12 * 200);
+        let paid: u64 = standings.iter().map(|standing| standing.prize).sum();
+        assert_eq!(paid, 1200);
+        assert!(tournament.play(&mut agents[..2], 1).is_err());
+    }
+}
@@ -0,0 +1,529 @@
+//! Video poker: paytables, the best cards to hold, and the return of a paytable.
+//!
+//! In video poker the player is dealt five cards, chooses which to hold, and the rest are
+//! replaced from the 47 cards left in the deck. The final hand is paid according to the
+//! machine's paytable. Pays are given per coin bet, using the five coin royal flush pay
+//! divided by five, so a return of 1.0 gives back the bet.
+//!
+//! The expected value of a hold is found exactly, by considering every possible draw.
+//! The return of a paytable is the average, over every possible deal, of the expected
+//! value of the best hold. Rather than solving each deal separately, this adds up the pays
+//! of all the final hands containing each set of up to five cards once, and then works out
+//! the value of each hold with the inclusion-exclusion principle.
+
+use crate::cards::{Card, Ranks};
+use crate::combinatorics::{binomial, DECK_SIZE, HAND_SIZE};
+use crate::error::PokerHandError;
+use crate::evaluator::evaluate_unchecked;
+use crate::poker_hand::PokerHandRanks;
+
+/// The video poker games with a paytable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Game {
+    JacksOrBetter,
+    /// Jacks or Better with extra pays for four aces and four twos, threes or fours.
+    BonusPoker,
+    /// The twos are wild, and the lowest paying hand is three of a kind.
+    DeucesWild,
+}
+
+/// The paying hands. Which ones can occur depends on the game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PayLine {
+    Nothing,
+    /// A pair of jacks, queens, kings or aces.
+    JacksOrBetter,
+    TwoPair,
+    ThreeOfAKind,
+    Straight,
+    Flush,
+    FullHouse,
+    FourOfAKind,
+    /// Four fives through four kings, in Bonus Poker.
+    FourFivesThroughKings,
+    /// Four twos, threes or fours, in Bonus Poker.
+    FourTwosThroughFours,
+    /// Four aces, in Bonus Poker.
+    FourAces,
+    StraightFlush,
+    /// Four of a kind and a wild card, in Deuces Wild.
+    FiveOfAKind,
+    /// A royal flush made with wild cards, in Deuces Wild.
+    WildRoyalFlush,
+    /// All four twos, in Deuces Wild.
+    FourDeuces,
+    /// A royal flush without wild cards.
+    RoyalFlush,
+}
+
+/// A paytable: the game and the pay per coin of each paying hand.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Paytable {
+    pub game: Game,
+    /// The pays, per coin bet. Hands not listed pay nothing.
+    pub pays: Vec<(PayLine, u32)>,
+}
+
+/// The expected value of holding some of the dealt cards.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hold {
+    pub held: Vec<Card>,
+    /// The average pay per coin over every possible draw.
+    pub expected_value: f64,
+}
+
+impl Paytable {
+    /// The "9/6" Jacks or Better paytable, which returns about 99.54%.
+    pub fn jacks_or_better() -> Paytable {
+        Paytable {
+            game: Game::JacksOrBetter,
+            pays: vec![
+                (PayLine::RoyalFlush, 800),
+                (PayLine::StraightFlush, 50),
+                (PayLine::FourOfAKind, 25),
+                (PayLine::FullHouse, 9),
+                (PayLine::Flush, 6),
+                (PayLine::Straight, 4),
+                (PayLine::ThreeOfAKind, 3),
+                (PayLine::TwoPair, 2),
+                (PayLine::JacksOrBetter, 1),
+            ],
+        }
+    }
+
+    /// The "8/5" Bonus Poker paytable, which returns about 99.17%.
+    pub fn bonus_poker() -> Paytable {
+        Paytable {
+            game: Game::BonusPoker,
+            pays: vec![
+                (PayLine::RoyalFlush, 800),
+                (PayLine::StraightFlush, 50),
+                (PayLine::FourAces, 80),
+                (PayLine::FourTwosThroughFours, 40),
+                (PayLine::FourFivesThroughKings, 25),
+                (PayLine::FullHouse, 8),
+                (PayLine::Flush, 5),
+                (PayLine::Straight, 4),
+                (PayLine::ThreeOfAKind, 3),
+                (PayLine::TwoPair, 2),
+                (PayLine::JacksOrBetter, 1),
+            ],
+        }
+    }
+
+    /// The "full pay" Deuces Wild paytable, which returns about 100.76%.
+    pub fn deuces_wild() -> Paytable {
+        Paytable {
+            game: Game::DeucesWild,
+            pays: vec![
+                (PayLine::RoyalFlush, 800),
+                (PayLine::FourDeuces, 200),
+                (PayLine::WildRoyalFlush, 25),
+                (PayLine::FiveOfAKind, 15),
+                (PayLine::StraightFlush, 9),
+                (PayLine::FourOfAKind, 5),
+                (PayLine::FullHouse, 3),
+                (PayLine::Flush, 2),
+                (PayLine::Straight, 2),
+                (PayLine::ThreeOfAKind, 1),
+            ],
+        }
+    }
+
+    /// The pay per coin for a paying hand.
+    pub fn pay(&self, line: PayLine) -> u32 {
+        self.pays
+            .iter()
+            .find(|(l, _)| *l == line)
+            .map_or(0, |(_, pay)| *pay)
+    }
+
+    /// The paying hand made by five cards.
+    pub fn classify(&self, hand: &[Card]) -> Result<PayLine, PokerHandError> {
+        check_hand(hand)?;
+        Ok(classify(self.game, hand))
+    }
+
+    /// The expected value of each of the 32 ways to hold the dealt cards, best first.
+    ///
+    /// Holds with the same expected value are listed with the most cards held first.
+    pub fn analyze_holds(&self, hand: &[Card]) -> Result<Vec<Hold>, PokerHandError> {
+        check_hand(hand)?;
+        let pays = self.pays_by_line();
+        let deck: Vec<Card> = (0..DECK_SIZE)
+            .filter_map(Card::from_index)
+            .filter(|card| !hand.contains(card))
+            .collect();
+
+        let mut holds: Vec<Hold> = (0..32u32)
+            .map(|mask| {
+                let held: Vec<Card> = (0..HAND_SIZE)
+                    .filter(|i| mask & (1 << i) != 0)
+                    .map(|i| hand[i])
+                    .collect();
+                let drawn = HAND_SIZE - held.len();
+                let mut total = 0u64;
+                let mut final_hand = [hand[0]; HAND_SIZE];
+                final_hand[..held.len()].copy_from_slice(&held);
+                for_each_draw(&deck, &mut final_hand, held.len(), 0, &mut |cards| {
+                    total += pays[classify(self.game, cards) as usize] as u64;
+                });
+                let draws = binomial(deck.len(), drawn) as f64;
+                Hold {
+                    held,
+                    expected_value: total as f64 / draws,
+                }
+            })
+            .collect();
+        holds.sort_by(|a, b| {
+            b.expected_value
+                .total_cmp(&a.expected_value)
+                .then(b.held.len().cmp(&a.held.len()))
+        });
+        Ok(holds)
+    }
+
+    /// The best cards to hold, and their expected value.
+    pub fn best_hold(&self, hand: &[Card]) -> Result<Hold, PokerHandError> {
+        Ok(self.analyze_holds(hand)?.swap_remove(0))
+    }
+
+    /// The long run return of the paytable as a percentage of the amount bet, when the
+    /// best cards are always held.
+    ///
+    /// Every one of the 2,598,960 deals is solved exactly, which takes a few seconds in
+    /// an optimised build.
+    pub fn return_percentage(&self) -> f64 {
+        let pays = self.pays_by_line();
+        let binomials = binomial_table();
+        let rank = |cards: &[usize]| -> usize {
+            cards
+                .iter()
+                .enumerate()
+                .map(|(i, &card)| binomials[card][i + 1])
+                .sum()
+        };
+
+        // totals[k][rank of a k card set] is the sum of the pays of all the final hands
+        // containing the set.
+        let mut totals: Vec<Vec<u64>> = (0..=HAND_SIZE)
+            .map(|k| vec![0; binomials[DECK_SIZE][k]])
+            .collect();
+        let mut subset = [0usize; HAND_SIZE];
+        for_each_hand(|indices, cards| {
+            let pay = pays[classify(self.game, cards) as usize] as u64;
+            if pay == 0 {
+                return;
+            }
+            for mask in 0..32usize {
+                let k = select(indices, mask, &mut subset);
+                totals[k][rank(&subset[..k])] += pay;
+            }
+        });
+
+        let mut sum = 0.0;
+        let mut values = [0i64; 32];
+        for_each_hand(|indices, _| {
+            for (mask, value) in values.iter_mut().enumerate() {
+                let k = select(indices, mask, &mut subset);
+                *value = totals[k][rank(&subset[..k])] as i64;
+            }
+            // Remove the final hands that contain any discarded card, so that values[hold]
+            // is the total pay of the final hands which keep exactly the held cards.
+            for bit in 0..HAND_SIZE {
+                for mask in 0..32 {
+                    if mask & (1 << bit) == 0 {
+                        values[mask] -= values[mask | (1 << bit)];
+                    }
+                }
+            }
+            let best = (0..32usize)
+                .map(|mask| {
+                    let drawn = HAND_SIZE - mask.count_ones() as usize;
+                    values[mask] as f64 / binomials[DECK_SIZE - HAND_SIZE][drawn] as f64
+                })
+                .fold(0.0, f64::max);
+            sum += best;
+        });
+        100.0 * sum / binomials[DECK_SIZE][HAND_SIZE] as f64
+    }
+
+    /// The pays indexed by `PayLine as usize`.
+    fn pays_by_line(&self) -> [u32; 16] {
+        let mut pays = [0; 16];
+        for &(line, pay) in &self.pays {
+            pays[line as usize] = pay;
+        }
+        pays
+    }
+}
+
+/// The paying hand made by five distinct cards.
+fn classify(game: Game, hand: &[Card]) -> PayLine {
+    if game == Game::DeucesWild && hand.iter().any(|card| card.rank == Ranks::Two) {
+        return classify_wild(hand);
+    }
+    let value = evaluate_unchecked(hand);
+    let ranks = value.ranks();
+    match value.hand_rank() {
+        PokerHandRanks::StraightFlush if ranks[0] == Ranks::Ace => PayLine::RoyalFlush,
+        PokerHandRanks::StraightFlush => PayLine::StraightFlush,
+        PokerHandRanks::FourOfAKind if game == Game::BonusPoker => match ranks[0] {
+            Ranks::Ace => PayLine::FourAces,
+            Ranks::Two | Ranks::Three | Ranks::Four => PayLine::FourTwosThroughFours,
+            _ => PayLine::FourFivesThroughKings,
+        },
+        PokerHandRanks::FourOfAKind => PayLine::FourOfAKind,
+        PokerHandRanks::FullHouse => PayLine::FullHouse,
+        PokerHandRanks::Flush => PayLine::Flush,
+        PokerHandRanks::Straight => PayLine::Straight,
+        PokerHandRanks::ThreeOfAKind => PayLine::ThreeOfAKind,
+        PokerHandRanks::TwoPair if game != Game::DeucesWild => PayLine::TwoPair,
+        PokerHandRanks::Pair if game != Game::DeucesWild && ranks[0] >= Ranks::Jack => {
+            PayLine::JacksOrBetter
+        }
+        _ => PayLine::Nothing,
+    }
+}
+
+/// The paying hand in Deuces Wild for a hand holding at least one two.
+fn classify_wild(hand: &[Card]) -> PayLine {
+    let deuces = hand.iter().filter(|card| card.rank == Ranks::Two).count();
+    if deuces == 4 {
+        return PayLine::FourDeuces;
+    }
+    let naturals: Vec<Card> = hand
+        .iter()
+        .copied()
+        .filter(|card| card.rank != Ranks::Two)
+        .collect();
+    let mut counts = [0usize; 15];
+    let mut rank_mask = 0u16;
+    for card in &naturals {
+        counts[card.rank as usize] += 1;
+        rank_mask |= 1 << card.rank as u16;
+    }
+    let most = counts.iter().copied().max().unwrap_or(0);
+    let pairs = counts.iter().filter(|&&count| count == 2).count();
+    let suited = naturals.iter().all(|card| card.suit == naturals[0].suit);
+    let distinct = most == 1;
+    // The ace also counts as a one, for the five high straight.
+    if rank_mask & (1 << Ranks::Ace as u16) != 0 {
+        rank_mask |= 1 << 1;
+    }
+    let straight = distinct
+        && (1..=10).any(|low| {
+            let window = 0b11111u16 << low;
+            let natural_ranks = naturals.len() as u32;
+            (rank_mask & window).count_ones() == natural_ranks
+        });
+    let royal = distinct && naturals.iter().all(|card| card.rank >= Ranks::Ten);
+
+    if suited && royal {
+        PayLine::WildRoyalFlush
+    } else if most + deuces >= 5 {
+        PayLine::FiveOfAKind
+    } else if suited && straight {
+        PayLine::StraightFlush
+    } else if most + deuces >= 4 {
+        PayLine::FourOfAKind
+    } else if pairs == 2 {
+        PayLine::FullHouse
+    } else if suited {
+        PayLine::Flush
+    } else if straight {
+        PayLine::Straight
+    } else if most + deuces >= 3 {
+        PayLine::ThreeOfAKind
+    } else {
+        PayLine::Nothing
+    }
+}
+
+fn check_hand(hand: &[Card]) -> Result<(), PokerHandError> {
+    if hand.len() != HAND_SIZE {
+        return Err(PokerHandError::new("A video poker hand has five cards"));
+    }
+    for (i, card) in hand.iter().enumerate() {
+        if hand[i + 1..].contains(card) {
+            return Err(PokerHandError::new("Duplicate cards in hand"));
+        }
+    }
+    Ok(())
+}
+
+/// Fill the rest of the hand, from position `filled`, with each combination of the cards
+/// in the deck from `start` onwards, and call `visit` with each complete hand.
+fn for_each_draw<F>(
+    deck: &[Card],
+    hand: &mut [Card; HAND_SIZE],
+    filled: usize,
+    start: usize,
+    visit: &mut F,
+) where
+    F: FnMut(&[Card]),
+{
+    if filled == HAND_SIZE {
+        visit(&hand[..]);
+        return;
+    }
+    let last = deck.len() + filled + 1 - HAND_SIZE;
+    for i in start..last {
+        hand[filled] = deck[i];
+        for_each_draw(deck, hand, filled + 1, i + 1, visit);
+    }
+}
+
+/// Call `visit` with the card indices, in ascending order, and the cards of every five
+/// card hand.
+fn for_each_hand<F: FnMut(&[usize; HAND_SIZE], &[Card])>(mut visit: F) {
+    let cards: Vec<Card> = (0..DECK_SIZE).filter_map(Card::from_index).collect();
+    let mut hand = [cards[0]; HAND_SIZE];
+    for a in 0..DECK_SIZE {
+        for b in a + 1..DECK_SIZE {
+            for c in b + 1..DECK_SIZE {
+                for d in c + 1..DECK_SIZE {
+                    for e in d + 1..DECK_SIZE {
+                        let indices = [a, b, c, d, e];
+                        for (card, &index) in hand.iter_mut().zip(&indices) {
+                            *card = cards[index];
+                        }
+                        visit(&indices, &hand);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Copy the indices picked out by the mask to the front of `subset`, returning how many.
+fn select(indices: &[usize; HAND_SIZE], mask: usize, subset: &mut [usize; HAND_SIZE]) -> usize {
+    let mut k = 0;
+    for (i, &index) in indices.iter().enumerate() {
+        if mask & (1 << i) != 0 {
+            subset[k] = index;
+            k += 1;
+        }
+    }
+    k
+}
+
+/// binomials[n][k] for n up to the deck size and k up to the hand size.
+fn binomial_table() -> Vec<[usize; HAND_SIZE + 1]> {
+    (0..=DECK_SIZE)
+        .map(|n| {
+            let mut row = [0; HAND_SIZE + 1];
+            for (k, value) in row.iter_mut().enumerate() {
+                *value = binomial(n, k) as usize;
+            }
+            row
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards::parse_cards;
+
+    fn classify_str(paytable: &Paytable, hand: &str) -> PayLine {
+        paytable.classify(&parse_cards(hand).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_classify_jacks_or_better() {
+        let paytable = Paytable::jacks_or_better();
+        assert_eq!(
+            classify_str(&paytable, "AH KH QH JH 10H"),
+            PayLine::RoyalFlush
+        );
+        assert_eq!(
+            classify_str(&paytable, "AH 2H 3H 4H 5H"),
+            PayLine::StraightFlush
+        );
+        assert_eq!(
+            classify_str(&paytable, "JH JS 3D 4C 9H"),
+            PayLine::JacksOrBetter
+        );
+        assert_eq!(
+            classify_str(&paytable, "10H 10S 3D 4C 9H"),
+            PayLine::Nothing
+        );
+        assert_eq!(
+            classify_str(&paytable, "AH AS AD AC 9H"),
+            PayLine::FourOfAKind
+        );
+        assert_eq!(paytable.pay(PayLine::FullHouse), 9);
+        assert_eq!(paytable.pay(PayLine::FourAces), 0);
+    }
+
+    #[test]
+    fn test_classify_bonus_poker() {
+        let paytable = Paytable::bonus_poker();
+        assert_eq!(classify_str(&paytable, "AH AS AD AC 9H"), PayLine::FourAces);
+        assert_eq!(
+            classify_str(&paytable, "3H 3S 3D 3C 9H"),
+            PayLine::FourTwosThroughFours
+        );
+        assert_eq!(
+            classify_str(&paytable, "KH KS KD KC 9H"),
+            PayLine::FourFivesThroughKings
+        );
+    }
+
+    #[test]
+    fn test_classify_deuces_wild() {
+        let paytable = Paytable::deuces_wild();
+        let cases = [
+            ("AH KH QH JH 10H", PayLine::RoyalFlush),
+            ("2H 2S 2D 2C 9H", PayLine::FourDeuces),
+            ("AH KH 2S JH 10H", PayLine::WildRoyalFlush),
+            ("9H 9S 9D 2C 2H", PayLine::FiveOfAKind),
+            ("2C 3H 4H 6H 7H", PayLine::StraightFlush),
+            ("AH 3H 4H 2S 5H", PayLine::StraightFlush),
+            ("9H 9S 2D 2C 4H", PayLine::FourOfAKind),
+            ("9H 9S 4D 4C 2H", PayLine::FullHouse),
+            ("KH 9H 4H 3H 2S", PayLine::Flush),
+            ("2H 3S 4D 6C 7H", PayLine::Straight),
+            ("2H 10S JD QC AH", PayLine::Straight),
+            ("2H 9S 9D 4C 7H", PayLine::ThreeOfAKind),
+            ("2H 9S 8D 4C KH", PayLine::Nothing),
+            ("KH KS 8D 8C 4H", PayLine::Nothing),
+        ];
+        for (hand, line) in cases {
+            assert_eq!(classify_str(&paytable, hand), line, "{}", hand);
+        }
+    }
+
+    #[test]
+    fn test_best_hold() {
+        let paytable = Paytable::jacks_or_better();
+        // Four to a royal flush beats the made flush.
+        let hand = parse_cards("AH KH QH JH 3H").unwrap();
+        let holds = paytable.analyze_holds(&hand).unwrap();
+        let best = &holds[0];
+        assert_eq!(holds.len(), 32);
+        assert_eq!(best.held, hand[..4].to_vec());
+        // Discarding everything draws five of the 47 cards left, with four high cards and
+        // four hearts gone, so it is worth less than the usual 0.36 or so.
+        let discard_all = holds.iter().find(|hold| hold.held.is_empty()).unwrap();
+        assert!(discard_all.expected_value > 0.25 && discard_all.expected_value < 0.32);
+        // A dealt royal flush is held and pays 800.
+        let royal = parse_cards("AS KS QS JS 10S").unwrap();
+        let best = paytable.best_hold(&royal).unwrap();
+        assert_eq!(best.held, royal);
+        assert_eq!(best.expected_value, 800.0);
+        assert!(paytable.best_hold(&royal[..4]).is_err());
+    }
+
+    #[test]
+    #[ignore = "solves every deal; run with --release -- --ignored"]
+    fn test_return_percentages() {
+        let jacks = Paytable::jacks_or_better().return_percentage();
+        assert!((jacks - 99.5439).abs() < 0.001, "{}", jacks);
+        let bonus = Paytable::bonus_poker().return_percentage();
+        assert!((bonus - 99.1660).abs() < 0.001, "{}", bonus);
+        let deuces = Paytable::deuces_wild().return_percentage();
+        assert!((deuces - 100.7620).abs() < 0.001, "{}", deuces);
+    }
+}
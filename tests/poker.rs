@@ -1,4 +1,6 @@
-use poker::winning_hands;
+#![cfg(feature = "std")]
+
+use poker::{ranked_hands, winning_hands};
 use std::collections::HashSet;
 
 fn hs_from<'a>(input: &[&'a str]) -> HashSet<&'a str> {
@@ -14,6 +16,7 @@ fn hs_from<'a>(input: &[&'a str]) -> HashSet<&'a str> {
 ///
 /// Note that the output can be in any order. Here, we use a HashSet to
 /// abstract away the order of outputs.
+#[allow(clippy::needless_lifetimes)]
 fn test<'a, 'b>(input: &[&'a str], expected: &[&'b str]) {
     assert_eq!(
         hs_from(&winning_hands(input).expect("This test should produce Some value",)),
@@ -205,3 +208,22 @@ fn test_straight_flush_ranks() {
     // both hands have straight flush, tie goes to highest-ranked card
     test(&["4H 6H 7H 8H 5H", "5S 7S 8S 9S 6S"], &["5S 7S 8S 9S 6S"])
 }
+
+#[test]
+fn test_ranked_hands_are_tiered() {
+    let hands = [
+        "4D 5S 6S 8D 3C",
+        "2S 4C 7S 9H 10H",
+        "3S 4S 5D 6H JH",
+        "3H 4H 5C 6C JD",
+        "4S 2H 7D 10X AD",
+    ];
+    let tiers = ranked_hands(&hands);
+    assert_eq!(tiers.len(), 3);
+    assert_eq!(
+        hs_from(&tiers[0]),
+        hs_from(&["3S 4S 5D 6H JH", "3H 4H 5C 6C JD"])
+    );
+    assert_eq!(tiers[1], vec!["2S 4C 7S 9H 10H"]);
+    assert_eq!(tiers[2], vec!["4D 5S 6S 8D 3C"]);
+}